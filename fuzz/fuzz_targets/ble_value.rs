@@ -0,0 +1,18 @@
+#![no_main]
+
+// a gatt notification value in each of the ble formats: short values are
+// refused, and whatever decodes must turn into a valid pose or be dropped
+
+use libfuzzer_sys::fuzz_target;
+use spatial_track::ble_input::BleFormat;
+use spatial_track::imu::{self, Mounting};
+
+fuzz_target!(|data: &[u8]| {
+    let mount = Mounting::default();
+    for format in [BleFormat::QuatF32, BleFormat::QuatQ30, BleFormat::EulerF32] {
+        let Some(q) = format.decode(data) else { continue };
+        if let Some(angles) = imu::to_euler(mount.correct(q)) {
+            assert!(angles.iter().all(|a| a.is_finite() && a.abs() <= 180.0), "{:?} led to {:?}", q, angles);
+        }
+    }
+});
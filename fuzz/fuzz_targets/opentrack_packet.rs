@@ -0,0 +1,51 @@
+#![no_main]
+
+// opentrack packets as anyone on the network could send them, through the
+// layout a uri query asks for. decoding must never panic, and what it lets
+// through must keep the filters and the speaker math finite.
+//
+// input: <uri query> 0x00 <packets, 48 bytes each>
+
+use std::time::{Duration, Instant};
+
+use libfuzzer_sys::fuzz_target;
+use spatial_track::engine::{Engine, FilterKind, SpatialState};
+use spatial_track::packet_layout::PacketLayout;
+use spatial_track::relay::OPENTRACK_PACKET_SIZE;
+
+fn is_finite(s: &SpatialState) -> bool {
+    [
+        s.left_az, s.right_az, s.elevation, s.radius, s.gain, s.reverb_gain, s.left_gain, s.right_gain,
+        s.left_shelf_db, s.right_shelf_db, s.stereo_mix, s.crosstalk,
+    ]
+    .iter()
+    .all(|v| v.is_finite())
+}
+
+fuzz_target!(|data: &[u8]| {
+    let (query, packets) = match data.iter().position(|&b| b == 0) {
+        Some(i) => (&data[..i], &data[i + 1..]),
+        None => (&[][..], data),
+    };
+    let query = String::from_utf8_lossy(query);
+    let params: Vec<(&str, &str)> = query.split('&').filter_map(|p| p.split_once('=')).collect();
+    let layout = PacketLayout::from_params(&params).unwrap_or_default();
+
+    for kind in [FilterKind::Ema, FilterKind::OneEuro, FilterKind::Kalman] {
+        let mut engine = Engine::new();
+        engine.smoothed.set_kind(kind);
+        // one packet every 10 ms
+        let mut time = Instant::now();
+        for packet in packets.chunks_exact(OPENTRACK_PACKET_SIZE) {
+            time += Duration::from_millis(10);
+            let Some([_, _, z, yaw, pitch, roll]) = layout.decode(packet.try_into().unwrap()) else {
+                continue;
+            };
+            engine.feed_lean(z);
+            let spatial = engine.feed_pose(yaw, pitch, roll, time);
+            assert!(is_finite(&spatial), "{:?} gave a speaker state that is not finite", [z, yaw, pitch, roll]);
+        }
+        engine.recenter();
+        assert!(is_finite(&engine.spatial()));
+    }
+});
@@ -0,0 +1,25 @@
+#![no_main]
+
+// a witmotion serial stream with garbage, cut packets and bad checksums, in
+// reads of varying size. the parser must resync without panicking, and every
+// orientation it passes on must turn into a valid pose or be dropped.
+//
+// input: <read size> <stream>
+
+use libfuzzer_sys::fuzz_target;
+use spatial_track::imu::{self, Mounting};
+use spatial_track::serial_imu::WitMotionParser;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&size, stream)) = data.split_first() else { return };
+    let mount = Mounting::default();
+    let mut parser = WitMotionParser::new();
+    for chunk in stream.chunks(size.max(1) as usize) {
+        parser.push(chunk, |q| {
+            if let Some(angles) = imu::to_euler(mount.correct(q)) {
+                assert!(angles.iter().all(|a| a.is_finite() && a.abs() <= 180.0), "{:?} led to {:?}", q, angles);
+            }
+            true
+        });
+    }
+});
@@ -0,0 +1,341 @@
+#ifndef SPATIAL_TRACK_H
+#define SPATIAL_TRACK_H
+
+/* Generated with cbindgen, do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Pose smoothing filter, passed as `uint32_t` in `StFilterConfig.filter`.
+ */
+typedef enum StFilterKind {
+  /**
+   * Exponential moving average using `smoothing_factor`.
+   */
+  ST_FILTER_KIND_EMA = 0,
+  /**
+   * One Euro filter: smooth at rest, responsive when moving.
+   */
+  ST_FILTER_KIND_ONE_EURO = 1,
+  /**
+   * Constant velocity Kalman filter.
+   */
+  ST_FILTER_KIND_KALMAN = 2,
+} StFilterKind;
+
+/**
+ * Virtual speaker placement, passed as `uint32_t` in `StFilterConfig.mode`.
+ */
+typedef enum StSpeakerMode {
+  ST_SPEAKER_MODE_FRONT = 0,
+  ST_SPEAKER_MODE_BACK = 1,
+} StSpeakerMode;
+
+/**
+ * What happens to head angles past an axis limit, passed as `uint32_t` in
+ * `StAxisMapping.limit_mode`.
+ */
+typedef enum StLimitMode {
+  /**
+   * Hold at the limit.
+   */
+  ST_LIMIT_MODE_CLAMP = 0,
+  /**
+   * Reflect back from the limit.
+   */
+  ST_LIMIT_MODE_FOLD = 1,
+  /**
+   * Keep the last accepted value for that axis.
+   */
+  ST_LIMIT_MODE_IGNORE = 2,
+} StLimitMode;
+
+/**
+ * What the output is played on, passed as `uint32_t` in `StFilterConfig.listening`.
+ */
+typedef enum StListening {
+  ST_LISTENING_HEADPHONES = 0,
+  /**
+   * Desktop speakers: pan folded to the front and narrowed, optional crosstalk cancellation.
+   */
+  ST_LISTENING_SPEAKERS = 1,
+} StListening;
+
+/**
+ * Opaque engine handle. Create with `st_engine_new`, release with `st_engine_free`.
+ */
+typedef struct StEngine StEngine;
+
+/**
+ * Result of a pose update: where the two virtual speakers sit relative to the head.
+ */
+typedef struct StSpatialState {
+  /**
+   * Left speaker azimuth in degrees.
+   */
+  double left_az;
+  /**
+   * Right speaker azimuth in degrees.
+   */
+  double right_az;
+  /**
+   * Elevation in degrees (positive = above).
+   */
+  double elevation;
+  /**
+   * Speaker distance in meters.
+   */
+  double radius;
+  /**
+   * Volume scaling derived from the radius.
+   */
+  double gain;
+  /**
+   * Reverb wet amount (0.0 - 1.0), dry amount is 1.0 - reverb_gain.
+   */
+  double reverb_gain;
+  /**
+   * Left speaker gain including rear attenuation.
+   */
+  double left_gain;
+  /**
+   * Right speaker gain including rear attenuation.
+   */
+  double right_gain;
+  /**
+   * Left speaker high shelf gain in dB (0 = flat).
+   */
+  double left_shelf_db;
+  /**
+   * Right speaker high shelf gain in dB (0 = flat).
+   */
+  double right_shelf_db;
+  /**
+   * Input cross-mix, 1.0 = untouched stereo, 0.0 = mono to both speakers.
+   */
+  double stereo_mix;
+  /**
+   * Crosstalk cancellation amount (0.0 = off), only used with speakers.
+   */
+  double crosstalk;
+} StSpatialState;
+
+/**
+ * Shaping of one head axis: dead zone, then center magnet, then response curve, then sensitivity.
+ */
+typedef struct StAxisMapping {
+  /**
+   * Degrees around center that are ignored.
+   */
+  double dead_zone;
+  /**
+   * Output multiplier (1.0 = 1:1).
+   */
+  double sensitivity;
+  /**
+   * Response exponent (1.0 = linear).
+   */
+  double curve;
+  /**
+   * Degrees past the dead zone that are gently pulled toward center (0 = off).
+   */
+  double magnet;
+  /**
+   * Largest accepted angle from center in degrees (0 = unlimited).
+   */
+  double limit;
+  /**
+   * One of `StLimitMode`.
+   */
+  uint32_t limit_mode;
+} StAxisMapping;
+
+/**
+ * Filter and speaker configuration. Out-of-range values are clamped, non-finite
+ * values and unknown enum values are rejected.
+ */
+typedef struct StFilterConfig {
+  /**
+   * Exponential smoothing factor (0.0 - 0.99), higher = smoother.
+   */
+  double smoothing_factor;
+  /**
+   * One of `StFilterKind`.
+   */
+  uint32_t filter;
+  /**
+   * Speaker distance in meters.
+   */
+  double radius;
+  /**
+   * Stereo width multiplier (1.0 = default separation).
+   */
+  double width;
+  /**
+   * One of `StSpeakerMode`.
+   */
+  uint32_t mode;
+  bool reverb_enabled;
+  struct StAxisMapping yaw;
+  struct StAxisMapping pitch;
+  struct StAxisMapping roll;
+  /**
+   * Attenuation in dB for a source directly behind the head.
+   */
+  double rear_attenuation_db;
+  /**
+   * High frequency cut in dB for a source directly behind the head.
+   */
+  double rear_rolloff_db;
+  /**
+   * One of `StListening`.
+   */
+  uint32_t listening;
+  /**
+   * Crosstalk cancellation amount for speakers (0.0 - 1.0).
+   */
+  double crosstalk_cancel;
+} StFilterConfig;
+
+/**
+ * State exported for game mods with --game-shm (shared memory) and --game-udp
+ * (one datagram per update). Little-endian, no padding.
+ *
+ * Shared memory readers use `sequence` as a seqlock: it is odd while an update
+ * is being written. Read it, copy the struct, read it again and retry when it
+ * was odd or changed. `magic` is zeroed when spatial-track exits.
+ */
+typedef struct StGameState {
+  /**
+   * 0x53475453, "STGS" in memory.
+   */
+  uint32_t magic;
+  /**
+   * Layout version, currently 1.
+   */
+  uint32_t version;
+  /**
+   * Seqlock counter, odd while an update is being written.
+   */
+  uint32_t sequence;
+  /**
+   * `sizeof(StGameState)`, newer versions only append fields.
+   */
+  uint32_t size;
+  /**
+   * When the pose was received, CLOCK_MONOTONIC in microseconds.
+   */
+  uint64_t timestamp_us;
+  /**
+   * Head position x, y, z in cm as sent by the tracker.
+   */
+  double translation[3];
+  /**
+   * Raw tracker angles in degrees (positive yaw = left, positive pitch = up).
+   */
+  double raw_yaw;
+  double raw_pitch;
+  double raw_roll;
+  /**
+   * Smoothed, recentered angles in degrees.
+   */
+  double yaw;
+  double pitch;
+  double roll;
+  /**
+   * Angular velocity in degrees per second (yaw, pitch, roll).
+   */
+  double velocity[3];
+  /**
+   * Motion intensity, 0.0 = still.
+   */
+  double intensity;
+  /**
+   * What the spatializer is set to.
+   */
+  struct StSpatialState spatial;
+} StGameState;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Allocate a new engine with default settings.
+ */
+struct StEngine *st_engine_new(void);
+
+/**
+ * Free an engine. Passing NULL is a no-op.
+ *
+ * # Safety
+ * `engine` must come from `st_engine_new` and must not be used afterwards.
+ */
+void st_engine_free(struct StEngine *engine);
+
+/**
+ * Feed a raw head pose (degrees) and write the resulting spatial state to `out`.
+ * The first pose after creation or a reset initializes the filter directly.
+ * Returns false if a pointer is NULL or an angle is NaN or infinite, which is then
+ * dropped like the network inputs drop such poses.
+ *
+ * # Safety
+ * `engine` must be a live handle, `out` must be valid for writes.
+ */
+bool st_engine_feed_pose(struct StEngine *engine,
+                         double yaw,
+                         double pitch,
+                         double roll,
+                         struct StSpatialState *out);
+
+/**
+ * Write the spatial state for the current pose and settings without feeding a new pose.
+ *
+ * # Safety
+ * `engine` must be a live handle, `out` must be valid for writes.
+ */
+bool st_engine_get_state(const struct StEngine *engine, struct StSpatialState *out);
+
+/**
+ * Read the current filter configuration into `out`.
+ *
+ * # Safety
+ * `engine` must be a live handle, `out` must be valid for writes.
+ */
+bool st_engine_get_config(const struct StEngine *engine, struct StFilterConfig *out);
+
+/**
+ * Apply a filter configuration. Values are clamped to the same ranges as the TUI.
+ * Returns false, changing nothing, if a pointer is NULL, a value is NaN or
+ * infinite, or an enum field holds an unknown value.
+ *
+ * # Safety
+ * `engine` must be a live handle, `config` must be valid for reads.
+ */
+bool st_engine_set_config(struct StEngine *engine, const struct StFilterConfig *config);
+
+/**
+ * Reset the smoothing filter so it restarts at the next fed pose, keeping the configuration.
+ *
+ * # Safety
+ * `engine` must be a live handle or NULL.
+ */
+void st_engine_reset(struct StEngine *engine);
+
+/**
+ * Treat the most recently fed pose as straight ahead.
+ *
+ * # Safety
+ * `engine` must be a live handle or NULL.
+ */
+void st_engine_recenter(struct StEngine *engine);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SPATIAL_TRACK_H */
@@ -0,0 +1,69 @@
+# rerun.io sink for spatial-track --rerun.
+#
+# spatial-track starts this with python3 and writes one whitespace separated
+# record per line on stdin:
+#   pose  <t> <raw yaw> <raw pitch> <raw roll> <smooth yaw> <smooth pitch> <smooth roll>
+#   audio <t> <left az> <right az> <elevation> <latency ms>
+# t is seconds since start. needs `pip install rerun-sdk`.
+
+import math
+import sys
+
+import rerun as rr
+
+rr.init("spatial-track")
+if len(sys.argv) > 1:
+    rr.save(sys.argv[1])
+else:
+    rr.spawn()
+
+rr.log("head", rr.ViewCoordinates.RIGHT_HAND_Z_UP, static=True)
+
+
+def set_time(t):
+    if hasattr(rr, "set_time"):
+        rr.set_time("time", duration=t)
+    else:
+        rr.set_time_seconds("time", t)
+
+
+def scalar(path, value):
+    if hasattr(rr, "Scalars"):
+        rr.log(path, rr.Scalars(value))
+    else:
+        rr.log(path, rr.Scalar(value))
+
+
+def facing(yaw, pitch):
+    # x forward, y left, z up; positive yaw looks left, positive pitch up
+    y, p = math.radians(yaw), math.radians(pitch)
+    return [math.cos(p) * math.cos(y), math.cos(p) * math.sin(y), math.sin(p)]
+
+
+for line in sys.stdin:
+    fields = line.split()
+    if len(fields) < 2:
+        continue
+    try:
+        kind, t, values = fields[0], float(fields[1]), [float(v) for v in fields[2:]]
+    except ValueError:
+        continue
+    set_time(t)
+
+    if kind == "pose" and len(values) == 6:
+        for name, raw, smooth in zip(("yaw", "pitch", "roll"), values[:3], values[3:]):
+            scalar(f"orientation/{name}/raw", raw)
+            scalar(f"orientation/{name}/smoothed", smooth)
+        rr.log(
+            "head/facing",
+            rr.Arrows3D(
+                vectors=[facing(values[0], values[1]), facing(values[3], values[4])],
+                colors=[[128, 128, 128], [0, 200, 255]],
+                labels=["raw", "smoothed"],
+            ),
+        )
+    elif kind == "audio" and len(values) == 4:
+        scalar("pan/left_az", values[0])
+        scalar("pan/right_az", values[1])
+        scalar("pan/elevation", values[2])
+        scalar("latency_ms", values[3])
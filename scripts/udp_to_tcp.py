@@ -0,0 +1,102 @@
+# forward opentrack "UDP over network" output to spatial-track's tcp input.
+#
+#   python3 scripts/udp_to_tcp.py [--key <file>] <tcp host:port> [udp listen port]
+#
+# run it next to opentrack and start spatial-track with
+# --input opentrack-tcp://0.0.0.0:4242. every udp packet is sent as a
+# u32 big-endian length followed by the packet; an empty frame goes out
+# every second as a keep-alive. when the connection drops it reconnects,
+# waiting 1s and doubling up to 30s.
+#
+# with --key the frames are encrypted for
+# --input 'opentrack-noise://0.0.0.0:4242?key=<file>' using the same key
+# file (64 hex digits). needs `pip install noiseprotocol`.
+
+import socket
+import struct
+import sys
+import time
+
+KEEPALIVE = 1.0
+NOISE_PATTERN = b"Noise_NNpsk0_25519_ChaChaPoly_BLAKE2s"
+
+args = sys.argv[1:]
+psk = None
+if args[:1] == ["--key"]:
+    with open(args[1]) as f:
+        psk = bytes.fromhex(f.read().strip())
+    args = args[2:]
+
+host, _, port = args[0].rpartition(":")
+target = (host or "127.0.0.1", int(port))
+udp_port = int(args[1]) if len(args) > 1 else 4242
+
+
+def send_frame(conn, payload):
+    conn.sendall(struct.pack(">I", len(payload)) + payload)
+
+
+def recv_frame(conn):
+    header = recv_exact(conn, 4)
+    return recv_exact(conn, struct.unpack(">I", header)[0])
+
+
+def recv_exact(conn, size):
+    data = b""
+    while len(data) < size:
+        chunk = conn.recv(size - len(data))
+        if not chunk:
+            raise OSError("connection closed during handshake")
+        data += chunk
+    return data
+
+
+# returns a function that turns a plaintext payload into a frame payload
+def handshake(conn):
+    if psk is None:
+        return lambda payload: payload
+
+    from noise.connection import NoiseConnection
+
+    noise = NoiseConnection.from_name(NOISE_PATTERN)
+    noise.set_as_initiator()
+    noise.set_psks(psk=psk)
+    noise.start_handshake()
+    send_frame(conn, noise.write_message())
+    noise.read_message(recv_frame(conn))
+    return noise.encrypt
+
+udp = socket.socket(socket.AF_INET, socket.SOCK_DGRAM)
+udp.bind(("127.0.0.1", udp_port))
+udp.settimeout(KEEPALIVE)
+
+backoff = 1.0
+while True:
+    try:
+        conn = socket.create_connection(target, timeout=5)
+    except OSError as e:
+        print(f"connect to {target[0]}:{target[1]} failed: {e}, retrying in {backoff:.0f}s", file=sys.stderr)
+        time.sleep(backoff)
+        backoff = min(backoff * 2, 30.0)
+        continue
+
+    print(f"connected to {target[0]}:{target[1]}", file=sys.stderr)
+    conn.setsockopt(socket.IPPROTO_TCP, socket.TCP_NODELAY, 1)
+    conn.setsockopt(socket.SOL_SOCKET, socket.SO_KEEPALIVE, 1)
+    backoff = 1.0
+    last_sent = time.monotonic()
+    try:
+        seal = handshake(conn)
+        while True:
+            try:
+                packet, _ = udp.recvfrom(1024)
+                send_frame(conn, seal(packet))
+                last_sent = time.monotonic()
+            except socket.timeout:
+                pass
+            if time.monotonic() - last_sent >= KEEPALIVE:
+                send_frame(conn, seal(b""))
+                last_sent = time.monotonic()
+    except OSError as e:
+        print(f"connection lost: {e}", file=sys.stderr)
+        conn.close()
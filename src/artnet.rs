@@ -0,0 +1,140 @@
+// art-net (dmx over udp) output so stage/streaming lights can follow the head.
+// writes 16-bit pan and tilt the way moving heads expect them:
+//   start+0 pan coarse, start+1 pan fine, start+2 tilt coarse, start+3 tilt fine
+
+use std::fmt;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use crate::sink::{Frame, OutputSink};
+
+pub const ARTNET_PORT: u16 = 6454;
+
+// head angle mapped to the full dmx range (center = 50%)
+pub const ARTNET_YAW_RANGE: f64 = 90.0;
+pub const ARTNET_PITCH_RANGE: f64 = 45.0;
+
+const ARTNET_ID: &[u8; 8] = b"Art-Net\0";
+const OP_DMX: u16 = 0x5000;
+const PROTOCOL_VERSION: u16 = 14;
+const DMX_CHANNELS: usize = 512;
+const HEADER_SIZE: usize = 18;
+const CHANNELS_USED: usize = 4;
+
+pub struct ArtNetOutput {
+    socket: UdpSocket,
+    target: SocketAddr,
+    universe: u16,
+    start_channel: usize, // 1-based like on a lighting desk
+    sequence: u8,
+    packet: Vec<u8>,
+    pub sent: u64,
+}
+
+impl ArtNetOutput {
+    // target is "host", "host:port", an ipv6 address or "[ipv6]:port", universe is the 15-bit port address
+    pub fn new(target: &str, universe: u16, start_channel: usize) -> Result<Self, String> {
+        if universe > 0x7FFF {
+            return Err(format!("art-net universe {} out of range (0-32767)", universe));
+        }
+        // checked: the channel comes straight from the command line
+        let end = start_channel.checked_add(CHANNELS_USED - 1);
+        if start_channel == 0 || end.is_none_or(|end| end > DMX_CHANNELS) {
+            return Err(format!("art-net start channel {} out of range (1-{})", start_channel, DMX_CHANNELS - CHANNELS_USED + 1));
+        }
+
+        let with_port = with_default_port(target, ARTNET_PORT);
+        let target = with_port
+            .to_socket_addrs()
+            .map_err(|e| format!("invalid art-net target '{}': {}", with_port, e))?
+            .next()
+            .ok_or_else(|| format!("art-net target '{}' did not resolve", with_port))?;
+
+        let bind = if target.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(bind).map_err(|e| format!("Failed to bind art-net socket: {}", e))?;
+        // nodes are commonly addressed by subnet broadcast (2.255.255.255 etc)
+        socket.set_broadcast(true).ok();
+        socket.set_nonblocking(true).ok();
+
+        // only send as many channels as needed, rounded up to an even length
+        let length = (start_channel - 1 + CHANNELS_USED).next_multiple_of(2);
+        let mut packet = Vec::with_capacity(HEADER_SIZE + length);
+        packet.extend_from_slice(ARTNET_ID);
+        packet.extend_from_slice(&OP_DMX.to_le_bytes());
+        packet.extend_from_slice(&PROTOCOL_VERSION.to_be_bytes());
+        packet.push(0); // sequence, filled per frame
+        packet.push(0); // physical input port
+        packet.push((universe & 0xFF) as u8); // sub-net + universe
+        packet.push((universe >> 8) as u8); // net
+        packet.extend_from_slice(&(length as u16).to_be_bytes());
+        packet.resize(HEADER_SIZE + length, 0);
+
+        Ok(Self { socket, target, universe, start_channel, sequence: 0, packet, sent: 0 })
+    }
+
+    pub fn target(&self) -> SocketAddr {
+        self.target
+    }
+
+    pub fn universe(&self) -> u16 {
+        self.universe
+    }
+
+    pub fn start_channel(&self) -> usize {
+        self.start_channel
+    }
+
+    // feed smoothed yaw/pitch in degrees
+    pub fn send_pan_tilt(&mut self, yaw: f64, pitch: f64) {
+        let pan = to_dmx16(yaw / ARTNET_YAW_RANGE);
+        let tilt = to_dmx16(pitch / ARTNET_PITCH_RANGE);
+
+        // sequence 0 means "disabled", so wrap 1..=255
+        self.sequence = self.sequence.wrapping_add(1).max(1);
+        self.packet[12] = self.sequence;
+
+        let at = HEADER_SIZE + self.start_channel - 1;
+        self.packet[at..at + 2].copy_from_slice(&pan.to_be_bytes());
+        self.packet[at + 2..at + 4].copy_from_slice(&tilt.to_be_bytes());
+
+        if self.socket.send_to(&self.packet, self.target).is_ok() {
+            self.sent += 1;
+        }
+    }
+}
+
+impl OutputSink for ArtNetOutput {
+    fn name(&self) -> &'static str {
+        "artnet"
+    }
+
+    fn send(&mut self, frame: &Frame) {
+        self.send_pan_tilt(frame.smoothed[0], frame.smoothed[1]);
+    }
+
+    fn status(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "\x1B[1;37mArt-Net:\x1B[0m → {} U{} ch{}  sent \x1B[1;37m{}\x1B[0m",
+            self.target, self.universe, self.start_channel, self.sent
+        )
+    }
+}
+
+// -1..1 -> 0..65535 with center at 32768
+fn to_dmx16(normalized: f64) -> u16 {
+    let n = if normalized.is_finite() { normalized.clamp(-1.0, 1.0) } else { 0.0 };
+    ((n + 1.0) / 2.0 * u16::MAX as f64).round() as u16
+}
+
+// "2.0.0.1" -> "2.0.0.1:6454", "fe80::1" -> "[fe80::1]:6454", "[fe80::1]:6455" stays
+fn with_default_port(target: &str, port: u16) -> String {
+    // a colon outside of brackets is the port, more than one is a bare ipv6 address
+    let has_port = target.rsplit_once(':').is_some_and(|(host, _)| host.ends_with(']') || !host.contains(':'));
+    if has_port {
+        target.to_string()
+    } else if target.contains(':') && !target.starts_with('[') {
+        format!("[{}]:{}", target, port)
+    } else {
+        format!("{}:{}", target, port)
+    }
+}
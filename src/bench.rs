@@ -0,0 +1,198 @@
+// `spatial-track bench [frames]`: time the per-update work without a tracker,
+// pipewire or terminal, and count the heap allocations it makes when built
+// with the count-allocations feature. synthetic poses go through the engine,
+// the pipewire parameters are built, the frame goes to the sinks (a zmq
+// publisher nobody subscribed to, the common case) and the dashboard is
+// rendered with its mouse and input rows and cut to a terminal, the same steps
+// the main loop runs for every update. sending the parameters is not
+// included: that spawns pw-cli, which needs pipewire and costs a fork and
+// exec, well beyond what is measured here. on a small board every allocation
+// and microsecond here is cpu time taken from the audio.
+
+use std::hint::black_box;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use spatial_track::calibration::Calibrator;
+use spatial_track::engine::{Engine, OutputSmoother};
+use spatial_track::history::RateHistory;
+use spatial_track::input::Pose;
+use spatial_track::motion::MotionTracker;
+use spatial_track::sink::{Frame, Sinks};
+use spatial_track::smoothing_tune::SmoothingTuner;
+use spatial_track::volume_guard::VolumeGuard;
+use spatial_track::zmq_pub::ZmqPublisher;
+
+use crate::dashboard::{render_dashboard, Screen};
+use crate::pipewire::{write_params, PipewireSink, VolumeControl};
+use crate::theme::Theme;
+use crate::{SPATIALIZER_NODE_NAME, UPDATE_RATE_MS};
+
+pub const DEFAULT_FRAMES: u64 = 10_000;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+// counts every allocation of the process, a relaxed add next to the
+// allocator's own work. only installed with the count-allocations feature,
+// otherwise the count stays 0
+#[cfg(feature = "count-allocations")]
+mod counting {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::Ordering;
+
+    use super::ALLOCATIONS;
+
+    struct CountingAllocator;
+
+    // SAFETY: every call is passed on to the system allocator unchanged
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            System.alloc_zeroed(layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            System.realloc(ptr, layout, new_size)
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAllocator = CountingAllocator;
+}
+
+// time and allocations of one stage, summed over all frames
+#[derive(Default)]
+struct Stage {
+    time: Duration,
+    allocations: u64,
+}
+
+impl Stage {
+    fn measure<T>(&mut self, f: impl FnOnce() -> T) -> T {
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        let result = f();
+        self.time += start.elapsed();
+        self.allocations += ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+        result
+    }
+
+    fn report(&self, name: &str, frames: u64) {
+        let time = self.time.as_secs_f64() * 1e6 / frames as f64;
+        if cfg!(feature = "count-allocations") {
+            println!("  {:<10} {:>8.2} µs/frame  {:>7.2} allocations/frame", name, time, self.allocations as f64 / frames as f64);
+        } else {
+            println!("  {:<10} {:>8.2} µs/frame", name, time);
+        }
+    }
+}
+
+pub fn run(frames: u64) {
+    let start = Instant::now();
+    let mut engine = Engine::new();
+    let mut motion = MotionTracker::new();
+    let mut output_smoother = OutputSmoother::new(0.05);
+    let mut volume_guard = VolumeGuard::new(1.0, 6.0);
+    let mut packet_rate = RateHistory::new(start);
+    let mut update_rate = RateHistory::new(start);
+    let calibrator = Calibrator::new();
+    let tuner = SmoothingTuner::new();
+    // an unlinked pipewire sink, so its status line is rendered but nothing runs
+    let mut sinks = Sinks::new();
+    sinks.add(Box::new(PipewireSink::new(SPATIALIZER_NODE_NAME, VolumeControl::Filter)), Duration::ZERO);
+    match ZmqPublisher::bind("tcp://127.0.0.1:0") {
+        Ok(publisher) => sinks.add(Box::new(publisher), Duration::ZERO),
+        Err(e) => println!("no zmq publisher in the sinks stage: {}", e),
+    }
+    let mut params = String::new();
+    let mut screen = Screen::new(Theme::Default);
+    let mut text = String::new();
+
+    let (mut engine_stage, mut pipewire_stage) = (Stage::default(), Stage::default());
+    let (mut sinks_stage, mut dashboard_stage) = (Stage::default(), Stage::default());
+    let frame = Duration::from_millis(UPDATE_RATE_MS);
+    for i in 0..frames {
+        // a slow turn with some nodding, one update per frame interval
+        let now = start + frame * i as u32;
+        let t = now.duration_since(start).as_secs_f64();
+        let (yaw, pitch, roll) = (60.0 * (t * 0.5).sin(), 15.0 * (t * 1.3).sin(), 3.0 * (t * 0.7).sin());
+        packet_rate.record(now);
+        update_rate.record(now);
+
+        let spatial = engine_stage.measure(|| {
+            motion.update([yaw, pitch, roll], now);
+            engine.feed_pose(yaw, pitch, roll, now);
+            let mut spatial = output_smoother.update(&engine.spatial(), frame.as_secs_f64());
+            volume_guard.apply(&mut spatial, now);
+            spatial
+        });
+        pipewire_stage.measure(|| write_params(&mut params, &spatial));
+        sinks_stage.measure(|| {
+            let pose = Pose { translation: [0.0; 3], yaw, pitch, roll, time: now };
+            sinks.send(&Frame {
+                pose: &pose,
+                smoothed: [yaw, pitch, roll],
+                motion: motion.motion,
+                spatial: &spatial,
+                force: false,
+                latency_ms: 0.0,
+            });
+        });
+        dashboard_stage.measure(|| {
+            render_dashboard(
+                &mut screen,
+                &engine,
+                yaw,
+                pitch,
+                roll,
+                &spatial,
+                &motion.motion,
+                50.0,
+                0.0,
+                i,
+                &packet_rate,
+                &update_rate,
+                &calibrator,
+                &tuner,
+                None,
+                Some("\x1B[1;32mACTIVE\x1B[0m (gain 40/°)"),
+                None,
+                &[],
+                &sinks,
+                None,
+                None,
+                None,
+                None,
+                Some(("demo scene 'orbit' (no tracker needed) (1/2)", None)),
+                None,
+                None,
+                None,
+                None,
+                &volume_guard,
+                Theme::Default,
+            );
+            screen.fit_into(120, 60, &mut text);
+        });
+        black_box((&params, &text));
+    }
+
+    println!("spatial-track bench: {} frames", frames);
+    engine_stage.report("engine", frames);
+    // the json only, see the top of this file
+    pipewire_stage.report("pw params", frames);
+    sinks_stage.report("sinks", frames);
+    dashboard_stage.report("dashboard", frames);
+    if !cfg!(feature = "count-allocations") {
+        println!("(build with --features count-allocations to count allocations)");
+    }
+}
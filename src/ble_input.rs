@@ -0,0 +1,441 @@
+// bluetooth le imu trackers (esp32/nrf diy boards, nordic thingy:52) without
+// opentrack and a serial bridge in between:
+//
+//   ble://<mac>?char=<uuid>[&format=quat-f32|quat-q30|euler-f32][&addr=public|random][&mount=..]
+//
+// the tracker is a gatt peripheral that notifies its orientation on one
+// characteristic. we talk ATT over an l2cap socket to it directly, the way
+// gatttool does, instead of going through bluez's d-bus api: find the
+// characteristic by uuid, enable notifications in its client configuration
+// descriptor and turn every notification into a pose. a background thread
+// owns the connection and reconnects when the tracker goes away, so the
+// tracker may be switched on after spatial-track.
+//
+// notification formats, all little-endian:
+//   quat-f32   4 x f32 w, x, y, z (most esp32 sketches)
+//   quat-q30   4 x i32 w, x, y, z fixed point with 30 fractional bits
+//              (thingy:52 motion service, ef680404-9b35-4933-9b10-52ffa9740042)
+//   euler-f32  3 x f32 yaw, pitch, roll in degrees
+// mount corrects a sensor that is not upright, see imu::Mounting.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::mem;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::imu::{self, Mounting, Quaternion};
+use crate::input::{InputSource, Pose};
+
+// from bluetooth/bluetooth.h and bluetooth/l2cap.h
+const BTPROTO_L2CAP: libc::c_int = 0;
+const ATT_CID: u16 = 4;
+const BDADDR_LE_PUBLIC: u8 = 1;
+const BDADDR_LE_RANDOM: u8 = 2;
+
+// ATT opcodes (bluetooth core spec vol 3 part F)
+const ATT_ERROR_RSP: u8 = 0x01;
+const ATT_EXCHANGE_MTU_REQ: u8 = 0x02;
+const ATT_EXCHANGE_MTU_RSP: u8 = 0x03;
+const ATT_FIND_INFO_REQ: u8 = 0x04;
+const ATT_FIND_INFO_RSP: u8 = 0x05;
+const ATT_READ_BY_TYPE_REQ: u8 = 0x08;
+const ATT_READ_BY_TYPE_RSP: u8 = 0x09;
+const ATT_WRITE_REQ: u8 = 0x12;
+const ATT_WRITE_RSP: u8 = 0x13;
+const ATT_NOTIFICATION: u8 = 0x1B;
+const ATT_INDICATION: u8 = 0x1D;
+const ATT_CONFIRMATION: u8 = 0x1E;
+const ATT_ERR_REQUEST_NOT_SUPPORTED: u8 = 0x06;
+const ATT_ERR_ATTRIBUTE_NOT_FOUND: u8 = 0x0A;
+
+const GATT_CHARACTERISTIC: u16 = 0x2803;
+const GATT_CLIENT_CONFIG: u16 = 0x2902;
+const PROP_NOTIFY: u8 = 0x10;
+const PROP_INDICATE: u8 = 0x20;
+
+const ATT_MTU: u16 = 247;
+// how long the tracker gets to answer one request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BleFormat {
+    QuatF32,
+    QuatQ30,
+    EulerF32,
+}
+
+impl BleFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "quat-f32" => Some(BleFormat::QuatF32),
+            "quat-q30" => Some(BleFormat::QuatQ30),
+            "euler-f32" => Some(BleFormat::EulerF32),
+            _ => None,
+        }
+    }
+
+    // the sensor orientation, None for a short value
+    pub fn decode(&self, value: &[u8]) -> Option<Quaternion> {
+        let f32_at = |i: usize| Some(f32::from_le_bytes(value.get(i * 4..i * 4 + 4)?.try_into().ok()?) as f64);
+        let q30_at = |i: usize| {
+            Some(i32::from_le_bytes(value.get(i * 4..i * 4 + 4)?.try_into().ok()?) as f64 / (1u32 << 30) as f64)
+        };
+        match self {
+            BleFormat::QuatF32 => Some([f32_at(0)?, f32_at(1)?, f32_at(2)?, f32_at(3)?]),
+            BleFormat::QuatQ30 => Some([q30_at(0)?, q30_at(1)?, q30_at(2)?, q30_at(3)?]),
+            BleFormat::EulerF32 => Some(imu::from_euler(f32_at(0)?, f32_at(1)?, f32_at(2)?)),
+        }
+    }
+}
+
+// "ef680404-9b35-4933-9b10-52ffa9740042" or a 16 bit "2a19", as the 16
+// little-endian bytes ATT uses
+fn parse_uuid(text: &str) -> Option<[u8; 16]> {
+    let hex: String = text.trim_start_matches("0x").chars().filter(|&c| c != '-').collect();
+    let full = match hex.len() {
+        4 => format!("0000{}00001000800000805f9b34fb", hex),
+        32 => hex,
+        _ => return None,
+    };
+    let mut uuid = [0u8; 16];
+    for (i, byte) in uuid.iter_mut().rev().enumerate() {
+        *byte = u8::from_str_radix(full.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    Some(uuid)
+}
+
+// a 16 bit uuid within the bluetooth base uuid 0000xxxx-0000-1000-8000-00805f9b34fb
+fn short_uuid(uuid: u16) -> [u8; 16] {
+    let [lo, hi] = uuid.to_le_bytes();
+    [0xFB, 0x34, 0x9B, 0x5F, 0x80, 0x00, 0x00, 0x80, 0x00, 0x10, 0x00, 0x00, lo, hi, 0x00, 0x00]
+}
+
+// "AA:BB:CC:DD:EE:FF" -> bdaddr_t, which stores the bytes reversed
+fn parse_mac(text: &str) -> Option<[u8; 6]> {
+    let parts: Vec<u8> = text.split(':').map(|p| u8::from_str_radix(p, 16).ok()).collect::<Option<_>>()?;
+    let mut addr: [u8; 6] = parts.try_into().ok()?;
+    addr.reverse();
+    Some(addr)
+}
+
+#[repr(C)]
+struct SockaddrL2 {
+    family: libc::sa_family_t,
+    psm: u16,
+    bdaddr: [u8; 6],
+    cid: u16,
+    bdaddr_type: u8,
+}
+
+struct Target {
+    mac: String,
+    bdaddr: [u8; 6],
+    bdaddr_type: u8,
+    characteristic: [u8; 16],
+    format: BleFormat,
+    mount: Mounting,
+}
+
+pub struct BleInput {
+    uri: String,
+    mac: String,
+    poses: Receiver<Pose>,
+    status: Arc<Mutex<String>>,
+}
+
+impl BleInput {
+    // `rest` is everything after "ble://"
+    pub fn new(uri: &str, rest: &str) -> Result<Self, String> {
+        let (mac, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let bdaddr = parse_mac(mac).ok_or_else(|| format!("invalid bluetooth address '{}' in '{}'", mac, uri))?;
+        let (mut characteristic, mut format, mut bdaddr_type) = (None, BleFormat::QuatF32, BDADDR_LE_PUBLIC);
+        let mut mount = Mounting::default();
+        for param in query.split('&').filter(|p| !p.is_empty()) {
+            match param.split_once('=') {
+                Some(("char", uuid)) => {
+                    characteristic = Some(parse_uuid(uuid).ok_or_else(|| format!("invalid characteristic uuid '{}'", uuid))?)
+                }
+                Some(("format", name)) => {
+                    format = BleFormat::parse(name)
+                        .ok_or_else(|| format!("unknown ble format '{}' (quat-f32, quat-q30, euler-f32)", name))?
+                }
+                Some(("addr", "public")) => bdaddr_type = BDADDR_LE_PUBLIC,
+                Some(("addr", "random")) => bdaddr_type = BDADDR_LE_RANDOM,
+                Some(("mount", spec)) => mount = Mounting::parse(spec).ok_or_else(|| format!("invalid mount '{}'", spec))?,
+                _ => return Err(format!("unknown ble parameter '{}' in '{}'", param, uri)),
+            }
+        }
+        let characteristic =
+            characteristic.ok_or_else(|| format!("input '{}' needs the imu characteristic, e.g. {}?char=<uuid>", uri, uri))?;
+
+        let target = Target { mac: mac.to_uppercase(), bdaddr, bdaddr_type, characteristic, format, mount };
+        let status = Arc::new(Mutex::new("connecting".to_string()));
+        let (tx, poses) = mpsc::channel();
+        let thread_status = Arc::clone(&status);
+        thread::spawn(move || run(target, tx, thread_status));
+        Ok(Self { uri: uri.to_string(), mac: mac.to_uppercase(), poses, status })
+    }
+}
+
+impl InputSource for BleInput {
+    fn recv(&mut self, timeout: Duration) -> Result<Option<Pose>, String> {
+        let pose = if timeout.is_zero() {
+            match self.poses.try_recv() {
+                Ok(pose) => Some(pose),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => return Err("ble input thread stopped".to_string()),
+            }
+        } else {
+            match self.poses.recv_timeout(timeout) {
+                Ok(pose) => Some(pose),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return Err("ble input thread stopped".to_string()),
+            }
+        };
+        Ok(pose)
+    }
+
+    fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    fn describe(&self) -> String {
+        let status = self.status.lock().map(|s| s.clone()).unwrap_or_default();
+        format!("BLE {} ({})", self.mac, status)
+    }
+}
+
+// connect, subscribe and forward poses until the receiver is gone
+fn run(target: Target, poses: Sender<Pose>, status: Arc<Mutex<String>>) {
+    let set_status = |text: String| {
+        if let Ok(mut s) = status.lock() {
+            *s = text;
+        }
+    };
+    loop {
+        let result = connect(&target).and_then(|mut socket| {
+            let handle = subscribe(&mut socket, &target.characteristic)?;
+            set_status("connected".to_string());
+            receive(&mut socket, handle, &target, &poses)
+        });
+        match result {
+            // the input was dropped
+            Ok(()) => return,
+            Err(e) => set_status(e),
+        }
+        thread::sleep(RECONNECT_DELAY);
+    }
+}
+
+fn connect(target: &Target) -> Result<File, String> {
+    // SAFETY: plain syscall without pointers, the result is checked below
+    let fd = unsafe { libc::socket(libc::AF_BLUETOOTH, libc::SOCK_SEQPACKET | libc::SOCK_CLOEXEC, BTPROTO_L2CAP) };
+    if fd < 0 {
+        return Err(format!("Failed to open bluetooth socket: {}", io::Error::last_os_error()));
+    }
+    // owned from here on, closed on every error path
+    // SAFETY: fd is a freshly opened socket that nothing else owns
+    let socket = unsafe { File::from_raw_fd(fd) };
+
+    let local = SockaddrL2 {
+        family: libc::AF_BLUETOOTH as libc::sa_family_t,
+        psm: 0,
+        bdaddr: [0; 6],
+        cid: ATT_CID.to_le(),
+        bdaddr_type: BDADDR_LE_PUBLIC,
+    };
+    let remote = SockaddrL2 { bdaddr: target.bdaddr, bdaddr_type: target.bdaddr_type, ..local };
+    let len = mem::size_of::<SockaddrL2>() as libc::socklen_t;
+    // SAFETY: local and remote are repr(C) sockaddr_l2 structs of the length
+    // passed that outlive the calls, and fd stays open as long as socket lives
+    unsafe {
+        if libc::bind(fd, &local as *const SockaddrL2 as *const libc::sockaddr, len) < 0 {
+            return Err(format!("Failed to bind bluetooth socket: {}", io::Error::last_os_error()));
+        }
+        if libc::connect(fd, &remote as *const SockaddrL2 as *const libc::sockaddr, len) < 0 {
+            return Err(format!("Failed to connect to {}: {}", target.mac, io::Error::last_os_error()));
+        }
+    }
+    set_read_timeout(&socket, Some(REQUEST_TIMEOUT))?;
+    Ok(socket)
+}
+
+fn set_read_timeout(socket: &File, timeout: Option<Duration>) -> Result<(), String> {
+    let timeout = timeout.unwrap_or_default();
+    let tv = libc::timeval { tv_sec: timeout.as_secs() as libc::time_t, tv_usec: timeout.subsec_micros() as libc::suseconds_t };
+    let tv_len = mem::size_of::<libc::timeval>() as libc::socklen_t;
+    // SAFETY: the fd belongs to the live socket and tv is a timeval of the size passed
+    let result = unsafe {
+        libc::setsockopt(socket.as_raw_fd(), libc::SOL_SOCKET, libc::SO_RCVTIMEO, &tv as *const _ as *const libc::c_void, tv_len)
+    };
+    if result < 0 {
+        return Err(format!("Failed to configure bluetooth socket: {}", io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+// find the characteristic, turn on its notifications (or indications), and
+// return its value handle
+fn subscribe<S: Read + Write>(socket: &mut S, characteristic: &[u8; 16]) -> Result<u16, String> {
+    // a bigger mtu lets longer values through, keep the default if refused
+    request(socket, &[&[ATT_EXCHANGE_MTU_REQ][..], &ATT_MTU.to_le_bytes()].concat(), ATT_EXCHANGE_MTU_RSP).ok();
+
+    // walk the characteristic declarations: [handle, properties, value handle, uuid]
+    let (mut start, mut found) = (1u16, None);
+    let mut range_end = 0xFFFF;
+    'discover: loop {
+        let mut req = vec![ATT_READ_BY_TYPE_REQ];
+        req.extend_from_slice(&start.to_le_bytes());
+        req.extend_from_slice(&0xFFFFu16.to_le_bytes());
+        req.extend_from_slice(&GATT_CHARACTERISTIC.to_le_bytes());
+        let rsp = match request(socket, &req, ATT_READ_BY_TYPE_RSP)? {
+            Some(rsp) => rsp,
+            None => break,
+        };
+        let entry_len = *rsp.get(1).ok_or("Failed to discover characteristics: empty response")? as usize;
+        if entry_len < 7 {
+            return Err("Failed to discover characteristics: bad response".to_string());
+        }
+        let mut last = start;
+        for entry in rsp[2..].chunks_exact(entry_len) {
+            let handle = u16::from_le_bytes([entry[0], entry[1]]);
+            last = handle;
+            if found.is_some() {
+                // the next declaration ends the found characteristic
+                range_end = handle.saturating_sub(1);
+                break 'discover;
+            }
+            let uuid = match entry.len() {
+                7 => short_uuid(u16::from_le_bytes([entry[5], entry[6]])),
+                21 => entry[5..21].try_into().unwrap_or_default(),
+                _ => continue,
+            };
+            if &uuid == characteristic {
+                found = Some((entry[2], u16::from_le_bytes([entry[3], entry[4]])));
+            }
+        }
+        if last == 0xFFFF {
+            break;
+        }
+        start = last + 1;
+    }
+    let (properties, value_handle) = found.ok_or("characteristic not found on the tracker")?;
+    let enable: u16 = if properties & PROP_NOTIFY != 0 {
+        0x0001
+    } else if properties & PROP_INDICATE != 0 {
+        0x0002
+    } else {
+        return Err("characteristic does not notify".to_string());
+    };
+
+    // the client characteristic configuration descriptor follows the value
+    let mut cccd = None;
+    // a value at the last handle leaves no room for the descriptor
+    let mut start = value_handle.checked_add(1).ok_or("characteristic has no client configuration descriptor")?;
+    while cccd.is_none() && start <= range_end {
+        let mut req = vec![ATT_FIND_INFO_REQ];
+        req.extend_from_slice(&start.to_le_bytes());
+        req.extend_from_slice(&range_end.to_le_bytes());
+        let Some(rsp) = request(socket, &req, ATT_FIND_INFO_RSP)? else { break };
+        let entry_len = match rsp.get(1) {
+            Some(1) => 4,
+            Some(2) => 18,
+            _ => return Err("Failed to discover descriptors: bad response".to_string()),
+        };
+        let mut last = start;
+        for entry in rsp[2..].chunks_exact(entry_len) {
+            last = u16::from_le_bytes([entry[0], entry[1]]);
+            if entry_len == 4 && u16::from_le_bytes([entry[2], entry[3]]) == GATT_CLIENT_CONFIG {
+                cccd = Some(last);
+                break;
+            }
+        }
+        if last == 0xFFFF {
+            break;
+        }
+        start = last + 1;
+    }
+    let cccd = cccd.ok_or("characteristic has no client configuration descriptor")?;
+
+    let mut req = vec![ATT_WRITE_REQ];
+    req.extend_from_slice(&cccd.to_le_bytes());
+    req.extend_from_slice(&enable.to_le_bytes());
+    request(socket, &req, ATT_WRITE_RSP)?.ok_or("Failed to enable notifications")?;
+    Ok(value_handle)
+}
+
+// send a request and wait for its response; Ok(None) for "attribute not found",
+// which ends a discovery
+fn request<S: Read + Write>(socket: &mut S, req: &[u8], response: u8) -> Result<Option<Vec<u8>>, String> {
+    socket.write_all(req).map_err(|e| format!("Failed to send to tracker: {}", e))?;
+    loop {
+        let pdu = read_pdu(socket)?;
+        match pdu.first() {
+            Some(&op) if op == response => return Ok(Some(pdu)),
+            Some(&ATT_ERROR_RSP) if pdu.get(1) == Some(&req[0]) => {
+                return match pdu.get(4) {
+                    Some(&ATT_ERR_ATTRIBUTE_NOT_FOUND) => Ok(None),
+                    code => Err(format!("tracker refused request 0x{:02x} (error 0x{:02x})", req[0], code.unwrap_or(&0))),
+                };
+            }
+            _ => answer_server(socket, &pdu)?,
+        }
+    }
+}
+
+fn read_pdu<S: Read>(socket: &mut S) -> Result<Vec<u8>, String> {
+    let mut buf = [0u8; ATT_MTU as usize];
+    match socket.read(&mut buf) {
+        Ok(0) => Err("tracker disconnected".to_string()),
+        Ok(n) => Ok(buf[..n].to_vec()),
+        Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {
+            Err("tracker stopped answering".to_string())
+        }
+        Err(e) => Err(format!("Failed to read from tracker: {}", e)),
+    }
+}
+
+// requests from the tracker's side: mtu exchange is answered, anything else refused
+fn answer_server<S: Write>(socket: &mut S, pdu: &[u8]) -> Result<(), String> {
+    let Some(&op) = pdu.first() else { return Ok(()) };
+    let reply = match op {
+        ATT_EXCHANGE_MTU_REQ => [&[ATT_EXCHANGE_MTU_RSP][..], &ATT_MTU.to_le_bytes()].concat(),
+        ATT_INDICATION => vec![ATT_CONFIRMATION],
+        // requests have bit 0 clear and are not commands (bit 6)
+        op if op & 0x01 == 0 && op & 0x40 == 0 && op != ATT_NOTIFICATION => {
+            vec![ATT_ERROR_RSP, op, 0, 0, ATT_ERR_REQUEST_NOT_SUPPORTED]
+        }
+        _ => return Ok(()),
+    };
+    socket.write_all(&reply).map_err(|e| format!("Failed to send to tracker: {}", e))
+}
+
+// notifications until the tracker disconnects (Err) or the input is dropped (Ok)
+fn receive(socket: &mut File, handle: u16, target: &Target, poses: &Sender<Pose>) -> Result<(), String> {
+    // a tracker that holds still may go quiet for a while, that is fine
+    set_read_timeout(socket, None)?;
+    loop {
+        let pdu = read_pdu(socket)?;
+        if let [op @ (ATT_NOTIFICATION | ATT_INDICATION), lo, hi, value @ ..] = pdu.as_slice() {
+            if *op == ATT_INDICATION {
+                socket.write_all(&[ATT_CONFIRMATION]).map_err(|e| format!("Failed to send to tracker: {}", e))?;
+            }
+            if u16::from_le_bytes([*lo, *hi]) != handle {
+                continue;
+            }
+            let sensor = target.format.decode(value);
+            let Some([yaw, pitch, roll]) = sensor.and_then(|q| imu::to_euler(target.mount.correct(q))) else { continue };
+            let pose = Pose { translation: [0.0; 3], yaw, pitch, roll, time: Instant::now() };
+            if poses.send(pose).is_err() {
+                return Ok(());
+            }
+        } else {
+            answer_server(socket, &pdu)?;
+        }
+    }
+}
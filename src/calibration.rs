@@ -0,0 +1,115 @@
+// automatic sensitivity calibration: watch the range of motion the user
+// actually produces and propose sensitivities that stretch it to the full range.
+// webcam trackers often only report about ±20°, which makes 1:1 mapping feel dead.
+
+use std::time::{Duration, Instant};
+
+// how long to observe motion before proposing
+pub const CALIBRATION_DURATION: Duration = Duration::from_secs(60);
+
+// head angle (half range) that should map to a full turn of the sound stage
+pub const CALIBRATION_TARGET_YAW: f64 = 90.0;
+pub const CALIBRATION_TARGET_PITCH: f64 = 45.0;
+
+// allowed sensitivity range
+pub const MIN_SENSITIVITY: f64 = 0.25;
+pub const MAX_SENSITIVITY: f64 = 4.0;
+
+// axes that barely moved during calibration keep their current sensitivity
+const MIN_OBSERVED_HALF_RANGE: f64 = 5.0;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Calibration {
+    pub yaw_sensitivity: f64,
+    pub pitch_sensitivity: f64,
+}
+
+pub struct Calibrator {
+    started: Option<Instant>,
+    duration: Duration,
+    yaw_min: f64,
+    yaw_max: f64,
+    pitch_min: f64,
+    pitch_max: f64,
+    proposal: Option<Calibration>,
+}
+
+impl Calibrator {
+    pub fn new() -> Self {
+        Self {
+            started: None,
+            duration: CALIBRATION_DURATION,
+            yaw_min: f64::MAX,
+            yaw_max: f64::MIN,
+            pitch_min: f64::MAX,
+            pitch_max: f64::MIN,
+            proposal: None,
+        }
+    }
+
+    // (re)start recording, dropping any previous proposal
+    pub fn start(&mut self, now: Instant) {
+        *self = Self { started: Some(now), duration: self.duration, ..Self::new() };
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started.is_some()
+    }
+
+    pub fn remaining(&self, now: Instant) -> Duration {
+        match self.started {
+            Some(t) => self.duration.saturating_sub(now.duration_since(t)),
+            None => Duration::ZERO,
+        }
+    }
+
+    pub fn proposal(&self) -> Option<Calibration> {
+        self.proposal
+    }
+
+    pub fn take_proposal(&mut self) -> Option<Calibration> {
+        self.proposal.take()
+    }
+
+    // record a raw pose; returns the proposal once the observation window ends.
+    // `current` is used for axes the user didn't move enough to measure.
+    pub fn observe(&mut self, yaw: f64, pitch: f64, current: Calibration, now: Instant) -> Option<Calibration> {
+        let started = self.started?;
+
+        if yaw.is_finite() && pitch.is_finite() {
+            self.yaw_min = self.yaw_min.min(yaw);
+            self.yaw_max = self.yaw_max.max(yaw);
+            self.pitch_min = self.pitch_min.min(pitch);
+            self.pitch_max = self.pitch_max.max(pitch);
+        }
+
+        if now.duration_since(started) < self.duration {
+            return None;
+        }
+
+        self.started = None;
+        let proposal = Calibration {
+            yaw_sensitivity: propose(self.yaw_min, self.yaw_max, CALIBRATION_TARGET_YAW)
+                .unwrap_or(current.yaw_sensitivity),
+            pitch_sensitivity: propose(self.pitch_min, self.pitch_max, CALIBRATION_TARGET_PITCH)
+                .unwrap_or(current.pitch_sensitivity),
+        };
+        self.proposal = Some(proposal);
+        Some(proposal)
+    }
+}
+
+impl Default for Calibrator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// use half of the observed span so an off-center resting pose doesn't skew the result
+fn propose(min: f64, max: f64, target: f64) -> Option<f64> {
+    let half_range = (max - min) / 2.0;
+    if half_range < MIN_OBSERVED_HALF_RANGE {
+        return None;
+    }
+    Some((target / half_range).clamp(MIN_SENSITIVITY, MAX_SENSITIVITY))
+}
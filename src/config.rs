@@ -0,0 +1,713 @@
+// command line options (no external deps, just a simple flag parser).
+// the config file uses the same option names as `key = value` lines, so
+// anything that works on the command line also works in the file.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use spatial_track::calibration::{MAX_SENSITIVITY, MIN_SENSITIVITY};
+use spatial_track::default_sink::SinkClass;
+use spatial_track::demo::DEFAULT_SCENE;
+use spatial_track::engine::{
+    FilterKind, Listening, PitchTarget, DEFAULT_REAR_ATTENUATION_DB, DEFAULT_REAR_ROLLOFF_DB, MAX_CROSSTALK_CANCEL, MAX_LEAN_WIDTH,
+    MAX_OUTPUT_SMOOTHING_MS, MAX_REAR_DB, MAX_SMOOTHING_FACTOR,
+};
+use spatial_track::failover::{DEFAULT_FAILOVER_TIMEOUT, MIN_FAILOVER_TIMEOUT};
+use spatial_track::glide::{Easing, DEFAULT_GLIDE, MAX_GLIDE};
+use spatial_track::input::DEFAULT_INPUT;
+use spatial_track::mapping::{AxisMapping, LimitMode, MAX_CURVE, MAX_DEAD_ZONE, MAX_LIMIT, MAX_MAGNET, MIN_CURVE};
+use spatial_track::loudness::{MAX_TARGET_DB, MIN_TARGET_DB};
+use spatial_track::output_zones::OutputZone;
+use spatial_track::room::RoomZone;
+use spatial_track::session_record::RECORD_FORMATS;
+use spatial_track::profile::DEFAULT_PROFILE;
+use spatial_track::uinput::{
+    JoystickAxis, DEFAULT_JOYSTICK_RANGES, DEFAULT_MOUSE_GAIN, MAX_JOYSTICK_RANGE, MAX_MOUSE_GAIN, MIN_MOUSE_GAIN,
+};
+use spatial_track::volume_guard::{DEFAULT_CEILING, DEFAULT_MAX_STEP_DB, MAX_MAX_STEP_DB, MIN_MAX_STEP_DB};
+
+use crate::bench::DEFAULT_FRAMES;
+use crate::listener::ListenerSpec;
+use crate::pipewire::VolumeControl;
+use crate::theme::Theme;
+
+const CONFIG_FILE_NAME: &str = "spatial-track.conf";
+
+pub const USAGE: &str = "\
+usage: spatial-track [options]
+       spatial-track demo [orbit|positions] [options]
+       spatial-track bench [frames]
+
+  demo plays a test sound into the spatializer and moves it around you
+  without a tracker, to hear what spatial-track does before setting it up
+  bench times the work done for every update and counts its allocations
+  (default: 10000 frames)
+
+options:
+  --config <path>                    read options from this file instead of
+                                     ~/.config/spatial-track/spatial-track.conf
+  --no-config                        ignore the config file
+  --<axis>-dead-zone <deg>           per-axis dead zone, axis = yaw|pitch|roll (default: 0)
+  --<axis>-sensitivity <x>           per-axis multiplier, 0.25-4 (default: 1)
+  --<axis>-curve <exp>               per-axis response exponent, 1 = linear, >1 softer
+                                     near center (default: 1)
+  --<axis>-magnet <deg>              per-axis range past the dead zone that is gently
+                                     pulled toward center (default: 0 = off)
+  --<axis>-limit <deg>               per-axis largest angle from center (default: 0 = none)
+  --<axis>-limit-mode <mode>         past the limit: clamp|fold|ignore (default: clamp)
+  --<axis>-smoothing <x>             per-axis ema factor, 0-0.99, higher = smoother
+                                     (default: 0.65)
+  --pitch-target <target>            what looking up and down controls: elevation|volume|
+                                     tilt|crossfeed|none (default: elevation)
+  --input <uri>                      tracker input (default: opentrack-udp://127.0.0.1:4242)
+                                     schemes: opentrack-udp://, opentrack-tcp://,
+                                     opentrack-noise://<addr>?key=<file>
+  --extra-input <uri>                another input read at the same time as --input, e.g.
+                                     opentrack-udp://[::]:4243 (repeatable)
+  --fallback-input <uri>             input to switch to while the ones before it send nothing,
+                                     back as soon as they return (repeatable, in order)
+  --failover-timeout <ms>            silence before switching to the next input (default: 1000)
+  --calibration <off|propose|auto>   sensitivity calibration over the first minute
+                                     (default: propose, press A to apply)
+  --filter <ema|one-euro|kalman>     pose smoothing filter (default: ema, F cycles at runtime)
+  --compare-filters                  run all filters side by side and show their output and
+                                     pan, F picks the one that drives the audio
+  --tune-smoothing                   start the smoothing analysis with the first packet: hold
+                                     still, then move, and get an ema factor per axis
+                                     proposed (N restarts it, Y applies the proposal)
+  --listening <mode>                 headphones|speakers, speakers narrows the pan and folds
+                                     it to the front; remembered per profile (default: headphones)
+  --crosstalk-cancel <x>             with speakers, cancel this much of each speaker reaching
+                                     the far ear, 0-1 (default: 0 = off)
+  --lean-width <x>                   stereo width change per cm of leaning in/out, e.g. 0.05;
+                                     narrower goes toward mono in front (default: 0 = off)
+  --output-smoothing <ms>            light ramp on the final speaker angles and gains,
+                                     independent of the pose smoothing (default: 0 = off)
+  --center-glide <ms>                glide to the new position on recenter or when tracking
+                                     comes back instead of snapping, 0 = snap (default: 400)
+  --center-curve <curve>             easing of the glide: linear|ease-out|ease-in-out
+                                     (default: ease-out)
+  --profile <name>                   keep recenter, calibration and listening mode per profile
+                                     (default: default)
+  --reset-profile                    forget what is stored for this profile and input
+                                     before starting
+  --media-control <off|on|exclusive> head gestures control volume/mute/playback;
+                                     exclusive keeps the speakers fixed
+  --mouse-look                       emit relative mouse motion from head yaw/pitch
+                                     (needs /dev/uinput access, M toggles at runtime)
+  --mouse-gain <counts>              mouse counts per degree of head rotation, 0.1-1000 (default: 20)
+  --joystick                         expose yaw/pitch/roll as X/Y/Z of a virtual joystick
+                                     (needs /dev/uinput access)
+  --joystick-<axis>-range <deg>      head angle for full deflection, 1-180 (default: yaw 90,
+                                     pitch 45, roll 45)
+  --joystick-<axis>-curve <exp>      joystick response exponent, 1 = linear, >1 finer near
+                                     center (default: 1)
+  --relay <host:port>                forward tracker packets to another listener (repeatable)
+  --relay-smoothed                   forward the smoothed, recentered pose instead of raw packets
+  --artnet <host[:port]>             send head pan/tilt as art-net dmx (16-bit pan + tilt)
+  --artnet-universe <n>              art-net port address, 0-32767 (default: 0)
+  --artnet-channel <n>               first dmx channel, 1-based (default: 1)
+  --drift-correction <off|manual|auto>
+                                     compensate gyro bias of imu trackers; G starts a 5s
+                                     hold-still calibration, auto also re-runs when drift
+                                     is detected while still (default: off)
+  --rear-attenuation <db>            volume drop for a speaker directly behind you (default: 3)
+  --rear-rolloff <db>                high frequency cut directly behind you (default: 6)
+  --loudness-target <dbfs>           level the incoming audio towards this rms level,
+                                     -40 to -6 (default: off)
+  --max-volume <x>                   hard ceiling on the applied speaker gain, 0-1 (default: 1)
+  --max-volume-step <db>             largest gain rise per update, bigger jumps are ramped,
+                                     0.5-24 (default: 6)
+  --listener <[addr:]port>=<node>    extra tracker input driving its own filter-chain node
+                                     with independent smoothing (repeatable)
+  --zmq <tcp://host:port>            zeromq PUB socket streaming topics pose, audio, events
+  --game-shm <name>                  export pose and spatial state for game mods in
+                                     /dev/shm/<name> (layout: StGameState in the c header)
+  --game-udp <host:port>             send the same record as one udp packet per update
+  --output-zone <from>:<to>=<sink>   move the output to <sink> after facing that yaw range
+                                     for 3s, positive = left (repeatable)
+  --room-zone <degrees>=<sink>       also play on <sink>, louder while facing its direction
+                                     (repeatable)
+  --follow-default-sink              watch the default output device: headphones as usual,
+                                     speakers switch --listening, hdmi pauses spatialization
+  --sink-class <name>=<class>        treat sinks whose name contains <name> as headphones|
+                                     speakers|hdmi (repeatable, default: guessed from the name)
+  --pause-on-lock                    hold the speakers in front at full volume while the
+                                     screen is locked (logind LockedHint)
+  --pause-on-idle                    the same while the session is idle (logind IdleHint)
+  --tracker <command>                launch the tracker (e.g. opentrack) at startup and
+                                     restart it with backoff when it exits
+  --volume-control <how>             where distance/rear/zone gain is applied: filter (soft-volume
+                                     in the filter-chain), stream (Props volume of its output
+                                     stream, balance in the filter) or node=<name> (a dedicated
+                                     loopback node) (default: filter)
+  --filter-chain <installed|managed> use the filter-chain installed in pipewire.conf.d, or
+                                     run it with spatial-track and remove it on exit
+                                     (default: installed)
+  --no-pipewire                      don't drive the pipewire filter-chain (other outputs only)
+  --sink-rate <sink>=<hz>            max update rate of one output, sink = pipewire|room|
+                                     relay|artnet|joystick|zmq|game|rerun (default: 50, relay/game/
+                                     rerun every packet)
+  --rerun                            log orientation, pan and latency to a rerun viewer
+                                     (needs python3 with rerun-sdk)
+  --rerun-save <file.rrd>            write the rerun recording to a file instead
+  --record <file.wav|file.flac>      record the spatialized output of the session
+  --theme <name>                     dashboard look: default|light|high-contrast|mono|ascii,
+                                     ascii has no color, emoji or box drawing; T cycles at
+                                     runtime (default: default, mono if NO_COLOR is set)
+  --plain-status                     print a short status line when it changes instead of
+                                     the full-screen dashboard (for screen readers and logs)
+  -h, --help                         show this help";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CalibrationMode {
+    Off,
+    Propose,
+    Auto,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FilterChainMode {
+    // conf/99-spatializer.conf copied to pipewire.conf.d
+    Installed,
+    // started and stopped by spatial-track
+    Managed,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DriftCorrection {
+    Off,
+    Manual,
+    Auto,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MediaControlMode {
+    Off,
+    // gestures on top of normal panning
+    On,
+    // gestures only, speakers stay centered
+    Exclusive,
+}
+
+// names accepted by --sink-rate
+const SINK_NAMES: [&str; 8] = ["pipewire", "room", "relay", "artnet", "joystick", "zmq", "game", "rerun"];
+
+pub struct Config {
+    pub input: String,
+    // read alongside `input`, poses from whichever one sends
+    pub extra_inputs: Vec<String>,
+    pub fallback_inputs: Vec<String>,
+    pub failover_timeout: Duration,
+    pub pipewire: bool,
+    pub filter_chain: FilterChainMode,
+    pub volume_control: VolumeControl,
+    pub sink_rates: Vec<(String, f64)>,
+    pub calibration: CalibrationMode,
+    pub filter: FilterKind,
+    pub compare_filters: bool,
+    pub pitch_target: PitchTarget,
+    // ema factor per axis (yaw, pitch, roll), None = the default
+    pub smoothing: [Option<f64>; 3],
+    pub tune_smoothing: bool,
+    // None = what the profile used last
+    pub listening: Option<Listening>,
+    pub crosstalk_cancel: f64,
+    pub lean_width: f64,
+    pub output_smoothing: f64,
+    pub center_glide: Duration,
+    pub center_curve: Easing,
+    pub profile: String,
+    pub reset_profile: bool,
+    pub media_control: MediaControlMode,
+    pub mouse_look: bool,
+    pub mouse_gain: f64,
+    pub joystick: bool,
+    // yaw, pitch, roll
+    pub joystick_axes: [JoystickAxis; 3],
+    pub relay_targets: Vec<String>,
+    pub relay_smoothed: bool,
+    pub artnet_target: Option<String>,
+    pub artnet_universe: u16,
+    pub artnet_channel: usize,
+    pub drift_correction: DriftCorrection,
+    pub yaw: AxisMapping,
+    pub pitch: AxisMapping,
+    pub roll: AxisMapping,
+    pub rear_attenuation_db: f64,
+    pub rear_rolloff_db: f64,
+    pub loudness_target: Option<f64>,
+    pub max_volume: f64,
+    pub max_volume_step_db: f64,
+    pub listeners: Vec<ListenerSpec>,
+    pub zmq_endpoint: Option<String>,
+    pub game_shm: Option<String>,
+    pub game_udp: Option<String>,
+    pub output_zones: Vec<OutputZone>,
+    pub room_zones: Vec<RoomZone>,
+    pub follow_default_sink: bool,
+    pub sink_classes: Vec<SinkClass>,
+    pub pause_on_lock: bool,
+    pub pause_on_idle: bool,
+    pub tracker_command: Option<String>,
+    pub rerun: bool,
+    pub rerun_save: Option<String>,
+    pub record: Option<PathBuf>,
+    pub theme: Theme,
+    pub plain_status: bool,
+    pub demo: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            input: DEFAULT_INPUT.to_string(),
+            extra_inputs: Vec::new(),
+            fallback_inputs: Vec::new(),
+            failover_timeout: DEFAULT_FAILOVER_TIMEOUT,
+            pipewire: true,
+            filter_chain: FilterChainMode::Installed,
+            volume_control: VolumeControl::Filter,
+            sink_rates: Vec::new(),
+            calibration: CalibrationMode::Propose,
+            filter: FilterKind::Ema,
+            compare_filters: false,
+            pitch_target: PitchTarget::Elevation,
+            smoothing: [None; 3],
+            tune_smoothing: false,
+            listening: None,
+            crosstalk_cancel: 0.0,
+            lean_width: 0.0,
+            output_smoothing: 0.0,
+            center_glide: DEFAULT_GLIDE,
+            center_curve: Easing::EaseOut,
+            profile: DEFAULT_PROFILE.to_string(),
+            reset_profile: false,
+            media_control: MediaControlMode::Off,
+            mouse_look: false,
+            mouse_gain: DEFAULT_MOUSE_GAIN,
+            joystick: false,
+            joystick_axes: DEFAULT_JOYSTICK_RANGES.map(|range| JoystickAxis { range, curve: 1.0 }),
+            relay_targets: Vec::new(),
+            relay_smoothed: false,
+            artnet_target: None,
+            artnet_universe: 0,
+            artnet_channel: 1,
+            drift_correction: DriftCorrection::Off,
+            yaw: AxisMapping::identity(),
+            pitch: AxisMapping::identity(),
+            roll: AxisMapping::identity(),
+            rear_attenuation_db: DEFAULT_REAR_ATTENUATION_DB,
+            rear_rolloff_db: DEFAULT_REAR_ROLLOFF_DB,
+            loudness_target: None,
+            max_volume: DEFAULT_CEILING,
+            max_volume_step_db: DEFAULT_MAX_STEP_DB,
+            listeners: Vec::new(),
+            zmq_endpoint: None,
+            game_shm: None,
+            game_udp: None,
+            output_zones: Vec::new(),
+            room_zones: Vec::new(),
+            follow_default_sink: false,
+            sink_classes: Vec::new(),
+            pause_on_lock: false,
+            pause_on_idle: false,
+            tracker_command: None,
+            rerun: false,
+            rerun_save: None,
+            record: None,
+            // https://no-color.org
+            theme: if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) { Theme::Mono } else { Theme::Default },
+            plain_status: false,
+            demo: false,
+        }
+    }
+}
+
+pub enum ParseOutcome {
+    Run(Box<Config>),
+    Help,
+    // `bench [frames]`
+    Bench(u64),
+}
+
+impl Config {
+    // config file first, then command line so flags override the file
+    pub fn from_args() -> Result<ParseOutcome, String> {
+        let mut cli: Vec<String> = std::env::args().skip(1).collect();
+        // `bench [frames]` subcommand, no config needed
+        if cli.first().is_some_and(|a| a == "bench") {
+            return match cli.get(1) {
+                Some(frames) => match frames.parse::<u64>() {
+                    Ok(frames) if frames > 0 => Ok(ParseOutcome::Bench(frames)),
+                    _ => Err(format!("invalid bench frame count '{}'", frames)),
+                },
+                None => Ok(ParseOutcome::Bench(DEFAULT_FRAMES)),
+            };
+        }
+        // `demo [scene]` subcommand
+        let demo = if cli.first().is_some_and(|a| a == "demo") {
+            cli.remove(0);
+            let scene = match cli.first() {
+                Some(a) if !a.starts_with('-') => cli.remove(0),
+                _ => DEFAULT_SCENE.to_string(),
+            };
+            Some(scene)
+        } else {
+            None
+        };
+
+        let mut args = match config_file_path(&cli)? {
+            Some(path) => read_config_file(&path)?,
+            None => Vec::new(),
+        };
+        args.extend(cli);
+        let mut outcome = Self::parse(args.into_iter())?;
+        if let (Some(scene), ParseOutcome::Run(config)) = (demo, &mut outcome) {
+            config.input = format!("demo://{}", scene);
+            // real poses from extra inputs would mix into the scene
+            config.extra_inputs.clear();
+            config.demo = true;
+            // don't start the real tracker from the config file, and
+            // calibrating against a synthetic head makes no sense
+            config.tracker_command = None;
+            config.calibration = CalibrationMode::Off;
+            config.tune_smoothing = false;
+            config.drift_correction = DriftCorrection::Off;
+        }
+        Ok(outcome)
+    }
+
+    // --sink-rate for `name` if given, otherwise the default interval
+    pub fn sink_interval(&self, name: &str, default: Duration) -> Duration {
+        match self.sink_rates.iter().rev().find(|(n, _)| n == name) {
+            Some((_, hz)) => Duration::from_secs_f64(1.0 / hz),
+            None => default,
+        }
+    }
+
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<ParseOutcome, String> {
+        let mut config = Config::default();
+
+        while let Some(arg) = args.next() {
+            if let Some((axis, setting)) = axis_flag(&arg) {
+                let (mapping, index) = match axis {
+                    "yaw" => (&mut config.yaw, 0),
+                    "pitch" => (&mut config.pitch, 1),
+                    _ => (&mut config.roll, 2),
+                };
+                if setting == "smoothing" {
+                    let value = parse_number(&mut args, &arg)?;
+                    config.smoothing[index] = Some(in_range(value, 0.0, MAX_SMOOTHING_FACTOR, &arg)?);
+                    continue;
+                }
+                if setting == "limit-mode" {
+                    let name = next_value(&mut args, &arg)?;
+                    mapping.limit_mode =
+                        LimitMode::parse(&name).ok_or_else(|| format!("invalid {} value '{}'", arg, name))?;
+                    continue;
+                }
+                let value = parse_number(&mut args, &arg)?;
+                match setting {
+                    "dead-zone" => mapping.dead_zone = in_range(value, 0.0, MAX_DEAD_ZONE, &arg)?,
+                    "sensitivity" => mapping.sensitivity = in_range(value, MIN_SENSITIVITY, MAX_SENSITIVITY, &arg)?,
+                    "magnet" => mapping.magnet = in_range(value, 0.0, MAX_MAGNET, &arg)?,
+                    "limit" => mapping.limit = in_range(value, 0.0, MAX_LIMIT, &arg)?,
+                    _ => mapping.curve = in_range(value, MIN_CURVE, MAX_CURVE, &arg)?,
+                }
+                continue;
+            }
+            if let Some((axis, setting)) = joystick_flag(&arg) {
+                let value = parse_number(&mut args, &arg)?;
+                let joystick_axis = &mut config.joystick_axes[axis];
+                match setting {
+                    "range" => joystick_axis.range = in_range(value, 1.0, MAX_JOYSTICK_RANGE, &arg)?,
+                    _ => joystick_axis.curve = in_range(value, MIN_CURVE, MAX_CURVE, &arg)?,
+                }
+                continue;
+            }
+
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(ParseOutcome::Help),
+                // already handled by from_args
+                "--config" => {
+                    next_value(&mut args, &arg)?;
+                }
+                "--no-config" => {}
+                "--input" => config.input = next_value(&mut args, &arg)?,
+                "--extra-input" => config.extra_inputs.push(next_value(&mut args, &arg)?),
+                "--fallback-input" => config.fallback_inputs.push(next_value(&mut args, &arg)?),
+                "--failover-timeout" => {
+                    let min = MIN_FAILOVER_TIMEOUT.as_millis() as f64;
+                    let ms = in_range(parse_number(&mut args, &arg)?, min, 60_000.0, &arg)?;
+                    config.failover_timeout = Duration::from_secs_f64(ms / 1000.0);
+                }
+                "--calibration" => {
+                    config.calibration = match next_value(&mut args, &arg)?.as_str() {
+                        "off" => CalibrationMode::Off,
+                        "propose" => CalibrationMode::Propose,
+                        "auto" => CalibrationMode::Auto,
+                        other => return Err(format!("invalid --calibration value '{}'", other)),
+                    };
+                }
+                "--filter" => {
+                    let name = next_value(&mut args, &arg)?;
+                    config.filter = FilterKind::parse(&name).ok_or_else(|| format!("invalid --filter value '{}'", name))?;
+                }
+                "--pitch-target" => {
+                    let name = next_value(&mut args, &arg)?;
+                    config.pitch_target =
+                        PitchTarget::parse(&name).ok_or_else(|| format!("invalid --pitch-target value '{}'", name))?;
+                }
+                "--compare-filters" => config.compare_filters = true,
+                "--tune-smoothing" => config.tune_smoothing = true,
+                "--listening" => {
+                    let name = next_value(&mut args, &arg)?;
+                    let listening =
+                        Listening::parse(&name).ok_or_else(|| format!("invalid --listening value '{}'", name))?;
+                    config.listening = Some(listening);
+                }
+                "--crosstalk-cancel" => {
+                    config.crosstalk_cancel = in_range(parse_number(&mut args, &arg)?, 0.0, MAX_CROSSTALK_CANCEL, &arg)?
+                }
+                "--lean-width" => {
+                    config.lean_width = in_range(parse_number(&mut args, &arg)?, -MAX_LEAN_WIDTH, MAX_LEAN_WIDTH, &arg)?
+                }
+                "--output-smoothing" => {
+                    let ms = in_range(parse_number(&mut args, &arg)?, 0.0, MAX_OUTPUT_SMOOTHING_MS, &arg)?;
+                    config.output_smoothing = ms / 1000.0;
+                }
+                "--center-glide" => {
+                    let ms = in_range(parse_number(&mut args, &arg)?, 0.0, MAX_GLIDE.as_millis() as f64, &arg)?;
+                    config.center_glide = Duration::from_secs_f64(ms / 1000.0);
+                }
+                "--center-curve" => {
+                    let name = next_value(&mut args, &arg)?;
+                    config.center_curve =
+                        Easing::parse(&name).ok_or_else(|| format!("invalid --center-curve value '{}'", name))?;
+                }
+                "--profile" => {
+                    let name = next_value(&mut args, &arg)?;
+                    // it becomes part of a whitespace separated state record
+                    if name.is_empty() || name.contains(char::is_whitespace) {
+                        return Err(format!("invalid --profile name '{}'", name));
+                    }
+                    config.profile = name;
+                }
+                "--reset-profile" => config.reset_profile = true,
+                "--media-control" => {
+                    config.media_control = match next_value(&mut args, &arg)?.as_str() {
+                        "off" => MediaControlMode::Off,
+                        "on" => MediaControlMode::On,
+                        "exclusive" => MediaControlMode::Exclusive,
+                        other => return Err(format!("invalid --media-control value '{}'", other)),
+                    };
+                }
+                "--mouse-look" => config.mouse_look = true,
+                "--mouse-gain" => {
+                    config.mouse_gain = in_range(parse_number(&mut args, &arg)?, MIN_MOUSE_GAIN, MAX_MOUSE_GAIN, &arg)?
+                }
+                "--joystick" => config.joystick = true,
+                "--relay" => config.relay_targets.push(next_value(&mut args, &arg)?),
+                "--relay-smoothed" => config.relay_smoothed = true,
+                "--artnet" => config.artnet_target = Some(next_value(&mut args, &arg)?),
+                "--artnet-universe" => config.artnet_universe = parse_value(&mut args, &arg)?,
+                "--artnet-channel" => config.artnet_channel = parse_value(&mut args, &arg)?,
+                "--drift-correction" => {
+                    config.drift_correction = match next_value(&mut args, &arg)?.as_str() {
+                        "off" => DriftCorrection::Off,
+                        "manual" => DriftCorrection::Manual,
+                        "auto" => DriftCorrection::Auto,
+                        other => return Err(format!("invalid --drift-correction value '{}'", other)),
+                    };
+                }
+                "--rear-attenuation" => {
+                    config.rear_attenuation_db = in_range(parse_number(&mut args, &arg)?, 0.0, MAX_REAR_DB, &arg)?
+                }
+                "--rear-rolloff" => {
+                    config.rear_rolloff_db = in_range(parse_number(&mut args, &arg)?, 0.0, MAX_REAR_DB, &arg)?
+                }
+                "--loudness-target" => {
+                    let target = in_range(parse_number(&mut args, &arg)?, MIN_TARGET_DB, MAX_TARGET_DB, &arg)?;
+                    config.loudness_target = Some(target);
+                }
+                "--max-volume" => config.max_volume = in_range(parse_number(&mut args, &arg)?, 0.0, 1.0, &arg)?,
+                "--max-volume-step" => {
+                    config.max_volume_step_db = in_range(parse_number(&mut args, &arg)?, MIN_MAX_STEP_DB, MAX_MAX_STEP_DB, &arg)?
+                }
+                "--listener" => config.listeners.push(ListenerSpec::parse(&next_value(&mut args, &arg)?)?),
+                "--zmq" => config.zmq_endpoint = Some(next_value(&mut args, &arg)?),
+                "--game-shm" => config.game_shm = Some(next_value(&mut args, &arg)?),
+                "--game-udp" => config.game_udp = Some(next_value(&mut args, &arg)?),
+                "--output-zone" => config.output_zones.push(OutputZone::parse(&next_value(&mut args, &arg)?)?),
+                "--room-zone" => config.room_zones.push(RoomZone::parse(&next_value(&mut args, &arg)?)?),
+                "--follow-default-sink" => config.follow_default_sink = true,
+                "--sink-class" => config.sink_classes.push(SinkClass::parse(&next_value(&mut args, &arg)?)?),
+                "--pause-on-lock" => config.pause_on_lock = true,
+                "--pause-on-idle" => config.pause_on_idle = true,
+                "--tracker" => config.tracker_command = Some(next_value(&mut args, &arg)?),
+                "--no-pipewire" => config.pipewire = false,
+                "--filter-chain" => {
+                    config.filter_chain = match next_value(&mut args, &arg)?.as_str() {
+                        "installed" => FilterChainMode::Installed,
+                        "managed" => FilterChainMode::Managed,
+                        other => return Err(format!("invalid --filter-chain value '{}'", other)),
+                    };
+                }
+                "--volume-control" => {
+                    let value = next_value(&mut args, &arg)?;
+                    config.volume_control =
+                        VolumeControl::parse(&value).ok_or_else(|| format!("invalid --volume-control value '{}'", value))?;
+                }
+                "--sink-rate" => config.sink_rates.push(parse_sink_rate(&next_value(&mut args, &arg)?)?),
+                "--theme" => {
+                    let value = next_value(&mut args, &arg)?;
+                    config.theme = Theme::parse(&value).ok_or_else(|| format!("invalid --theme value '{}'", value))?;
+                }
+                "--plain-status" => config.plain_status = true,
+                "--rerun" => config.rerun = true,
+                "--rerun-save" => config.rerun_save = Some(next_value(&mut args, &arg)?),
+                "--record" => {
+                    let path = PathBuf::from(next_value(&mut args, &arg)?);
+                    let format = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+                    if !RECORD_FORMATS.contains(&format.as_str()) {
+                        return Err(format!("--record needs a .wav or .flac file, got '{}'", path.display()));
+                    }
+                    config.record = Some(path);
+                }
+                other => return Err(format!("unknown option '{}'\n\n{}", other, USAGE)),
+            }
+        }
+
+        if config.record.is_some() && !config.pipewire {
+            return Err("--record records the filter-chain output and cannot be used with --no-pipewire".to_string());
+        }
+
+        Ok(ParseOutcome::Run(Box::new(config)))
+    }
+}
+
+// "--yaw-dead-zone" -> ("yaw", "dead-zone")
+fn axis_flag(arg: &str) -> Option<(&str, &str)> {
+    let (axis, setting) = arg.strip_prefix("--")?.split_once('-')?;
+    let known_axis = matches!(axis, "yaw" | "pitch" | "roll");
+    let known_setting = matches!(setting, "dead-zone" | "sensitivity" | "curve" | "magnet" | "limit" | "limit-mode" | "smoothing");
+    (known_axis && known_setting).then_some((axis, setting))
+}
+
+// "--joystick-pitch-curve" -> (1, "curve")
+fn joystick_flag(arg: &str) -> Option<(usize, &str)> {
+    let (axis, setting) = arg.strip_prefix("--joystick-")?.split_once('-')?;
+    let axis = ["yaw", "pitch", "roll"].iter().position(|&a| a == axis)?;
+    matches!(setting, "range" | "curve").then_some((axis, setting))
+}
+
+// "<sink>=<hz>"
+fn parse_sink_rate(spec: &str) -> Result<(String, f64), String> {
+    let (name, hz) = spec
+        .split_once('=')
+        .ok_or_else(|| format!("invalid --sink-rate '{}', expected <sink>=<hz>", spec))?;
+    if !SINK_NAMES.contains(&name) {
+        return Err(format!("unknown sink '{}' (one of {})", name, SINK_NAMES.join(", ")));
+    }
+    let hz: f64 = hz.parse().map_err(|_| format!("invalid rate '{}' for sink {}", hz, name))?;
+    Ok((name.to_string(), in_range(hz, 0.1, 1000.0, "--sink-rate")?))
+}
+
+fn in_range(value: f64, min: f64, max: f64, flag: &str) -> Result<f64, String> {
+    if value < min || value > max {
+        return Err(format!("{} must be between {} and {}", flag, min, max));
+    }
+    Ok(value)
+}
+
+fn default_config_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("spatial-track").join(CONFIG_FILE_NAME))
+}
+
+// explicit --config must exist, the default file is optional
+fn config_file_path(cli: &[String]) -> Result<Option<PathBuf>, String> {
+    if cli.iter().any(|a| a == "--no-config") {
+        return Ok(None);
+    }
+    if let Some(i) = cli.iter().position(|a| a == "--config") {
+        let path = cli.get(i + 1).ok_or("missing value for --config")?;
+        return Ok(Some(PathBuf::from(path)));
+    }
+    Ok(default_config_path().filter(|p| p.exists()))
+}
+
+// turn "key = value" lines into the equivalent flags:
+//   mouse-look = true   -> --mouse-look
+//   relay = "host:4243" -> --relay host:4243
+fn read_config_file(path: &PathBuf) -> Result<Vec<String>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+
+    let mut args = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("{}:{}: expected 'key = value'", path.display(), n + 1));
+        };
+        let key = key.trim();
+        let value = value.trim().trim_matches('"');
+        if key == "config" || key == "no-config" {
+            return Err(format!("{}:{}: '{}' is only valid on the command line", path.display(), n + 1, key));
+        }
+        match value {
+            "true" => args.push(format!("--{}", key)),
+            "false" => {}
+            _ => {
+                args.push(format!("--{}", key));
+                args.push(value.to_string());
+            }
+        }
+    }
+    Ok(args)
+}
+
+fn parse_number(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<f64, String> {
+    let value: f64 = parse_value(args, flag)?;
+    if !value.is_finite() {
+        return Err(format!("invalid number '{}' for {}", value, flag));
+    }
+    Ok(value)
+}
+
+fn parse_value<T: std::str::FromStr>(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<T, String> {
+    let value = next_value(args, flag)?;
+    value.parse::<T>().map_err(|_| format!("invalid value '{}' for {}", value, flag))
+}
+
+fn next_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("missing value for {}", flag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<ParseOutcome, String> {
+        Config::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn mouse_gain_out_of_range_is_rejected() {
+        for gain in ["0", "-5", "0.01", "5000"] {
+            let err = parse(&["--mouse-gain", gain]).err().expect(gain);
+            assert_eq!(err, "--mouse-gain must be between 0.1 and 1000");
+        }
+        match parse(&["--mouse-gain", "40"]) {
+            Ok(ParseOutcome::Run(config)) => assert_eq!(config.mouse_gain, 40.0),
+            _ => panic!("--mouse-gain 40 should parse"),
+        }
+    }
+}
@@ -0,0 +1,945 @@
+// the full-screen dashboard. it is rendered into a Screen instead of
+// straight to the terminal, so main can cut it to the terminal size and the
+// tests below can compare it against the snapshots in src/snapshots.
+
+use std::fmt::{self, Write};
+use std::time::Instant;
+
+use spatial_track::calibration::Calibrator;
+use spatial_track::default_sink::{DefaultSinkWatcher, DeviceClass};
+use spatial_track::engine::{Engine, Listening, PitchTarget, SpatialState, SpeakerMode};
+use spatial_track::filters::FilterComparison;
+use spatial_track::gyro_bias::DriftCompensator;
+use spatial_track::history::{RateHistory, HISTORY_SECONDS};
+use spatial_track::loudness::Leveler;
+use spatial_track::media_control::MediaController;
+use spatial_track::motion::Motion;
+use spatial_track::output_zones::OutputSwitcher;
+use spatial_track::session_lock::SessionWatcher;
+use spatial_track::session_record::SessionRecorder;
+use spatial_track::sink::{Sinks, Status};
+use spatial_track::smoothing_tune::{AxisAnalysis, Phase, SmoothingTuner};
+use spatial_track::supervisor::{TrackerStatus, TrackerSupervisor};
+use spatial_track::volume_guard::VolumeGuard;
+
+use crate::filter_chain::{ChainStatus, FilterChain};
+use crate::listener::Listener;
+use crate::pipewire::CHANGE_THRESHOLD;
+use crate::theme::Theme;
+
+// inside width of the box, between the side borders
+pub const BOX_WIDTH: usize = 66;
+
+// the dashboard, built as lines of text and written out in one go. rows are
+// cut to the box so a long value can't push the right border out, and the
+// whole screen is cut to the terminal so it doesn't wrap or scroll. the line
+// buffers are kept from one update to the next, so once they have grown to
+// size a redraw doesn't allocate
+pub struct Screen {
+    theme: Theme,
+    lines: Vec<String>,
+    // lines of this update, the ones after it are left over from longer ones
+    len: usize,
+    // a row's text before and after painting
+    text: String,
+    painted: String,
+}
+
+impl Screen {
+    pub fn new(theme: Theme) -> Self {
+        Self { theme, lines: Vec::new(), len: 0, text: String::new(), painted: String::new() }
+    }
+
+    // start over for the next update, keeping the buffers
+    pub fn clear(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.len = 0;
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines[..self.len]
+    }
+
+    // one line between the side borders
+    pub fn row(&mut self, content: impl fmt::Display) {
+        self.text.clear();
+        write!(self.text, "{}", content).ok();
+        let line = next_line(&mut self.lines, &mut self.len);
+        write_box_row(self.theme, &self.text, &mut self.painted, line);
+    }
+
+    // a row centered by its width once painted
+    pub fn centered_row(&mut self, content: &str) {
+        self.painted.clear();
+        self.theme.paint_into(content, &mut self.painted);
+        let pad = BOX_WIDTH.saturating_sub(get_visible_width(&self.painted)) / 2;
+        self.row(format_args!("{}{}", Repeat(" ", pad), content));
+    }
+
+    // a full-width line such as the box edges and separators
+    pub fn line(&mut self, line: &str) {
+        self.theme.paint_into(line, next_line(&mut self.lines, &mut self.len));
+    }
+
+    // the lines that fit a terminal of cols x rows, without a newline after
+    // the last one so a screen of exactly that height doesn't scroll
+    #[cfg(test)]
+    pub fn fit(&self, cols: usize, rows: usize) -> String {
+        let mut out = String::new();
+        self.fit_into(cols, rows, &mut out);
+        out
+    }
+
+    pub fn fit_into(&self, cols: usize, rows: usize, out: &mut String) {
+        out.clear();
+        for (i, line) in self.lines().iter().take(rows).enumerate() {
+            if i > 0 {
+                out.push_str("\r\n");
+            }
+            write_truncated(line, cols, out);
+        }
+    }
+}
+
+// the next line buffer, emptied
+fn next_line<'a>(lines: &'a mut Vec<String>, len: &mut usize) -> &'a mut String {
+    if *len == lines.len() {
+        lines.push(String::new());
+    }
+    *len += 1;
+    let line = &mut lines[*len - 1];
+    line.clear();
+    line
+}
+
+// terminal columns a character takes. the emoji icons are double width
+fn char_width(c: char) -> usize {
+    match c {
+        '🎧' | '🧭' | '🔊' | '📐' | '📡' | '📈' | '🎵' | '🔌' | '🔍' | '⏳' => 2,
+        _ => 1,
+    }
+}
+
+// helper: calculate string width ignoring ansi color codes
+// fixes border alignment by counting emojis as 2 width
+pub fn get_visible_width(s: &str) -> usize {
+    let mut counter = VisibleWidth::default();
+    counter.write_str(s).ok();
+    counter.width
+}
+
+// counts the visible columns of whatever is written to it
+#[derive(Default)]
+struct VisibleWidth {
+    width: usize,
+    inside_ansi: bool,
+}
+
+impl Write for VisibleWidth {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if c == '\x1B' {
+                self.inside_ansi = true;
+                continue;
+            }
+            if self.inside_ansi {
+                if c == 'm' {
+                    self.inside_ansi = false;
+                }
+                continue;
+            }
+            self.width += char_width(c);
+        }
+        Ok(())
+    }
+}
+
+// the rows are formatted straight into the screen's buffers; these write
+// their part while that happens instead of building a string first
+
+// `text` `count` times
+struct Repeat(&'static str, usize);
+
+impl fmt::Display for Repeat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for _ in 0..self.1 {
+            f.write_str(self.0)?;
+        }
+        Ok(())
+    }
+}
+
+// the value followed by spaces up to `width` visible columns
+struct Padded<T>(T, usize);
+
+impl<T: fmt::Display> fmt::Display for Padded<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut counter = VisibleWidth::default();
+        write!(counter, "{}", self.0)?;
+        write!(f, "{}{}", self.0, Repeat(" ", self.1.saturating_sub(counter.width)))
+    }
+}
+
+// the first `width` visible columns of `s`. escape codes are kept, and a
+// reset is added when the cut may have left a color open
+#[cfg(test)]
+fn truncate_visible(s: &str, width: usize) -> String {
+    let mut out = String::with_capacity(s.len());
+    write_truncated(s, width, &mut out);
+    out
+}
+
+// truncate_visible appended to `out`, returns the columns written
+fn write_truncated(s: &str, width: usize, out: &mut String) -> usize {
+    let visible = get_visible_width(s);
+    if visible <= width {
+        out.push_str(s);
+        return visible;
+    }
+    let mut used = 0;
+    let mut inside_ansi = false;
+    let mut colored = false;
+    for c in s.chars() {
+        if c == '\x1B' {
+            inside_ansi = true;
+            colored = true;
+        }
+        if inside_ansi {
+            if c == 'm' {
+                inside_ansi = false;
+            }
+            out.push(c);
+            continue;
+        }
+        if used + char_width(c) > width {
+            break;
+        }
+        used += char_width(c);
+        out.push(c);
+    }
+    if colored {
+        out.push_str("\x1B[0m");
+    }
+    used
+}
+
+// one line between the side borders, padded to the box width
+pub fn box_row(theme: Theme, content: &str) -> String {
+    let mut line = String::new();
+    write_box_row(theme, content, &mut String::new(), &mut line);
+    line
+}
+
+// box_row into `line`, with `painted` as scratch space
+fn write_box_row(theme: Theme, content: &str, painted: &mut String, line: &mut String) {
+    const BORDER: &str = "\x1B[1;96m║\x1B[0m";
+    painted.clear();
+    theme.paint_into(content, painted);
+    theme.paint_into(BORDER, line);
+    let used = write_truncated(painted, BOX_WIDTH, line);
+    for _ in used..BOX_WIDTH {
+        line.push(' ');
+    }
+    theme.paint_into(BORDER, line);
+}
+
+// a full-width line such as the box edges and separators
+pub fn box_line(theme: Theme, line: &str) -> String {
+    theme.paint(line)
+}
+
+// an azimuth position bar showing where a speaker is relative to center:
+// azimuth, width
+struct AzimuthBar(f64, usize);
+
+impl fmt::Display for AzimuthBar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let AzimuthBar(azimuth, width) = *self;
+        f.write_str("[")?;
+
+        // map azimuth (-180..180) to bar position
+        // clamp to reasonable range for display
+        let clamped = azimuth.clamp(-90.0, 90.0);
+        let normalized = (clamped + 90.0) / 180.0; // 0..1
+        let pos = (normalized * (width - 1) as f64).round() as usize;
+        let center_idx = width / 2;
+
+        for i in 0..width {
+            if i == pos {
+                f.write_str("\x1B[1;33m◆\x1B[0m")?; // speaker position marker
+            } else if i == center_idx {
+                f.write_str("\x1B[90m│\x1B[0m")?; // center line
+            } else {
+                f.write_str(" ")?;
+            }
+        }
+
+        f.write_str("]")
+    }
+}
+
+// one column per second, scaled to the busiest second. seconds well below
+// the typical rate are red so stalls stand out, empty ones stay blank
+struct RateGraph<'a>(&'a [u32]);
+
+impl fmt::Display for RateGraph<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+        let counts = self.0;
+        let max = counts.iter().copied().max().unwrap_or(0).max(1);
+        let mut sorted = [0; HISTORY_SECONDS];
+        let sorted = &mut sorted[..counts.len()];
+        sorted.copy_from_slice(counts);
+        sorted.sort_unstable();
+        let typical = sorted.get(sorted.len() / 2).copied().unwrap_or(0);
+
+        for &count in counts {
+            if count == 0 {
+                f.write_str(" ")?;
+                continue;
+            }
+            let level = LEVELS[((count * 8).div_ceil(max) as usize).clamp(1, 8) - 1];
+            if (count as f64) < typical as f64 * 0.75 {
+                write!(f, "\x1B[1;31m{}\x1B[0m", level)?;
+            } else {
+                write!(f, "\x1B[1;32m{}\x1B[0m", level)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// render an elevation indicator
+fn render_elevation_indicator(elevation: f64) -> &'static str {
+    if elevation > 10.0 {
+        "⬆ Above"
+    } else if elevation < -10.0 {
+        "⬇ Below"
+    } else {
+        "━ Level"
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn render_dashboard(
+    screen: &mut Screen,
+    engine: &Engine,
+    raw_yaw: f64,
+    raw_pitch: f64,
+    raw_roll: f64,
+    spatial: &SpatialState,
+    motion: &Motion,
+    fps: f64,
+    latency_ms: f64,
+    packets: u64,
+    packet_rate: &RateHistory,
+    update_rate: &RateHistory,
+    calibrator: &Calibrator,
+    tuner: &SmoothingTuner,
+    media: Option<&MediaController>,
+    mouse_status: Option<&str>,
+    drift: Option<&DriftCompensator>,
+    listeners: &[Listener],
+    sinks: &Sinks,
+    output: Option<&OutputSwitcher>,
+    tracker: Option<&TrackerSupervisor>,
+    default_sink: Option<&DefaultSinkWatcher>,
+    session: Option<&SessionWatcher>,
+    input: Option<(&str, Option<&(String, Instant)>)>,
+    filter_chain: Option<&FilterChain>,
+    comparison: Option<&FilterComparison>,
+    recorder: Option<&SessionRecorder>,
+    leveler: Option<&Leveler>,
+    volume_guard: &VolumeGuard,
+    theme: Theme,
+) {
+    screen.clear(theme);
+
+    let smoothed = &engine.smoothed;
+    let mode = engine.mode;
+    let reverb_enabled = engine.reverb_enabled;
+    let width = engine.width;
+
+    screen.line("\x1B[1;96m╔══════════════════════════════════════════════════════════════════╗\x1B[0m");
+
+    screen.centered_row("\x1B[1;37m🎧 SPATIAL AUDIO ENGINE (HRTF STEREO)\x1B[0m");
+    screen.line("\x1B[1;96m╠══════════════════════════════════════════════════════════════════╣\x1B[0m");
+
+    for (sink, lines) in sinks.iter().filter_map(|s| Some((s, s.error()?))) {
+        screen.row(format_args!("  \x1B[1;31m✗ {} ERROR\x1B[0m", sink.name().to_uppercase()));
+        screen.row("");
+        for line in lines {
+            screen.row(format_args!("    {}", line));
+        }
+        screen.row("");
+        screen.line("\x1B[1;96m╠══════════════════════════════════════════════════════════════════╣\x1B[0m");
+    }
+
+    screen.row(format_args!("  {}", "\x1B[1;33m🧭 HEAD TRACKING\x1B[0m"));
+    screen.row("");
+    screen.row(format_args!("    \x1B[90mRAW:\x1B[0m     Yaw={:>7.1}°  Pitch={:>7.1}°  Roll={:>7.1}°",
+                      raw_yaw, raw_pitch, raw_roll));
+    screen.row(format_args!("    \x1B[1;37mSMOOTH:\x1B[0m  Yaw={:>7.1}°  Pitch={:>7.1}°  Roll={:>7.1}°",
+                      smoothed.yaw, smoothed.pitch, smoothed.roll));
+    let [vy, vp, vr] = motion.velocity;
+    screen.row(format_args!("    \x1B[1;37mVEL:\x1B[0m     Yaw={:>+6.0}°/s  Pitch={:>+6.0}°/s  Roll={:>+6.0}°/s", vy, vp, vr));
+    let filled = (motion.intensity * 20.0).round() as usize;
+    screen.row(format_args!("    \x1B[1;37mMOTION:\x1B[0m  [\x1B[1;36m{}\x1B[90m{}\x1B[0m] {:>3.0}%  {:>4.0}°/s",
+                      Repeat("█", filled), Repeat("░", 20 - filled), motion.intensity * 100.0, motion.speed()));
+    screen.row(format_args!("    \x1B[1;37mSENS:\x1B[0m    Yaw=\x1B[1;37mx{:.2}\x1B[0m  Pitch=\x1B[1;37mx{:.2}\x1B[0m  {}",
+                      engine.yaw.sensitivity, engine.pitch.sensitivity, calibration_status(calibrator)));
+    render_tuning(screen, tuner);
+    if let Some(drift) = drift {
+        let status = match drift.calibration_remaining(Instant::now()) {
+            Some(left) => format!("\x1B[1;33mHOLD STILL {}s\x1B[0m", left.as_secs() + 1),
+            None => drift.last_event.clone().unwrap_or_default(),
+        };
+        screen.row(format_args!("    \x1B[1;37mDRIFT:\x1B[0m   {:>+6.3} {:>+6.3} {:>+6.3} °/s  {}",
+                          drift.bias[0], drift.bias[1], drift.bias[2], status));
+    }
+
+    screen.row("");
+    screen.line("\x1B[1;96m╠══════════════════════════════════════════════════════════════════╣\x1B[0m");
+
+    if let Some(comparison) = comparison {
+        screen.row(format_args!("  {}", "\x1B[1;36m🎛 FILTERS\x1B[0m  \x1B[90m(F switches)\x1B[0m"));
+        screen.row("");
+        for filter in &comparison.filters {
+            let pan = engine.spatial_at(filter.yaw, filter.pitch);
+            let (marker, color) = if filter.kind == smoothed.kind { ("▶", "\x1B[1;32m") } else { (" ", "\x1B[1;37m") };
+            screen.row(format_args!(
+                "    {}{} {:<9}\x1B[0m Yaw={:>+6.1}°  Pitch={:>+6.1}°  Pan L{:>+6.1}° R{:>+6.1}°",
+                color, marker, filter.kind.label(), filter.yaw, filter.pitch, pan.left_az, pan.right_az
+            ));
+        }
+        screen.row("");
+        screen.line("\x1B[1;96m╠══════════════════════════════════════════════════════════════════╣\x1B[0m");
+    }
+
+    let mode_color = match mode {
+        SpeakerMode::Front => "\x1B[1;32m",
+        SpeakerMode::Back => "\x1B[1;33m",
+    };
+    // headphones are the normal case and not worth a tag
+    let listening = match engine.listening {
+        Listening::Headphones => String::new(),
+        Listening::Speakers if engine.crosstalk_cancel > 0.0 => format!(
+            "  [\x1B[1;36m{}\x1B[0m xtalk -{:.0}%]", engine.listening.label(), engine.crosstalk_cancel * 100.0
+        ),
+        Listening::Speakers => format!("  [\x1B[1;36m{}\x1B[0m]", engine.listening.label()),
+    };
+    screen.row(format_args!("  \x1B[1;35m🔊 VIRTUAL SPEAKERS\x1B[0m  [{}{}°\x1B[0m]{}", mode_color, mode.label(), listening));
+    screen.row("");
+
+    let adjust_display_azimuth = |a: f64| -> f64 {
+        let mut x = a;
+        // normalize to -180..180
+        while x <= -180.0 { x += 360.0; }
+        while x > 180.0 { x -= 360.0; }
+        // so it doesnt clamp to the end
+        if x > 90.0 {
+            x -= 180.0;
+        } else if x < -90.0 {
+            x += 180.0;
+        }
+        x
+    };
+
+    let left_display = adjust_display_azimuth(spatial.right_az);
+    let right_display = adjust_display_azimuth(spatial.left_az);
+
+    let l_bar = AzimuthBar(left_display, 24);
+    screen.row(format_args!("    \x1B[1;34mLeft Speaker:\x1B[0m  {}  {:>+6.1}°", l_bar, left_display));
+
+    let r_bar = AzimuthBar(right_display, 24);
+    screen.row(format_args!("    \x1B[1;35mRight Speaker:\x1B[0m {}  {:>+6.1}°", r_bar, right_display));
+
+    screen.row("");
+
+    let pitch = engine.mapped().1;
+    let amount = engine.pitch_target.amount(pitch);
+    match engine.pitch_target {
+        PitchTarget::Elevation => {
+            let elev_indicator = render_elevation_indicator(spatial.elevation);
+            screen.row(format_args!("    \x1B[1;37mElevation:\x1B[0m {:>+6.1}°  {}", spatial.elevation, elev_indicator));
+        }
+        PitchTarget::Volume | PitchTarget::Tilt => screen.row(format_args!(
+            "    \x1B[1;37mPitch:\x1B[0m     {:>+6.1}°  → {} {:>+5.1}dB", pitch, engine.pitch_target.label(), amount
+        )),
+        PitchTarget::Crossfeed => screen.row(format_args!(
+            "    \x1B[1;37mPitch:\x1B[0m     {:>+6.1}°  → {} {:>3.0}%", pitch, engine.pitch_target.label(), amount * 100.0
+        )),
+        PitchTarget::None => screen.row(format_args!("    \x1B[1;37mPitch:\x1B[0m     {:>+6.1}°  (not used)", pitch)),
+    }
+
+    let gain_pct = spatial.gain * 100.0;
+    screen.row(format_args!("    \x1B[1;37mRadius:\x1B[0m    {:>6.2}m  (Gain: {:>3.0}%)", spatial.radius, gain_pct));
+
+    // displayed left/right follow the azimuth bars above
+    let gain_db = |g: f64| 20.0 * (g / spatial.gain).log10();
+    screen.row(format_args!("    \x1B[1;37mRear:\x1B[0m      L {:>+5.1}dB HF {:>+5.1}dB   R {:>+5.1}dB HF {:>+5.1}dB",
+                      gain_db(spatial.right_gain), spatial.right_shelf_db,
+                      gain_db(spatial.left_gain), spatial.left_shelf_db));
+
+    let reverb_pct = spatial.reverb_gain * 100.0;
+    let reverb_status = if reverb_enabled { "\x1B[1;32mON\x1B[0m" } else { "\x1B[1;31mOFF\x1B[0m" };
+    screen.row(format_args!("    \x1B[1;37mReverb:\x1B[0m   {:>6.1}%  [{}]", reverb_pct, reverb_status));
+
+    screen.row("");
+    screen.line("\x1B[1;96m╠══════════════════════════════════════════════════════════════════╣\x1B[0m");
+
+    screen.row(format_args!("  {}", "\x1B[1;33m📐 STEREO FIELD\x1B[0m"));
+    screen.row("");
+
+    let width_pct = width * 100.0;
+    let width_desc = if width >= 1.2 {
+        "\x1B[1;36mVery Wide\x1B[0m"
+    } else if width >= 0.8 {
+        "\x1B[1;37mNormal\x1B[0m"
+    } else {
+        "\x1B[1;33mNarrow\x1B[0m"
+    };
+    screen.row(format_args!("    \x1B[1;37mWidth:\x1B[0m    {:>6.0}%  ({})", width_pct, width_desc));
+
+    let sep_angle = (spatial.left_az - spatial.right_az).abs();
+    screen.row(format_args!("    \x1B[1;37mSeparation:\x1B[0m {:>5.1}°  (speaker spread)", sep_angle));
+
+    if engine.lean_width != 0.0 {
+        screen.row(format_args!("    \x1B[1;37mLean:\x1B[0m     {:>+6.1}cm  (width x{:.2}, stereo {:>3.0}%)",
+                          engine.lean, engine.lean_factor(), spatial.stereo_mix * 100.0));
+    }
+
+    screen.row("");
+    screen.line("\x1B[1;96m╠══════════════════════════════════════════════════════════════════╣\x1B[0m");
+
+    if let Some(media) = media {
+        render_media_panel(screen, media);
+        screen.line("\x1B[1;96m╠══════════════════════════════════════════════════════════════════╣\x1B[0m");
+    }
+
+    screen.row(format_args!("  {}", "\x1B[1;32m📡 CONNECTION\x1B[0m"));
+    screen.row("");
+
+    if let Some((describe, event)) = input {
+        // inputs listened on together, one per row
+        for (i, part) in describe.split(" + ").enumerate() {
+            let label = if i == 0 { "Input:" } else { "    + " };
+            screen.row(format_args!("    \x1B[1;37m{}\x1B[0m {}", label, part));
+        }
+        if let Some((event, at)) = event {
+            screen.row(format_args!("      \x1B[1;33m{}\x1B[0m \x1B[90m{}s ago\x1B[0m", event, at.elapsed().as_secs()));
+        }
+    }
+    for sink in sinks.iter() {
+        screen.row(format_args!("    {}", Status(sink)));
+    }
+    for (i, l) in listeners.iter().enumerate() {
+        let node = match l.pipewire.node_id {
+            Some(ref id) => format!("\x1B[1;32m✓\x1B[0m Node \x1B[1;37m{}\x1B[0m", id),
+            None => format!("\x1B[1;31m✗\x1B[0m '{}'", l.pipewire.node_name),
+        };
+        screen.row(format_args!(
+            "    \x1B[1;37m#{}\x1B[0m {} {}  yaw {:>+6.1}°  pkts {}",
+            i + 2, l.input.describe(), node, l.engine.smoothed.yaw, l.packets
+        ));
+    }
+    if let Some(tracker) = tracker {
+        let now = Instant::now();
+        let status = match tracker.status {
+            TrackerStatus::Running { pid, since } => format!(
+                "\x1B[1;32m✓ RUNNING\x1B[0m pid {}  up {}s",
+                pid, now.duration_since(since).as_secs()
+            ),
+            TrackerStatus::Waiting { restart_at, exit } => {
+                let why = match exit {
+                    Some(exit) => format!("exited ({})", exit.code().map_or("signal".to_string(), |c| c.to_string())),
+                    None => "failed to launch".to_string(),
+                };
+                format!(
+                    "\x1B[1;31m✗\x1B[0m {}, restart in {}s",
+                    why, restart_at.saturating_duration_since(now).as_secs()
+                )
+            }
+        };
+        screen.row(format_args!("    \x1B[1;37mTracker:\x1B[0m {}  restarts {}", status, tracker.restarts));
+    }
+    if let Some(output) = output {
+        let now = Instant::now();
+        let current = match output.active {
+            Some(i) => format!("\x1B[1;37m{}\x1B[0m", output.zones()[i].sink),
+            None => "\x1B[90munchanged\x1B[0m".to_string(),
+        };
+        let pending = match output.pending(now) {
+            Some((i, progress)) => format!("  → {} {:>3.0}%", output.zones()[i].sink, progress * 100.0),
+            None => String::new(),
+        };
+        screen.row(format_args!("    \x1B[1;37mOutput:\x1B[0m {}{}", current, pending));
+    }
+    if let Some(chain) = filter_chain {
+        let now = Instant::now();
+        let status = match chain.status {
+            ChainStatus::Loaded { pid, since } => format!(
+                "\x1B[1;32m✓ MANAGED\x1B[0m pid {}  up {}s",
+                pid, now.duration_since(since).as_secs()
+            ),
+            ChainStatus::Reused => "\x1B[1;32m✓ REUSED\x1B[0m (not started by spatial-track)".to_string(),
+            ChainStatus::Waiting { restart_at, exit } => {
+                let why = match (exit, &chain.last_error) {
+                    (Some(exit), _) => format!("exited ({})", exit.code().map_or("signal".to_string(), |c| c.to_string())),
+                    (None, Some(e)) => e.clone(),
+                    (None, None) => "failed to load".to_string(),
+                };
+                format!("\x1B[1;31m✗\x1B[0m {}, reload in {}s", why, restart_at.saturating_duration_since(now).as_secs())
+            }
+        };
+        screen.row(format_args!("    \x1B[1;37mFilter-chain:\x1B[0m {}  restarts {}", status, chain.restarts));
+    }
+    if let Some(leveler) = leveler {
+        let status = match (leveler.loudness_db, &leveler.error) {
+            (_, Some(e)) => format!("\x1B[1;31m✗\x1B[0m {}", e),
+            (None, None) => "waiting for audio".to_string(),
+            (Some(loudness), None) => format!("{:>6.1} dBFS  gain \x1B[1;37m{:>+5.1} dB\x1B[0m", loudness, leveler.gain_db),
+        };
+        screen.row(format_args!("    \x1B[1;37mLoudness:\x1B[0m {}", status));
+    }
+    if let Some((reason, at)) = volume_guard.last_limit {
+        let ago = at.elapsed().as_secs();
+        let status = if ago < 2 {
+            format!("\x1B[1;33mLIMITING\x1B[0m {}", reason)
+        } else {
+            format!("\x1B[90m{} {}s ago\x1B[0m", reason, ago)
+        };
+        screen.row(format_args!("    \x1B[1;37mLimiter:\x1B[0m {}  updates {}", status, volume_guard.limited));
+    }
+    if let Some(recorder) = recorder {
+        let status = match recorder.exit {
+            None => {
+                let secs = recorder.started.elapsed().as_secs();
+                format!(
+                    "\x1B[1;31m⏺ REC\x1B[0m {}:{:02}  {:.1} MB",
+                    secs / 60, secs % 60, recorder.size() as f64 / 1_000_000.0
+                )
+            }
+            Some(exit) => format!("\x1B[1;31m✗\x1B[0m pw-record stopped ({})", exit),
+        };
+        screen.row(format_args!("    \x1B[1;37mRecording:\x1B[0m {}  {}", status, recorder.path.display()));
+    }
+    if let Some(watcher) = default_sink {
+        let status = match (&watcher.sink, watcher.class, &watcher.error) {
+            (_, _, Some(e)) => format!("\x1B[1;31m✗\x1B[0m {}", e),
+            (Some(sink), Some(DeviceClass::Hdmi), _) => {
+                format!("{} \x1B[1;33m(hdmi, paused)\x1B[0m", sink.chars().take(34).collect::<String>())
+            }
+            (Some(sink), Some(class), _) => format!("{} ({})", sink.chars().take(34).collect::<String>(), class.label()),
+            _ => "\x1B[90mwaiting for pw-metadata\x1B[0m".to_string(),
+        };
+        screen.row(format_args!("    \x1B[1;37mDevice:\x1B[0m {}", status));
+    }
+    if let Some(watcher) = session {
+        let status = match &watcher.error {
+            Some(e) => format!("\x1B[1;31m✗\x1B[0m {}", e),
+            None if !watcher.known => "\x1B[90mwaiting for loginctl\x1B[0m".to_string(),
+            None if watcher.paused => format!("{} \x1B[1;33m(paused, speakers in front)\x1B[0m", watcher.label()),
+            None => watcher.label().to_string(),
+        };
+        screen.row(format_args!("    \x1B[1;37mSession:\x1B[0m {}", status));
+    }
+    if let Some(mouse) = mouse_status {
+        screen.row(format_args!("    \x1B[1;37mMouse look:\x1B[0m {}", mouse));
+    }
+
+    screen.row("");
+    screen.line("\x1B[1;96m╠══════════════════════════════════════════════════════════════════╣\x1B[0m");
+
+    screen.row(format_args!("  {}", "\x1B[1;34m📈 STATS\x1B[0m"));
+    screen.row("");
+
+    let col_width = 25;
+
+    let fps_str = Padded(format_args!("FPS: \x1B[1;37m{:>5.1}\x1B[0m", fps), col_width);
+    let lat_str = format_args!("Latency: \x1B[1;37m{:>5.2}ms\x1B[0m", latency_ms);
+    screen.row(format_args!("    {}  │  {}", fps_str, lat_str));
+
+    let pkts_str = Padded(format_args!("Packets: \x1B[1;37m{}\x1B[0m", packets), col_width);
+    let thresh_str = format_args!("Threshold: \x1B[1;37m{:.1}°\x1B[0m", CHANGE_THRESHOLD);
+    screen.row(format_args!("    {}  │  {}", pkts_str, thresh_str));
+
+    // one value, or yaw/pitch/roll once they differ
+    match smoothed.factors().map(|k| k * 100.0) {
+        [y, p, r] if y == p && p == r => {
+            screen.row(format_args!("    {}  │", Padded(format_args!("Smoothing: \x1B[1;37m{:.0}%\x1B[0m", y), col_width)))
+        }
+        [y, p, r] => screen.row(format_args!(
+            "    {}  │",
+            Padded(format_args!("Smoothing: \x1B[1;37m{:.0}/{:.0}/{:.0}%\x1B[0m", y, p, r), col_width)
+        )),
+    }
+
+    // per second history, right aligned so the newest second is at the edge
+    for (label, history) in [("Packets/s", packet_rate), ("Updates/s", update_rate)] {
+        let mut counts = [0; HISTORY_SECONDS];
+        let len = counts.iter_mut().zip(history.completed()).map(|(slot, count)| *slot = count).count();
+        let counts = &counts[..len];
+        let (min, max) = (counts.iter().min().unwrap_or(&0), counts.iter().max().unwrap_or(&0));
+        screen.row("");
+        screen.row(format_args!("    \x1B[1;37m{}\x1B[0m  last {}s  min \x1B[1;37m{}\x1B[0m  max \x1B[1;37m{}\x1B[0m",
+                          label, HISTORY_SECONDS, min, max));
+        screen.row(format_args!("  {}{}", Repeat(" ", HISTORY_SECONDS - counts.len()), RateGraph(counts)));
+    }
+
+    screen.row("");
+    screen.line("\x1B[1;96m╠══════════════════════════════════════════════════════════════════╣\x1B[0m");
+
+    screen.row(format_args!("  {}", "\x1B[1;90m⌨ CONTROLS\x1B[0m"));
+    screen.row("    \x1B[90m↑/↓\x1B[0m Radius   \x1B[90m←/→\x1B[0m Width   \x1B[90mW\x1B[0m Front   \x1B[90mS\x1B[0m Back   \x1B[90mM\x1B[0m Mouse   \x1B[90mG\x1B[0m Bias");
+    screen.row("    \x1B[90mR\x1B[0m Reverb   \x1B[90mSpace\x1B[0m Center   \x1B[90mC\x1B[0m Calibrate   \x1B[90mA\x1B[0m Apply   \x1B[90mQ/Esc\x1B[0m Quit");
+    screen.row(format_args!(
+        "    \x1B[90mN\x1B[0m Tune smoothing   \x1B[90mY\x1B[0m Apply   \x1B[90mF\x1B[0m Filter ({})",
+        smoothed.kind.label()
+    ));
+    screen.row(format_args!("    \x1B[90mT\x1B[0m Theme ({})", theme.label()));
+    screen.line("\x1B[1;96m╚══════════════════════════════════════════════════════════════════╝\x1B[0m");
+}
+
+// dwell progress and last triggered action for the accessibility mode
+fn render_media_panel(screen: &mut Screen, media: &MediaController) {
+    let now = Instant::now();
+    screen.row(format_args!("  {}", "\x1B[1;36m🎵 MEDIA CONTROL\x1B[0m"));
+    screen.row("");
+
+    let zone = media.zone();
+    let target = match zone.hold_action() {
+        Some(action) => format!("→ {}", action.label()),
+        None => String::new(),
+    };
+    let progress = media.dwell_progress(now).unwrap_or(0.0);
+    let filled = (progress * 10.0).round() as usize;
+    let bar = format_args!("\x1B[1;36m{}\x1B[90m{}\x1B[0m", Repeat("█", filled), Repeat("░", 10 - filled));
+    screen.row(format_args!("    \x1B[1;37mZone:\x1B[0m     {:<10} {:<18} [{}]", zone.label(), target, bar));
+
+    let last = match media.last_action {
+        Some((action, at)) => format!(
+            "\x1B[1;32m✓ {}\x1B[0m ({:.0}s ago)",
+            action.label(),
+            now.duration_since(at).as_secs_f64()
+        ),
+        None => "\x1B[90mnone yet\x1B[0m".to_string(),
+    };
+    screen.row(format_args!("    \x1B[1;37mLast:\x1B[0m     {}", last));
+    screen.row("    \x1B[90mhold ↑/↓ volume  ←/→ prev/next  nod play  shake mute\x1B[0m");
+    screen.row("");
+}
+
+// smoothing analysis progress, or its proposal with what it is based on
+fn render_tuning(screen: &mut Screen, tuner: &SmoothingTuner) {
+    let left = tuner.remaining(Instant::now()).as_secs() + 1;
+    match (tuner.phase(), tuner.proposal()) {
+        (Some(Phase::Still), _) => screen.row(format_args!("    \x1B[1;37mTUNE:\x1B[0m    \x1B[1;33mHOLD STILL {}s\x1B[0m", left)),
+        (Some(Phase::Moving), _) => screen.row(format_args!("    \x1B[1;37mTUNE:\x1B[0m    \x1B[1;33mTURN AND NOD {}s\x1B[0m", left)),
+        (None, Some(p)) => {
+            let [y, pi, r] = p.factors();
+            screen.row(format_args!("    \x1B[1;37mTUNE:\x1B[0m    \x1B[1;32m→ Yaw={:.2}  Pitch={:.2}  Roll={:.2}\x1B[0m  (Y)", y, pi, r));
+            // yaw, pitch, roll side by side, * = held back by the lag budget
+            let columns = |f: fn(&AxisAnalysis) -> String| p.axes.iter().map(f).collect::<Vec<_>>().join("  ");
+            screen.row(format_args!("             \x1B[90mnoise\x1B[0m   {}",
+                              columns(|a| format!("{:>5.2}°{:>5}", a.noise, format!("{:.0}Hz", a.noise_hz)))));
+            screen.row(format_args!("             \x1B[90mjitter\x1B[0m  {}",
+                              columns(|a| format!("{:>5.2}→{:<5.2}", a.current.0, a.proposed.0))));
+            screen.row(format_args!("             \x1B[90mlag\x1B[0m     {}",
+                              columns(|a| format!("{:>4.0}→{:>3.0}ms{}", a.current.1, a.proposed.1, if a.lag_limited { "*" } else { " " }))));
+        }
+        (None, None) => {
+            if let Some(ref event) = tuner.last_event {
+                screen.row(format_args!("    \x1B[1;37mTUNE:\x1B[0m    {} \x1B[90m(N retries)\x1B[0m", event));
+            }
+        }
+    }
+}
+
+// short calibration hint for the head tracking panel
+fn calibration_status(calibrator: &Calibrator) -> String {
+    if calibrator.is_running() {
+        let left = calibrator.remaining(Instant::now()).as_secs();
+        format!("\x1B[1;33mCALIBRATING {}s\x1B[0m", left)
+    } else if let Some(p) = calibrator.proposal() {
+        format!("\x1B[1;32m→ x{:.2}/x{:.2} (A)\x1B[0m", p.yaw_sensitivity, p.pitch_sensitivity)
+    } else {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::{Duration, Instant};
+
+    use spatial_track::sink::{Frame, OutputSink};
+
+    use super::*;
+    use crate::theme::THEMES;
+
+    struct TestSink {
+        status: String,
+        error: Option<Vec<String>>,
+    }
+
+    impl OutputSink for TestSink {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        fn send(&mut self, _frame: &Frame) {}
+
+        fn status(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(&self.status)
+        }
+
+        fn error(&self) -> Option<Vec<String>> {
+            self.error.clone()
+        }
+    }
+
+    // the panels that don't depend on the clock, with a packet history that
+    // has a gap in it
+    fn render(engine: &Engine, motion: &Motion, packets: u64, sinks: &Sinks, theme: Theme) -> Screen {
+        let start = Instant::now();
+        let mut packet_rate = RateHistory::new(start);
+        for second in 0..12u64 {
+            let count = if second == 7 { 5 } else { 30 };
+            for _ in 0..count {
+                packet_rate.record(start + Duration::from_secs(second));
+            }
+        }
+        packet_rate.advance(start + Duration::from_secs(12));
+        let mut update_rate = RateHistory::new(start);
+        update_rate.advance(start + Duration::from_secs(3));
+        let spatial = engine.spatial_at(engine.smoothed.yaw, engine.smoothed.pitch);
+        let mut screen = Screen::new(theme);
+        render_dashboard(
+            &mut screen,
+            engine,
+            engine.smoothed.yaw,
+            engine.smoothed.pitch,
+            engine.smoothed.roll,
+            &spatial,
+            motion,
+            49.96,
+            1.25,
+            packets,
+            &packet_rate,
+            &update_rate,
+            &Calibrator::new(),
+            &SmoothingTuner::new(),
+            None,
+            Some("\x1B[1;33mPAUSED\x1B[0m (M to resume)"),
+            None,
+            &[],
+            sinks,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            &VolumeGuard::new(1.0, 6.0),
+            theme,
+        );
+        screen
+    }
+
+    fn test_sinks(status: &str, error: Option<Vec<String>>) -> Sinks {
+        let mut sinks = Sinks::new();
+        sinks.add(Box::new(TestSink { status: status.to_string(), error }), Duration::ZERO);
+        sinks
+    }
+
+    fn turned_engine(yaw: f64, pitch: f64, roll: f64) -> Engine {
+        let mut engine = Engine::new();
+        engine.smoothed.yaw = yaw;
+        engine.smoothed.pitch = pitch;
+        engine.smoothed.roll = roll;
+        engine
+    }
+
+    // every line, borders included, is exactly as wide as the box
+    fn assert_aligned(screen: &Screen) {
+        for (i, line) in screen.lines().iter().enumerate() {
+            assert_eq!(get_visible_width(line), BOX_WIDTH + 2, "line {} is misaligned: {:?}", i + 1, line);
+        }
+    }
+
+    // compare with src/snapshots/<name>.txt, escape codes written as \e.
+    // UPDATE_SNAPSHOTS=1 cargo test writes the current output instead
+    fn assert_snapshot(name: &str, text: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/snapshots").join(format!("{}.txt", name));
+        let actual = format!("{}\n", text.replace('\x1B', "\\e").replace("\r\n", "\n"));
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, &actual).unwrap();
+            return;
+        }
+        let expected = fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("Failed to read {}: {} (UPDATE_SNAPSHOTS=1 creates it)", path.display(), e));
+        if let Some((i, (a, e))) = actual.lines().zip(expected.lines()).enumerate().find(|(_, (a, e))| a != e) {
+            panic!("snapshot {} differs at line {}\n  expected: {}\n  actual:   {}", name, i + 1, e, a);
+        }
+        assert_eq!(actual.lines().count(), expected.lines().count(), "snapshot {} has a different number of lines", name);
+    }
+
+    #[test]
+    fn dashboard() {
+        let engine = turned_engine(30.0, -5.0, 2.5);
+        let motion = Motion { velocity: [40.0, -3.0, 0.0], intensity: 0.35 };
+        let screen = render(&engine, &motion, 1234, &test_sinks("\x1B[1;32m✓\x1B[0m Test sink ready", None), Theme::Default);
+        assert_aligned(&screen);
+        assert_snapshot("dashboard", &screen.fit(200, 200));
+    }
+
+    #[test]
+    fn wide_values() {
+        let engine = turned_engine(-179.94, 1234.5, -98765.4);
+        let motion = Motion { velocity: [-123456.0, 99999.0, -7.0], intensity: 1.0 };
+        let long = "a sink status line that is far too long for the box and has to be cut before the border";
+        let error = vec![long.to_string(), "\x1B[1;31mred text\x1B[0m that keeps going past the right border of the box".to_string()];
+        let screen = render(&engine, &motion, u64::MAX, &test_sinks(long, Some(error)), Theme::Default);
+        assert_aligned(&screen);
+        assert_snapshot("wide_values", &screen.fit(200, 200));
+    }
+
+    #[test]
+    fn every_theme_keeps_the_border() {
+        let engine = turned_engine(-45.0, 12.0, 0.0);
+        let motion = Motion::default();
+        for theme in THEMES {
+            assert_aligned(&render(&engine, &motion, 99, &test_sinks("ready", None), theme));
+        }
+        let screen = render(&engine, &motion, 99, &test_sinks("\x1B[1;32m✓\x1B[0m ready", None), Theme::Ascii);
+        assert!(screen.lines().iter().all(|line| line.is_ascii() && !line.contains('\x1B')));
+        assert_snapshot("ascii", &screen.fit(200, 200));
+    }
+
+    #[test]
+    fn small_terminal() {
+        let engine = turned_engine(10.0, 0.0, 0.0);
+        let screen = render(&engine, &Motion::default(), 42, &Sinks::new(), Theme::Default);
+        let text = screen.fit(40, 12);
+        let lines: Vec<&str> = text.split("\r\n").collect();
+        assert_eq!(lines.len(), 12);
+        assert!(lines.iter().all(|line| get_visible_width(line) <= 40));
+        assert!(!text.ends_with('\n'));
+        assert_snapshot("small_terminal", &text);
+    }
+
+    #[test]
+    fn truncate_counts_visible_columns() {
+        assert_eq!(truncate_visible("plain", 10), "plain");
+        assert_eq!(truncate_visible("\x1B[1;33mabcdef\x1B[0m", 3), "\x1B[1;33mabc\x1B[0m");
+        // a double width icon that doesn't fit is left out whole
+        assert_eq!(truncate_visible("ab🎧c", 3), "ab");
+        assert_eq!(get_visible_width(&truncate_visible("\x1B[90m🔌 Opening\x1B[0m", 5)), 5);
+    }
+}
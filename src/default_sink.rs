@@ -0,0 +1,185 @@
+// follow the system's default output device. `pw-metadata -m` reports every
+// change of default.audio.sink in pipewire's "default" metadata; the new sink
+// is classified by its node name (or a --sink-class rule) and the caller
+// adapts: headphones get the binaural output, speakers the speaker pan, and
+// on hdmi (usually a tv) spatialization is paused with the speakers held in
+// front instead of following the head.
+
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// pw-metadata exits with pipewire, try again after this long
+const RESTART_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DeviceClass {
+    Headphones,
+    Speakers,
+    Hdmi,
+}
+
+impl DeviceClass {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "headphones" => Some(DeviceClass::Headphones),
+            "speakers" => Some(DeviceClass::Speakers),
+            "hdmi" => Some(DeviceClass::Hdmi),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DeviceClass::Headphones => "headphones",
+            DeviceClass::Speakers => "speakers",
+            DeviceClass::Hdmi => "hdmi",
+        }
+    }
+}
+
+// user override: sinks whose node name contains `pattern` are `class`
+#[derive(Clone)]
+pub struct SinkClass {
+    pub pattern: String,
+    pub class: DeviceClass,
+}
+
+impl SinkClass {
+    // "<part of the sink name>=<headphones|speakers|hdmi>"
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid sink class '{}', expected <name>=<headphones|speakers|hdmi>", spec);
+        let (pattern, class) = spec.rsplit_once('=').ok_or_else(invalid)?;
+        let class = DeviceClass::parse(class).ok_or_else(invalid)?;
+        if pattern.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self { pattern: pattern.to_string(), class })
+    }
+}
+
+// rules first, then a guess from names like alsa_output.pci-0000_00_1f.3.hdmi-stereo.
+// analog and bluetooth outputs are taken as headphones, the case this tool is for
+pub fn classify(sink: &str, rules: &[SinkClass]) -> DeviceClass {
+    if let Some(rule) = rules.iter().find(|r| sink.contains(&r.pattern)) {
+        return rule.class;
+    }
+    let name = sink.to_lowercase();
+    if ["hdmi", "displayport", "iec958"].iter().any(|k| name.contains(k)) {
+        DeviceClass::Hdmi
+    } else if name.contains("speaker") {
+        DeviceClass::Speakers
+    } else {
+        DeviceClass::Headphones
+    }
+}
+
+// the sink name from a pw-metadata line about default.audio.sink, e.g.
+// update: id:0 key:'default.audio.sink' value:'{"name":"alsa_output.usb-headset"}' type:'Spa:String:JSON'
+// Some(None) when the default was removed, None for other keys
+fn parse_update(line: &str) -> Option<Option<String>> {
+    if !line.contains("key:'default.audio.sink'") {
+        return None;
+    }
+    let value = line.split_once("value:'").map_or("", |(_, v)| v);
+    let name = value
+        .split_once("\"name\":\"")
+        .and_then(|(_, rest)| rest.split_once('"'))
+        .map(|(name, _)| name.to_string());
+    Some(name)
+}
+
+pub struct DefaultSinkWatcher {
+    rules: Vec<SinkClass>,
+    monitor: Option<(Child, Receiver<Option<String>>)>,
+    restart_at: Option<Instant>,
+    pub sink: Option<String>,
+    pub class: Option<DeviceClass>,
+    pub error: Option<String>,
+}
+
+impl DefaultSinkWatcher {
+    pub fn new(rules: Vec<SinkClass>) -> Self {
+        Self { rules, monitor: None, restart_at: Some(Instant::now()), sink: None, class: None, error: None }
+    }
+
+    // the new class when the default sink changed to another class, never blocks
+    pub fn poll(&mut self, now: Instant) -> Option<DeviceClass> {
+        if self.restart_at.is_some_and(|t| now >= t) {
+            self.restart_at = None;
+            match start_monitor() {
+                Ok(monitor) => {
+                    self.monitor = Some(monitor);
+                    self.error = None;
+                }
+                Err(e) => {
+                    self.error = Some(e);
+                    self.restart_at = Some(now + RESTART_DELAY);
+                }
+            }
+        }
+
+        let (_, updates) = self.monitor.as_ref()?;
+        let mut exited = false;
+        loop {
+            match updates.try_recv() {
+                Ok(sink) => self.sink = sink,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    exited = true;
+                    break;
+                }
+            }
+        }
+        if exited {
+            if let Some((mut child, _)) = self.monitor.take() {
+                child.kill().ok();
+                child.wait().ok();
+            }
+            self.error = Some("pw-metadata exited".to_string());
+            self.restart_at = Some(now + RESTART_DELAY);
+        }
+
+        let class = self.sink.as_deref().map(|s| classify(s, &self.rules));
+        if class == self.class {
+            return None;
+        }
+        self.class = class;
+        class
+    }
+}
+
+impl Drop for DefaultSinkWatcher {
+    fn drop(&mut self) {
+        if let Some((ref mut child, _)) = self.monitor {
+            child.kill().ok();
+            child.wait().ok();
+        }
+    }
+}
+
+// pw-metadata in monitor mode, its lines parsed on a reader thread
+fn start_monitor() -> Result<(Child, Receiver<Option<String>>), String> {
+    let mut child = Command::new("pw-metadata")
+        .args(["-m", "-n", "default"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run pw-metadata: {}", e))?;
+    let stdout = child.stdout.take().ok_or("Failed to read pw-metadata output")?;
+
+    let (tx, updates) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if let Some(update) = parse_update(&line) {
+                if tx.send(update).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    Ok((child, updates))
+}
@@ -0,0 +1,172 @@
+// `spatial-track demo`: hear the spatializer without a tracker. a synthetic
+// input (demo://<scene>) moves a virtual head through the normal pipeline,
+// and a test stream from pw-cat plays into the filter-chain: ticks on the
+// left channel and a low tone on the right, so both virtual speakers can be
+// followed as they move around you.
+
+use std::f64::consts::TAU;
+use std::io::Write;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::input::{InputSource, Pose};
+
+pub const DEFAULT_SCENE: &str = "orbit";
+pub const SCENES: [&str; 2] = ["orbit", "positions"];
+
+// poses per second from the synthetic head
+const POSE_INTERVAL: Duration = Duration::from_millis(20);
+// one full turn of the orbit scene
+const ORBIT_PERIOD: f64 = 10.0;
+// how long the positions scene stays at each position
+const HOLD_TIME: f64 = 3.0;
+// front, left, behind, right
+const POSITIONS: [f64; 4] = [0.0, -90.0, 180.0, 90.0];
+
+const SAMPLE_RATE: u32 = 48000;
+
+#[derive(Clone, Copy)]
+enum Scene {
+    // the sound circles the listener
+    Orbit,
+    // the sound jumps between fixed directions
+    Positions,
+}
+
+pub struct DemoInput {
+    uri: String,
+    scene: Scene,
+    start: Instant,
+    next: Instant,
+}
+
+impl DemoInput {
+    pub fn new(uri: &str, scene: &str) -> Result<Self, String> {
+        let scene = match scene {
+            "" | "orbit" => Scene::Orbit,
+            "positions" => Scene::Positions,
+            other => return Err(format!("unknown demo scene '{}' (available: {})", other, SCENES.join(", "))),
+        };
+        let now = Instant::now();
+        Ok(Self { uri: uri.to_string(), scene, start: now, next: now })
+    }
+
+    // head yaw at `t` seconds; the sound moves the opposite way
+    fn yaw(&self, t: f64) -> f64 {
+        match self.scene {
+            Scene::Orbit => -360.0 * (t / ORBIT_PERIOD).fract() + 180.0,
+            Scene::Positions => POSITIONS[(t / HOLD_TIME) as usize % POSITIONS.len()],
+        }
+    }
+}
+
+impl InputSource for DemoInput {
+    fn recv(&mut self, timeout: Duration) -> Result<Option<Pose>, String> {
+        let now = Instant::now();
+        if now < self.next {
+            let wait = self.next - now;
+            if wait > timeout {
+                thread::sleep(timeout);
+                return Ok(None);
+            }
+            thread::sleep(wait);
+        }
+
+        let now = Instant::now();
+        // don't try to catch up after a stall, just continue from here
+        self.next = (self.next + POSE_INTERVAL).max(now);
+        let t = now.duration_since(self.start).as_secs_f64();
+        // a slow nod so elevation moves a little too
+        let pitch = 10.0 * (TAU * t / (ORBIT_PERIOD * 1.5)).sin();
+        Ok(Some(Pose { translation: [0.0; 3], yaw: self.yaw(t), pitch, roll: 0.0, time: now }))
+    }
+
+    fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    fn describe(&self) -> String {
+        let scene = match self.scene {
+            Scene::Orbit => "orbit",
+            Scene::Positions => "positions",
+        };
+        format!("demo scene '{}' (no tracker needed)", scene)
+    }
+}
+
+// test stream played into the spatializer until dropped
+pub struct DemoSound {
+    child: Child,
+}
+
+impl DemoSound {
+    pub fn spawn(target: &str) -> Result<Self, String> {
+        let mut child = Command::new("pw-cat")
+            .args(["--playback", "--target", target, "--media-name", "spatial-track demo"])
+            .args(["--format", "s16", "--rate", &SAMPLE_RATE.to_string(), "--channels", "2", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to run pw-cat: {}", e))?;
+
+        let mut stdin = child.stdin.take().ok_or("pw-cat has no stdin")?;
+        // pw-cat reads at playback speed, so the blocking writes pace this thread
+        thread::spawn(move || {
+            let mut synth = Synth::new();
+            let mut buf = Vec::with_capacity(SAMPLE_RATE as usize / 50 * 4);
+            loop {
+                buf.clear();
+                for _ in 0..SAMPLE_RATE / 50 {
+                    let (l, r) = synth.next();
+                    buf.extend_from_slice(&((l * i16::MAX as f64) as i16).to_le_bytes());
+                    buf.extend_from_slice(&((r * i16::MAX as f64) as i16).to_le_bytes());
+                }
+                if stdin.write_all(&buf).is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(Self { child })
+    }
+}
+
+impl Drop for DemoSound {
+    fn drop(&mut self) {
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+// left: short noise ticks twice a second, right: a soft tone pulse in between
+struct Synth {
+    n: u64,
+    noise: u32,
+}
+
+impl Synth {
+    fn new() -> Self {
+        Self { n: 0, noise: 0x1234_5678 }
+    }
+
+    fn next(&mut self) -> (f64, f64) {
+        let t = self.n as f64 / SAMPLE_RATE as f64;
+        self.n += 1;
+
+        // xorshift white noise
+        self.noise ^= self.noise << 13;
+        self.noise ^= self.noise >> 17;
+        self.noise ^= self.noise << 5;
+        let white = self.noise as f64 / u32::MAX as f64 * 2.0 - 1.0;
+
+        let tick_age = t % 0.5;
+        let tick = white * (-tick_age * 60.0).exp() * 0.5;
+
+        let tone_age = (t + 0.25) % 0.5;
+        let envelope = (tone_age * 40.0).min(1.0) * (-tone_age * 8.0).exp();
+        let tone = ((TAU * 220.0 * t).sin() + 0.3 * (TAU * 440.0 * t).sin()) * envelope * 0.3;
+
+        (tick, tone)
+    }
+}
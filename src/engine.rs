@@ -0,0 +1,705 @@
+// core spatialization engine: head pose in, virtual speaker positions out.
+// shared by the tui binary and the c abi (see ffi.rs).
+
+use std::time::Instant;
+
+use crate::filters::{Kalman, OneEuro};
+use crate::mapping::AxisMapping;
+
+// smoothing: higher = smoother but more latency (0.0 - 0.99)
+pub const SMOOTHING_FACTOR: f64 = 0.65;
+pub const MAX_SMOOTHING_FACTOR: f64 = 0.99;
+
+// one euro and kalman need the time between poses; a first pose or a
+// stall falls back into this range
+const MIN_FILTER_DT: f64 = 0.001;
+const MAX_FILTER_DT: f64 = 0.1;
+
+// default radius, can change at runtime
+pub const DEFAULT_RADIUS: f64 = 1.5;
+pub const MIN_RADIUS: f64 = 0.1;
+pub const MAX_RADIUS: f64 = 10.0;
+
+// dynamic reverb wet/dry mix depending on distance
+pub const MIN_REVERB: f64 = 0.05;  // closest
+pub const MAX_REVERB: f64 = 0.60;  // farthest
+
+// speaker angles for front and back modes (base angles at 100% width)
+pub const FRONT_LEFT_ANGLE: f64 = 45.0;   // +45° (front-left) - wider for less focus
+pub const FRONT_RIGHT_ANGLE: f64 = -45.0; // -45° (front-right)
+pub const BACK_LEFT_ANGLE: f64 = 135.0;   // +135° (back-left)
+pub const BACK_RIGHT_ANGLE: f64 = -135.0; // -135° (back-right)
+
+// stereo width control: adjusts speaker separation
+pub const DEFAULT_WIDTH: f64 = 1.0;  // 100% = full separation
+pub const MIN_WIDTH: f64 = 0.3;      // 30% = narrow (more focused)
+pub const MAX_WIDTH: f64 = 1.5;      // 150% = extra wide (very diffuse)
+
+// leaning in/out (tracker z, cm) scales the width by 1 + lean_width * z;
+// below 1 the channels are also cross-mixed toward mono in front
+pub const MAX_LEAN_WIDTH: f64 = 0.2;
+
+// rear hemisphere: sources behind the head get quieter and duller,
+// scaled from nothing at ±90° to the full amount at 180°
+pub const DEFAULT_REAR_ATTENUATION_DB: f64 = 3.0;
+pub const DEFAULT_REAR_ROLLOFF_DB: f64 = 6.0;
+pub const MAX_REAR_DB: f64 = 24.0;
+
+// desktop speakers: the hrtf pan is folded into the front and squeezed to
+// this azimuth with a sine law, hard panning just ends up in one speaker
+pub const SPEAKER_MAX_PAN: f64 = 60.0;
+// simple crosstalk cancellation: how much of the delayed, low-passed other
+// channel is subtracted from each speaker (0 = off)
+pub const MAX_CROSSTALK_CANCEL: f64 = 1.0;
+
+// pitch targets other than elevation: the mapped pitch reaches the full
+// amount at this angle from center
+pub const PITCH_TARGET_RANGE: f64 = 45.0;
+// attenuation looking fully down with the volume target
+pub const PITCH_VOLUME_DB: f64 = 20.0;
+// high shelf gain looking fully up (brighter) or down (duller) with the tilt target
+pub const PITCH_TILT_DB: f64 = 6.0;
+// share of the channels cross-mixed looking fully down with the crossfeed target
+pub const PITCH_CROSSFEED: f64 = 0.5;
+
+// ==============================================================================
+// DATA STRUCTURES
+// ==============================================================================
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SpeakerMode {
+    Front,
+    Back,
+}
+
+impl SpeakerMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SpeakerMode::Front => "FRONT",
+            SpeakerMode::Back => "BACK",
+        }
+    }
+
+    pub fn base_angles(&self) -> (f64, f64) {
+        match self {
+            SpeakerMode::Front => (BACK_LEFT_ANGLE, BACK_RIGHT_ANGLE),
+            SpeakerMode::Back => (FRONT_LEFT_ANGLE, FRONT_RIGHT_ANGLE),
+        }
+    }
+
+    // spatializer azimuth the speaker pair is centered on with the head at
+    // rest, i.e. straight ahead of the listener in this mode
+    pub fn front_azimuth(&self) -> f64 {
+        match self {
+            SpeakerMode::Front => 180.0,
+            SpeakerMode::Back => 0.0,
+        }
+    }
+}
+
+// what the output is played on
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Listening {
+    Headphones,
+    Speakers,
+}
+
+impl Listening {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "headphones" => Some(Listening::Headphones),
+            "speakers" => Some(Listening::Speakers),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Listening::Headphones => "HEADPHONES",
+            Listening::Speakers => "SPEAKERS",
+        }
+    }
+}
+
+// what looking up and down controls
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PitchTarget {
+    // the elevation of the virtual speakers
+    Elevation,
+    // quieter looking down
+    Volume,
+    // brighter looking up, duller looking down
+    Tilt,
+    // looking down cross-mixes the channels toward mono
+    Crossfeed,
+    None,
+}
+
+impl PitchTarget {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "elevation" => Some(PitchTarget::Elevation),
+            "volume" => Some(PitchTarget::Volume),
+            "tilt" => Some(PitchTarget::Tilt),
+            "crossfeed" => Some(PitchTarget::Crossfeed),
+            "none" => Some(PitchTarget::None),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PitchTarget::Elevation => "ELEVATION",
+            PitchTarget::Volume => "VOLUME",
+            PitchTarget::Tilt => "TILT",
+            PitchTarget::Crossfeed => "CROSSFEED",
+            PitchTarget::None => "NONE",
+        }
+    }
+
+    // what a mapped pitch (degrees, up positive) does: degrees of elevation,
+    // db of volume or shelf gain, or the crossfeed share
+    pub fn amount(&self, pitch: f64) -> f64 {
+        let t = (pitch / PITCH_TARGET_RANGE).clamp(-1.0, 1.0);
+        match self {
+            // inverted, looking up moves the source down relative to the eyes
+            PitchTarget::Elevation => -pitch,
+            PitchTarget::Volume => PITCH_VOLUME_DB * t.min(0.0),
+            PitchTarget::Tilt => PITCH_TILT_DB * t,
+            PitchTarget::Crossfeed => PITCH_CROSSFEED * -t.min(0.0),
+            PitchTarget::None => 0.0,
+        }
+    }
+}
+
+// which filter smooths the head pose
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FilterKind {
+    // exponential moving average with `factor`
+    Ema,
+    // adaptive cutoff: smooth at rest, responsive when moving
+    OneEuro,
+    // constant velocity kalman filter
+    Kalman,
+}
+
+impl FilterKind {
+    pub const ALL: [FilterKind; 3] = [FilterKind::Ema, FilterKind::OneEuro, FilterKind::Kalman];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "ema" => Some(FilterKind::Ema),
+            "one-euro" => Some(FilterKind::OneEuro),
+            "kalman" => Some(FilterKind::Kalman),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FilterKind::Ema => "EMA",
+            FilterKind::OneEuro => "ONE EURO",
+            FilterKind::Kalman => "KALMAN",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            FilterKind::Ema => FilterKind::OneEuro,
+            FilterKind::OneEuro => FilterKind::Kalman,
+            FilterKind::Kalman => FilterKind::Ema,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SmoothedState {
+    pub yaw: f64,
+    pub pitch: f64,
+    pub roll: f64,
+    pub factor: f64,
+    // per-axis ema factor (yaw, pitch, roll) instead of `factor`, e.g. from
+    // the smoothing analysis
+    pub axis_factors: [Option<f64>; 3],
+    pub kind: FilterKind,
+    // false until the first pose, which is taken as is
+    primed: bool,
+    one_euro: [OneEuro; 3],
+    kalman: [Kalman; 3],
+    last_update: Option<Instant>,
+}
+
+impl SmoothedState {
+    pub fn new() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: 0.0,
+            roll: 0.0,
+            factor: SMOOTHING_FACTOR,
+            axis_factors: [None; 3],
+            kind: FilterKind::Ema,
+            primed: false,
+            one_euro: [OneEuro::default(); 3],
+            kalman: [Kalman::default(); 3],
+            last_update: None,
+        }
+    }
+
+    // apply the smoothing filter. the first pose initializes the filter
+    // directly so the speakers start where the head is instead of gliding
+    // over from 0/0/0. `time` is when the pose was received: inputs are
+    // drained in bursts, so the time of the call says little about how far
+    // apart the poses are
+    pub fn update(&mut self, raw_yaw: f64, raw_pitch: f64, raw_roll: f64, time: Instant) {
+        let dt = self.last_update.map_or(MAX_FILTER_DT, |t| time.duration_since(t).as_secs_f64());
+        let dt = dt.clamp(MIN_FILTER_DT, MAX_FILTER_DT);
+        self.last_update = Some(time);
+        if !self.primed {
+            (self.yaw, self.pitch, self.roll) = (raw_yaw, raw_pitch, raw_roll);
+            self.seed();
+            self.primed = true;
+            return;
+        }
+        let raw = [raw_yaw, raw_pitch, raw_roll];
+        match self.kind {
+            FilterKind::Ema => {
+                let [ky, kp, kr] = self.factors();
+                self.yaw = ky * self.yaw + (1.0 - ky) * raw_yaw;
+                self.pitch = kp * self.pitch + (1.0 - kp) * raw_pitch;
+                self.roll = kr * self.roll + (1.0 - kr) * raw_roll;
+            }
+            FilterKind::OneEuro => {
+                let [y, p, r] = std::array::from_fn(|i| self.one_euro[i].update(raw[i], dt));
+                (self.yaw, self.pitch, self.roll) = (y, p, r);
+            }
+            FilterKind::Kalman => {
+                let [y, p, r] = std::array::from_fn(|i| self.kalman[i].update(raw[i], dt));
+                (self.yaw, self.pitch, self.roll) = (y, p, r);
+            }
+        }
+    }
+
+    // ema factor per axis, `factor` where none is set
+    pub fn factors(&self) -> [f64; 3] {
+        self.axis_factors.map(|k| k.unwrap_or(self.factor))
+    }
+
+    // switch filters, continuing from the current output
+    pub fn set_kind(&mut self, kind: FilterKind) {
+        self.kind = kind;
+        self.seed();
+    }
+
+    // move the output (and any filter state) by a fixed offset, e.g. on recenter
+    pub fn shift(&mut self, (yaw, pitch, roll): (f64, f64, f64)) {
+        self.yaw += yaw;
+        self.pitch += pitch;
+        self.roll += roll;
+        for (i, delta) in [yaw, pitch, roll].into_iter().enumerate() {
+            self.one_euro[i].shift(delta);
+            self.kalman[i].shift(delta);
+        }
+    }
+
+    // forget the history, keeping filter kind and factors. the next pose
+    // restarts the filter
+    pub fn reset(&mut self) {
+        *self = Self { factor: self.factor, axis_factors: self.axis_factors, kind: self.kind, ..Self::new() };
+    }
+
+    fn seed(&mut self) {
+        for (i, value) in [self.yaw, self.pitch, self.roll].into_iter().enumerate() {
+            self.one_euro[i].reset(value);
+            self.kalman[i].reset(value);
+        }
+    }
+}
+
+impl Default for SmoothedState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// holds the calculated positions for the virtual speakers relative to head
+#[derive(Clone, Copy)]
+pub struct SpatialState {
+    pub left_az: f64,
+    pub right_az: f64,
+    pub elevation: f64,
+    pub radius: f64,
+    pub gain: f64, // volume scaling based on radius (1.0 / radius)
+    pub reverb_gain: f64, // wet signal amount (0.0 - 1.0)
+    // per speaker gain including rear attenuation
+    pub left_gain: f64,
+    pub right_gain: f64,
+    // per speaker high shelf gain in db (0 = flat, negative = duller)
+    pub left_shelf_db: f64,
+    pub right_shelf_db: f64,
+    // input cross-mix: 1 = untouched stereo, 0 = both speakers get mono
+    pub stereo_mix: f64,
+    // crosstalk cancellation amount on the output (0 = off)
+    pub crosstalk: f64,
+}
+
+// wrap to -180..180 so rotations past the side keep tracking instead of saturating
+pub fn wrap_degrees(angle: f64) -> f64 {
+    let wrapped = (angle + 180.0).rem_euclid(360.0) - 180.0;
+    if wrapped == -180.0 { 180.0 } else { wrapped }
+}
+
+// 0 within 90° of `front`, ramps to 1 directly opposite of it
+fn rearness(azimuth: f64, front: f64) -> f64 {
+    ((wrap_degrees(azimuth - front).abs() - 90.0) / 90.0).clamp(0.0, 1.0)
+}
+
+fn db_to_gain(db: f64) -> f64 {
+    10f64.powf(db / 20.0)
+}
+
+impl SpatialState {
+    pub fn from_head_tracking(yaw: f64, pitch: f64, radius: f64, mode: SpeakerMode, reverb_enabled: bool, width: f64) -> Self {
+        // get base speaker angles based on mode
+        let (left_base, right_base) = mode.base_angles();
+
+        // width > 1.0 = wider (diffused), width < 1.0 = narrower (focused)
+        let left_base_scaled = left_base * width;
+        let right_base_scaled = right_base * width;
+
+        // relative azimuth = base_pos - head_yaw
+        let left_az = wrap_degrees(left_base_scaled - yaw);
+        let right_az = wrap_degrees(right_base_scaled - yaw);
+
+        // pitch is inverted (looking up moves the source down relative to eyes)
+        let elevation = -pitch;
+
+        // calculate gain: inverse relationship with radius
+        // at radius 1.0 = 100% gain, radius 2.0 = 50% gain, etc.
+        // clamp to reasonable range
+        let gain = (1.0 / radius).clamp(0.1, 2.0);
+
+        // calculate reverb gain using square-root curve for natural progression
+        // sqrt gives more reverb early on, then tapers - matches physical acoustics
+        let reverb_gain = if reverb_enabled {
+            let normalized = ((radius - MIN_RADIUS) / (MAX_RADIUS - MIN_RADIUS)).clamp(0.0, 1.0);
+            MIN_REVERB + normalized.sqrt() * (MAX_REVERB - MIN_REVERB)
+        } else {
+            0.0 // reverb disabled
+        };
+
+        Self {
+            left_az,
+            right_az,
+            elevation,
+            radius,
+            gain,
+            reverb_gain,
+            left_gain: gain,
+            right_gain: gain,
+            left_shelf_db: 0.0,
+            right_shelf_db: 0.0,
+            stereo_mix: 1.0,
+            crosstalk: 0.0,
+        }
+    }
+
+    pub fn with_stereo_mix(mut self, mix: f64) -> Self {
+        self.stereo_mix = mix.clamp(0.0, 1.0);
+        self
+    }
+
+    // pitch routed somewhere other than the elevation, on top of the gains,
+    // shelves and cross-mix the other settings left
+    pub fn with_pitch_target(mut self, target: PitchTarget, pitch: f64) -> Self {
+        let amount = target.amount(pitch);
+        match target {
+            PitchTarget::Volume => {
+                let gain = db_to_gain(amount);
+                self.gain *= gain;
+                self.left_gain *= gain;
+                self.right_gain *= gain;
+            }
+            PitchTarget::Tilt => {
+                self.left_shelf_db += amount;
+                self.right_shelf_db += amount;
+            }
+            PitchTarget::Crossfeed => self.stereo_mix *= 1.0 - amount,
+            PitchTarget::Elevation | PitchTarget::None => {}
+        }
+        self
+    }
+
+    // adapt the pan to loudspeakers: sources behind are mirrored to the front
+    // and the azimuth follows sin(az), so turning moves the image smoothly
+    // between the speakers instead of throwing it into one of them
+    pub fn with_listening(mut self, listening: Listening, crosstalk: f64) -> Self {
+        if listening == Listening::Speakers {
+            let pan = |az: f64| SPEAKER_MAX_PAN * az.to_radians().sin();
+            self.left_az = pan(self.left_az);
+            self.right_az = pan(self.right_az);
+            self.crosstalk = crosstalk.clamp(0.0, MAX_CROSSTALK_CANCEL);
+        }
+        self
+    }
+
+    // attenuate and darken speakers that ended up behind the listener,
+    // measured from where the mode puts the speakers at rest
+    pub fn with_rear_attenuation(mut self, mode: SpeakerMode, attenuation_db: f64, rolloff_db: f64) -> Self {
+        let front = mode.front_azimuth();
+        let left = rearness(self.left_az, front);
+        let right = rearness(self.right_az, front);
+        self.left_gain = self.gain * db_to_gain(-attenuation_db * left);
+        self.right_gain = self.gain * db_to_gain(-attenuation_db * right);
+        self.left_shelf_db = -rolloff_db * left;
+        self.right_shelf_db = -rolloff_db * right;
+        self
+    }
+}
+
+// output-stage smoothing: a light ramp on the final speaker values, separate
+// from the pose filter. with little input smoothing for responsiveness the
+// values still step from packet to packet, which is audible on the gains;
+// this evens those steps out without adding much latency.
+pub const MAX_OUTPUT_SMOOTHING_MS: f64 = 500.0;
+
+pub struct OutputSmoother {
+    // time constant in seconds, 0 = pass through
+    pub time_constant: f64,
+    last: Option<SpatialState>,
+}
+
+impl OutputSmoother {
+    pub fn new(time_constant: f64) -> Self {
+        Self { time_constant, last: None }
+    }
+
+    // move `dt` seconds towards `target`; the first call starts right there
+    pub fn update(&mut self, target: &SpatialState, dt: f64) -> SpatialState {
+        let out = match self.last {
+            Some(last) if self.time_constant > 0.0 => {
+                let a = 1.0 - (-dt.max(0.0) / self.time_constant).exp();
+                let lerp = |from: f64, to: f64| from + a * (to - from);
+                // azimuths take the short way around ±180°
+                let turn = |from: f64, to: f64| wrap_degrees(from + a * wrap_degrees(to - from));
+                SpatialState {
+                    left_az: turn(last.left_az, target.left_az),
+                    right_az: turn(last.right_az, target.right_az),
+                    elevation: lerp(last.elevation, target.elevation),
+                    radius: lerp(last.radius, target.radius),
+                    gain: lerp(last.gain, target.gain),
+                    reverb_gain: lerp(last.reverb_gain, target.reverb_gain),
+                    left_gain: lerp(last.left_gain, target.left_gain),
+                    right_gain: lerp(last.right_gain, target.right_gain),
+                    left_shelf_db: lerp(last.left_shelf_db, target.left_shelf_db),
+                    right_shelf_db: lerp(last.right_shelf_db, target.right_shelf_db),
+                    stereo_mix: lerp(last.stereo_mix, target.stereo_mix),
+                    crosstalk: lerp(last.crosstalk, target.crosstalk),
+                }
+            }
+            _ => *target,
+        };
+        self.last = Some(out);
+        out
+    }
+}
+
+// ==============================================================================
+// ENGINE
+// ==============================================================================
+
+// bundles the smoothing filter with the user-adjustable speaker settings so
+// callers only have to feed poses and read back the spatial state
+pub struct Engine {
+    pub smoothed: SmoothedState,
+    pub radius: f64,
+    pub mode: SpeakerMode,
+    pub reverb_enabled: bool,
+    pub width: f64,
+    pub listening: Listening,
+    // crosstalk cancellation amount in speaker mode
+    pub crosstalk_cancel: f64,
+    // width change per cm of lean (tracker z), 0 = off
+    pub lean_width: f64,
+    // smoothed lean relative to the recentered position, cm
+    pub lean: f64,
+    lean_center: f64,
+    last_z: f64,
+    // what the mapped pitch drives
+    pub pitch_target: PitchTarget,
+    // dead zone / curve / sensitivity per axis
+    pub yaw: AxisMapping,
+    pub pitch: AxisMapping,
+    pub roll: AxisMapping,
+    // rear hemisphere attenuation and high frequency rolloff (db at 180°)
+    pub rear_attenuation_db: f64,
+    pub rear_rolloff_db: f64,
+    // pose treated as straight ahead, subtracted from every raw pose
+    pub center: (f64, f64, f64),
+    last_raw: (f64, f64, f64),
+    // last pose fed into the filter, recentered and limited
+    pub input: (f64, f64, f64),
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Self {
+            smoothed: SmoothedState::new(),
+            radius: DEFAULT_RADIUS,
+            mode: SpeakerMode::Front,
+            reverb_enabled: false, // off by default
+            width: DEFAULT_WIDTH,
+            listening: Listening::Headphones,
+            crosstalk_cancel: 0.0,
+            lean_width: 0.0,
+            lean: 0.0,
+            lean_center: 0.0,
+            last_z: 0.0,
+            pitch_target: PitchTarget::Elevation,
+            yaw: AxisMapping::identity(),
+            pitch: AxisMapping::identity(),
+            roll: AxisMapping::identity(),
+            rear_attenuation_db: DEFAULT_REAR_ATTENUATION_DB,
+            rear_rolloff_db: DEFAULT_REAR_ROLLOFF_DB,
+            center: (0.0, 0.0, 0.0),
+            last_raw: (0.0, 0.0, 0.0),
+            input: (0.0, 0.0, 0.0),
+        }
+    }
+
+    // feed a raw pose (degrees) received at `time` through the smoothing filter
+    pub fn feed_pose(&mut self, raw_yaw: f64, raw_pitch: f64, raw_roll: f64, time: Instant) -> SpatialState {
+        self.last_raw = (raw_yaw, raw_pitch, raw_roll);
+        let (cy, cp, cr) = self.center;
+        // an ignored axis feeds the filter its own value, so it holds still
+        let s = &self.smoothed;
+        let yaw = self.yaw.limit(raw_yaw - cy).unwrap_or(s.yaw);
+        let pitch = self.pitch.limit(raw_pitch - cp).unwrap_or(s.pitch);
+        let roll = self.roll.limit(raw_roll - cr).unwrap_or(s.roll);
+        self.input = (yaw, pitch, roll);
+        self.smoothed.update(yaw, pitch, roll, time);
+        self.spatial()
+    }
+
+    // feed the tracker z translation (cm) for lean-dependent width
+    pub fn feed_lean(&mut self, z: f64) {
+        let k = self.smoothed.factor;
+        self.last_z = z;
+        self.lean = k * self.lean + (1.0 - k) * (z - self.lean_center);
+    }
+
+    // width multiplier from the current lean, 1 = neutral
+    pub fn lean_factor(&self) -> f64 {
+        (1.0 + self.lean_width * self.lean).clamp(0.0, MAX_WIDTH / MIN_WIDTH)
+    }
+
+    // make the current head pose the new straight-ahead. the filter state is
+    // shifted along so the speakers snap to center instead of gliding there.
+    // returns that shift, for other filters running on the same input
+    pub fn recenter(&mut self) -> (f64, f64, f64) {
+        self.lean -= self.last_z - self.lean_center;
+        self.lean_center = self.last_z;
+        let (oy, op, or) = self.center;
+        let (ny, np, nr) = self.last_raw;
+        let shift = (oy - ny, op - np, or - nr);
+        self.smoothed.shift(shift);
+        self.center = self.last_raw;
+        shift
+    }
+
+    // smoothed pose after the per-axis mapping (yaw, pitch, roll)
+    pub fn mapped(&self) -> (f64, f64, f64) {
+        (
+            self.yaw.apply(self.smoothed.yaw),
+            self.pitch.apply(self.smoothed.pitch),
+            self.roll.apply(self.smoothed.roll),
+        )
+    }
+
+    // speaker positions for the current smoothed pose and settings
+    pub fn spatial(&self) -> SpatialState {
+        self.spatial_at(self.smoothed.yaw, self.smoothed.pitch)
+    }
+
+    // speaker positions for a smoothed pose other than the filter's own,
+    // e.g. one that is still gliding towards it
+    pub fn spatial_at(&self, yaw: f64, pitch: f64) -> SpatialState {
+        let lean = self.lean_factor();
+        let pitch = self.pitch.apply(pitch);
+        // with another target the speakers stay level
+        let elevation_pitch = if self.pitch_target == PitchTarget::Elevation { pitch } else { 0.0 };
+        SpatialState::from_head_tracking(
+            self.yaw.apply(yaw),
+            elevation_pitch,
+            self.radius,
+            self.mode,
+            self.reverb_enabled,
+            (self.width * lean).min(MAX_WIDTH),
+        )
+        .with_rear_attenuation(self.mode, self.rear_attenuation_db, self.rear_rolloff_db)
+        .with_stereo_mix(lean)
+        .with_pitch_target(self.pitch_target, pitch)
+        .with_listening(self.listening, self.crosstalk_cancel)
+    }
+
+    // forget the filter history, keeping the settings. the next pose
+    // restarts the filter
+    pub fn reset(&mut self) {
+        self.smoothed.reset();
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the speakers in front of a centered head are not treated as behind it
+    #[test]
+    fn no_rear_attenuation_at_rest() {
+        for mode in [SpeakerMode::Front, SpeakerMode::Back] {
+            let mut engine = Engine::new();
+            engine.mode = mode;
+            let spatial = engine.spatial_at(0.0, 0.0);
+            assert_eq!(spatial.left_gain, spatial.gain, "{} left gain", mode.label());
+            assert_eq!(spatial.right_gain, spatial.gain, "{} right gain", mode.label());
+            assert_eq!(spatial.left_shelf_db, 0.0, "{} left shelf", mode.label());
+            assert_eq!(spatial.right_shelf_db, 0.0, "{} right shelf", mode.label());
+        }
+    }
+
+    // poses drained in a burst are filtered by how far apart they arrived,
+    // not by how fast they were fed
+    #[test]
+    fn filter_dt_follows_pose_time() {
+        let start = Instant::now();
+        let mut state = SmoothedState::new();
+        state.set_kind(FilterKind::OneEuro);
+        let mut reference = OneEuro::default();
+        reference.reset(0.0);
+        state.update(0.0, 0.0, 0.0, start);
+        let mut expected = 0.0;
+        for i in 1..=20 {
+            state.update(i as f64, 0.0, 0.0, start + std::time::Duration::from_millis(20 * i));
+            expected = reference.update(i as f64, 0.02);
+        }
+        assert!((state.yaw - expected).abs() < 1e-9, "{} != {}", state.yaw, expected);
+    }
+
+    // turned all the way around, each speaker of the pair sits 135° from the
+    // front (45° to either side of straight behind), halfway along the ramp
+    // from 90° to 180°, so both get half the rear attenuation
+    #[test]
+    fn half_rear_attenuation_turned_around() {
+        let mut engine = Engine::new();
+        engine.smoothed.yaw = 180.0;
+        let spatial = engine.spatial();
+        let expected = spatial.gain * db_to_gain(-DEFAULT_REAR_ATTENUATION_DB * 0.5);
+        assert!((spatial.left_gain - expected).abs() < 1e-9);
+        assert!((spatial.right_gain - expected).abs() < 1e-9);
+    }
+}
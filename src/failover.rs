@@ -0,0 +1,169 @@
+// several inputs in order of preference, e.g. a camera tracker over udp with
+// an imu on the serial port behind it. all of them are read all the time;
+// poses are passed on from the first one that sent something within the
+// timeout, so when it goes quiet the next live one takes over, and when it
+// comes back it takes over again right away.
+
+use std::collections::VecDeque;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::input::{self, InputSource, Pose, TRACKING_LOSS_TIMEOUT};
+
+pub const DEFAULT_FAILOVER_TIMEOUT: Duration = Duration::from_millis(1000);
+// shorter than this would fail over on ordinary tracking loss jitter
+pub const MIN_FAILOVER_TIMEOUT: Duration = TRACKING_LOSS_TIMEOUT;
+
+// how often the sources are checked while waiting for a pose
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+// poses of the active source read but not handed out yet; a tracker sending
+// faster than they are taken loses its oldest poses instead of adding lag
+const MAX_QUEUED_POSES: usize = 64;
+
+struct Source {
+    input: Box<dyn InputSource>,
+    last_seen: Option<Instant>,
+}
+
+pub struct FailoverInput {
+    sources: Vec<Source>,
+    timeout: Duration,
+    active: usize,
+    // poses of the active source not handed out yet
+    queue: VecDeque<Pose>,
+    switched: Option<String>,
+}
+
+impl FailoverInput {
+    // uris in order of preference, the first one is the main input
+    pub fn open(uris: &[String], timeout: Duration) -> Result<Self, String> {
+        let inputs = uris.iter().map(|uri| input::open(uri)).collect::<Result<Vec<_>, String>>()?;
+        Self::new(inputs, timeout)
+    }
+
+    // inputs opened already, e.g. a MultiInput as the main one
+    pub fn new(inputs: Vec<Box<dyn InputSource>>, timeout: Duration) -> Result<Self, String> {
+        if inputs.is_empty() {
+            return Err("no input to fail over between".to_string());
+        }
+        let sources = inputs.into_iter().map(|input| Source { input, last_seen: None }).collect();
+        Ok(Self { sources, timeout, active: 0, queue: VecDeque::new(), switched: None })
+    }
+
+    // read what every source has queued, keeping the active one's poses. a
+    // failing source simply stays quiet
+    fn poll_sources(&mut self) {
+        for (i, source) in self.sources.iter_mut().enumerate() {
+            while let Ok(Some(pose)) = source.input.recv(Duration::ZERO) {
+                source.last_seen = Some(pose.time);
+                if i == self.active {
+                    if self.queue.len() >= MAX_QUEUED_POSES {
+                        self.queue.pop_front();
+                    }
+                    self.queue.push_back(pose);
+                }
+            }
+        }
+    }
+
+    // the first live source becomes the active one. with all of them quiet
+    // the active one stays, nothing to switch to
+    fn select(&mut self, now: Instant) {
+        let live = |s: &Source| s.last_seen.is_some_and(|t| now.duration_since(t) <= self.timeout);
+        let Some(best) = self.sources.iter().position(live) else { return };
+        if best == self.active {
+            return;
+        }
+        let (from, to) = (self.sources[self.active].input.describe(), self.sources[best].input.describe());
+        self.switched = Some(if best < self.active {
+            format!("{} is sending, switched back from {}", to, from)
+        } else {
+            format!("{} is not sending, switched to {}", from, to)
+        });
+        self.active = best;
+        self.queue.clear();
+    }
+}
+
+impl InputSource for FailoverInput {
+    fn recv(&mut self, timeout: Duration) -> Result<Option<Pose>, String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            self.poll_sources();
+            let now = Instant::now();
+            self.select(now);
+            if let Some(pose) = self.queue.pop_front() {
+                return Ok(Some(pose));
+            }
+            if now >= deadline {
+                return Ok(None);
+            }
+            thread::sleep(POLL_INTERVAL.min(deadline - now));
+        }
+    }
+
+    // the main input, which profiles and drift corrections are kept for
+    fn uri(&self) -> &str {
+        self.sources[0].input.uri()
+    }
+
+    // the source poses are passed on from, whose drift correction applies
+    fn active_uri(&self) -> &str {
+        self.sources[self.active].input.active_uri()
+    }
+
+    fn describe(&self) -> String {
+        format!("{} ({}/{})", self.sources[self.active].input.describe(), self.active + 1, self.sources.len())
+    }
+
+    // e.g. "OpenTrack UDP 127.0.0.1:4242 is not sending, switched to demo scene 'orbit' (no tracker needed)"
+    fn take_event(&mut self) -> Option<String> {
+        self.switched.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // hands out a burst of poses, yaw counting up, on every recv
+    struct Burst {
+        next: usize,
+        left: usize,
+        start: Instant,
+    }
+
+    impl InputSource for Burst {
+        fn recv(&mut self, _timeout: Duration) -> Result<Option<Pose>, String> {
+            if self.left == 0 {
+                return Ok(None);
+            }
+            self.left -= 1;
+            self.next += 1;
+            let time = self.start + Duration::from_millis(self.next as u64);
+            Ok(Some(Pose { translation: [0.0; 3], yaw: self.next as f64, pitch: 0.0, roll: 0.0, time }))
+        }
+
+        fn uri(&self) -> &str {
+            "burst://"
+        }
+
+        fn describe(&self) -> String {
+            "burst".to_string()
+        }
+    }
+
+    // a backlog from a fast tracker keeps only the newest poses
+    #[test]
+    fn queue_keeps_the_newest_poses() {
+        let burst = Burst { next: 0, left: MAX_QUEUED_POSES * 3, start: Instant::now() };
+        let mut input = FailoverInput::new(vec![Box::new(burst)], DEFAULT_FAILOVER_TIMEOUT).unwrap();
+        let first = input.recv(Duration::ZERO).unwrap().unwrap();
+        assert_eq!(first.yaw, (MAX_QUEUED_POSES * 2 + 1) as f64);
+        let mut taken = 1;
+        while input.recv(Duration::ZERO).unwrap().is_some() {
+            taken += 1;
+        }
+        assert_eq!(taken, MAX_QUEUED_POSES);
+    }
+}
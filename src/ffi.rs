@@ -0,0 +1,441 @@
+// c abi for embedding the engine in non-rust software (game plugins etc).
+// header is generated with cbindgen, see cbindgen.toml and include/spatial_track.h
+
+use std::time::Instant;
+
+use crate::calibration::{MAX_SENSITIVITY, MIN_SENSITIVITY};
+use crate::engine::{
+    Engine, FilterKind, Listening, SpatialState, SpeakerMode, MAX_CROSSTALK_CANCEL, MAX_RADIUS, MAX_REAR_DB, MAX_SMOOTHING_FACTOR,
+    MAX_WIDTH, MIN_RADIUS, MIN_WIDTH,
+};
+use crate::mapping::{AxisMapping, LimitMode, MAX_CURVE, MAX_DEAD_ZONE, MAX_LIMIT, MAX_MAGNET, MIN_CURVE};
+
+/// Opaque engine handle. Create with `st_engine_new`, release with `st_engine_free`.
+pub struct StEngine {
+    inner: Engine,
+}
+
+/// Virtual speaker placement, passed as `uint32_t` in `StFilterConfig.mode`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub enum StSpeakerMode {
+    Front = 0,
+    Back = 1,
+}
+
+impl StSpeakerMode {
+    // enum fields come from c as plain integers, any value can show up
+    fn parse(value: u32) -> Option<SpeakerMode> {
+        match value {
+            v if v == Self::Front as u32 => Some(SpeakerMode::Front),
+            v if v == Self::Back as u32 => Some(SpeakerMode::Back),
+            _ => None,
+        }
+    }
+}
+
+/// Pose smoothing filter, passed as `uint32_t` in `StFilterConfig.filter`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub enum StFilterKind {
+    /// Exponential moving average using `smoothing_factor`.
+    Ema = 0,
+    /// One Euro filter: smooth at rest, responsive when moving.
+    OneEuro = 1,
+    /// Constant velocity Kalman filter.
+    Kalman = 2,
+}
+
+impl StFilterKind {
+    fn parse(value: u32) -> Option<FilterKind> {
+        match value {
+            v if v == Self::Ema as u32 => Some(FilterKind::Ema),
+            v if v == Self::OneEuro as u32 => Some(FilterKind::OneEuro),
+            v if v == Self::Kalman as u32 => Some(FilterKind::Kalman),
+            _ => None,
+        }
+    }
+}
+
+/// What the output is played on, passed as `uint32_t` in `StFilterConfig.listening`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub enum StListening {
+    Headphones = 0,
+    /// Desktop speakers: pan folded to the front and narrowed, optional crosstalk cancellation.
+    Speakers = 1,
+}
+
+impl StListening {
+    fn parse(value: u32) -> Option<Listening> {
+        match value {
+            v if v == Self::Headphones as u32 => Some(Listening::Headphones),
+            v if v == Self::Speakers as u32 => Some(Listening::Speakers),
+            _ => None,
+        }
+    }
+}
+
+/// What happens to head angles past an axis limit, passed as `uint32_t` in
+/// `StAxisMapping.limit_mode`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub enum StLimitMode {
+    /// Hold at the limit.
+    Clamp = 0,
+    /// Reflect back from the limit.
+    Fold = 1,
+    /// Keep the last accepted value for that axis.
+    Ignore = 2,
+}
+
+impl StLimitMode {
+    fn parse(value: u32) -> Option<LimitMode> {
+        match value {
+            v if v == Self::Clamp as u32 => Some(LimitMode::Clamp),
+            v if v == Self::Fold as u32 => Some(LimitMode::Fold),
+            v if v == Self::Ignore as u32 => Some(LimitMode::Ignore),
+            _ => None,
+        }
+    }
+}
+
+/// Result of a pose update: where the two virtual speakers sit relative to the head.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct StSpatialState {
+    /// Left speaker azimuth in degrees.
+    pub left_az: f64,
+    /// Right speaker azimuth in degrees.
+    pub right_az: f64,
+    /// Elevation in degrees (positive = above).
+    pub elevation: f64,
+    /// Speaker distance in meters.
+    pub radius: f64,
+    /// Volume scaling derived from the radius.
+    pub gain: f64,
+    /// Reverb wet amount (0.0 - 1.0), dry amount is 1.0 - reverb_gain.
+    pub reverb_gain: f64,
+    /// Left speaker gain including rear attenuation.
+    pub left_gain: f64,
+    /// Right speaker gain including rear attenuation.
+    pub right_gain: f64,
+    /// Left speaker high shelf gain in dB (0 = flat).
+    pub left_shelf_db: f64,
+    /// Right speaker high shelf gain in dB (0 = flat).
+    pub right_shelf_db: f64,
+    /// Input cross-mix, 1.0 = untouched stereo, 0.0 = mono to both speakers.
+    pub stereo_mix: f64,
+    /// Crosstalk cancellation amount (0.0 = off), only used with speakers.
+    pub crosstalk: f64,
+}
+
+/// Shaping of one head axis: dead zone, then center magnet, then response curve, then sensitivity.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct StAxisMapping {
+    /// Degrees around center that are ignored.
+    pub dead_zone: f64,
+    /// Output multiplier (1.0 = 1:1).
+    pub sensitivity: f64,
+    /// Response exponent (1.0 = linear).
+    pub curve: f64,
+    /// Degrees past the dead zone that are gently pulled toward center (0 = off).
+    pub magnet: f64,
+    /// Largest accepted angle from center in degrees (0 = unlimited).
+    pub limit: f64,
+    /// One of `StLimitMode`.
+    pub limit_mode: u32,
+}
+
+impl From<AxisMapping> for StAxisMapping {
+    fn from(m: AxisMapping) -> Self {
+        Self {
+            dead_zone: m.dead_zone,
+            sensitivity: m.sensitivity,
+            curve: m.curve,
+            magnet: m.magnet,
+            limit: m.limit,
+            limit_mode: match m.limit_mode {
+                LimitMode::Clamp => StLimitMode::Clamp as u32,
+                LimitMode::Fold => StLimitMode::Fold as u32,
+                LimitMode::Ignore => StLimitMode::Ignore as u32,
+            },
+        }
+    }
+}
+
+impl StAxisMapping {
+    // clamped to the tui's ranges, None for a non-finite field or an unknown limit mode
+    fn parse(&self) -> Option<AxisMapping> {
+        let values = [self.dead_zone, self.sensitivity, self.curve, self.magnet, self.limit];
+        if !values.iter().all(|v| v.is_finite()) {
+            return None;
+        }
+        Some(AxisMapping {
+            dead_zone: self.dead_zone.clamp(0.0, MAX_DEAD_ZONE),
+            sensitivity: self.sensitivity.clamp(MIN_SENSITIVITY, MAX_SENSITIVITY),
+            curve: self.curve.clamp(MIN_CURVE, MAX_CURVE),
+            magnet: self.magnet.clamp(0.0, MAX_MAGNET),
+            limit: self.limit.clamp(0.0, MAX_LIMIT),
+            limit_mode: StLimitMode::parse(self.limit_mode)?,
+        })
+    }
+}
+
+/// Filter and speaker configuration. Out-of-range values are clamped, non-finite
+/// values and unknown enum values are rejected.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct StFilterConfig {
+    /// Exponential smoothing factor (0.0 - 0.99), higher = smoother.
+    pub smoothing_factor: f64,
+    /// One of `StFilterKind`.
+    pub filter: u32,
+    /// Speaker distance in meters.
+    pub radius: f64,
+    /// Stereo width multiplier (1.0 = default separation).
+    pub width: f64,
+    /// One of `StSpeakerMode`.
+    pub mode: u32,
+    pub reverb_enabled: bool,
+    pub yaw: StAxisMapping,
+    pub pitch: StAxisMapping,
+    pub roll: StAxisMapping,
+    /// Attenuation in dB for a source directly behind the head.
+    pub rear_attenuation_db: f64,
+    /// High frequency cut in dB for a source directly behind the head.
+    pub rear_rolloff_db: f64,
+    /// One of `StListening`.
+    pub listening: u32,
+    /// Crosstalk cancellation amount for speakers (0.0 - 1.0).
+    pub crosstalk_cancel: f64,
+}
+
+impl From<&SpatialState> for StSpatialState {
+    fn from(s: &SpatialState) -> Self {
+        Self {
+            left_az: s.left_az,
+            right_az: s.right_az,
+            elevation: s.elevation,
+            radius: s.radius,
+            gain: s.gain,
+            reverb_gain: s.reverb_gain,
+            left_gain: s.left_gain,
+            right_gain: s.right_gain,
+            left_shelf_db: s.left_shelf_db,
+            right_shelf_db: s.right_shelf_db,
+            stereo_mix: s.stereo_mix,
+            crosstalk: s.crosstalk,
+        }
+    }
+}
+
+/// "STGS" as read from memory, first field of `StGameState`.
+pub const ST_GAME_MAGIC: u32 = 0x5347_5453;
+/// Layout version of `StGameState`, bumped on incompatible changes.
+pub const ST_GAME_VERSION: u32 = 1;
+
+/// State exported for game mods with --game-shm (shared memory) and --game-udp
+/// (one datagram per update). Little-endian, no padding.
+///
+/// Shared memory readers use `sequence` as a seqlock: it is odd while an update
+/// is being written. Read it, copy the struct, read it again and retry when it
+/// was odd or changed. `magic` is zeroed when spatial-track exits.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct StGameState {
+    /// 0x53475453, "STGS" in memory.
+    pub magic: u32,
+    /// Layout version, currently 1.
+    pub version: u32,
+    /// Seqlock counter, odd while an update is being written.
+    pub sequence: u32,
+    /// `sizeof(StGameState)`, newer versions only append fields.
+    pub size: u32,
+    /// When the pose was received, CLOCK_MONOTONIC in microseconds.
+    pub timestamp_us: u64,
+    /// Head position x, y, z in cm as sent by the tracker.
+    pub translation: [f64; 3],
+    /// Raw tracker angles in degrees (positive yaw = left, positive pitch = up).
+    pub raw_yaw: f64,
+    pub raw_pitch: f64,
+    pub raw_roll: f64,
+    /// Smoothed, recentered angles in degrees.
+    pub yaw: f64,
+    pub pitch: f64,
+    pub roll: f64,
+    /// Angular velocity in degrees per second (yaw, pitch, roll).
+    pub velocity: [f64; 3],
+    /// Motion intensity, 0.0 = still.
+    pub intensity: f64,
+    /// What the spatializer is set to.
+    pub spatial: StSpatialState,
+}
+
+/// Allocate a new engine with default settings.
+#[no_mangle]
+pub extern "C" fn st_engine_new() -> *mut StEngine {
+    Box::into_raw(Box::new(StEngine { inner: Engine::new() }))
+}
+
+/// Free an engine. Passing NULL is a no-op.
+///
+/// # Safety
+/// `engine` must come from `st_engine_new` and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn st_engine_free(engine: *mut StEngine) {
+    if !engine.is_null() {
+        drop(Box::from_raw(engine));
+    }
+}
+
+/// Feed a raw head pose (degrees) and write the resulting spatial state to `out`.
+/// The first pose after creation or a reset initializes the filter directly.
+/// The time between calls is taken as the time between poses.
+/// Returns false if a pointer is NULL or an angle is NaN or infinite, which is then
+/// dropped like the network inputs drop such poses.
+///
+/// # Safety
+/// `engine` must be a live handle, `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn st_engine_feed_pose(
+    engine: *mut StEngine,
+    yaw: f64,
+    pitch: f64,
+    roll: f64,
+    out: *mut StSpatialState,
+) -> bool {
+    let (Some(engine), Some(out)) = (engine.as_mut(), out.as_mut()) else {
+        return false;
+    };
+    if ![yaw, pitch, roll].iter().all(|v| v.is_finite()) {
+        return false;
+    }
+    let spatial = engine.inner.feed_pose(yaw, pitch, roll, Instant::now());
+    *out = StSpatialState::from(&spatial);
+    true
+}
+
+/// Write the spatial state for the current pose and settings without feeding a new pose.
+///
+/// # Safety
+/// `engine` must be a live handle, `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn st_engine_get_state(engine: *const StEngine, out: *mut StSpatialState) -> bool {
+    let (Some(engine), Some(out)) = (engine.as_ref(), out.as_mut()) else {
+        return false;
+    };
+    *out = StSpatialState::from(&engine.inner.spatial());
+    true
+}
+
+/// Read the current filter configuration into `out`.
+///
+/// # Safety
+/// `engine` must be a live handle, `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn st_engine_get_config(engine: *const StEngine, out: *mut StFilterConfig) -> bool {
+    let (Some(engine), Some(out)) = (engine.as_ref(), out.as_mut()) else {
+        return false;
+    };
+    let e = &engine.inner;
+    *out = StFilterConfig {
+        smoothing_factor: e.smoothed.factor,
+        filter: match e.smoothed.kind {
+            FilterKind::Ema => StFilterKind::Ema as u32,
+            FilterKind::OneEuro => StFilterKind::OneEuro as u32,
+            FilterKind::Kalman => StFilterKind::Kalman as u32,
+        },
+        radius: e.radius,
+        width: e.width,
+        mode: match e.mode {
+            SpeakerMode::Front => StSpeakerMode::Front as u32,
+            SpeakerMode::Back => StSpeakerMode::Back as u32,
+        },
+        reverb_enabled: e.reverb_enabled,
+        yaw: e.yaw.into(),
+        pitch: e.pitch.into(),
+        roll: e.roll.into(),
+        rear_attenuation_db: e.rear_attenuation_db,
+        rear_rolloff_db: e.rear_rolloff_db,
+        listening: match e.listening {
+            Listening::Headphones => StListening::Headphones as u32,
+            Listening::Speakers => StListening::Speakers as u32,
+        },
+        crosstalk_cancel: e.crosstalk_cancel,
+    };
+    true
+}
+
+/// Apply a filter configuration. Values are clamped to the same ranges as the TUI.
+/// Returns false, changing nothing, if a pointer is NULL, a value is NaN or
+/// infinite, or an enum field holds an unknown value.
+///
+/// # Safety
+/// `engine` must be a live handle, `config` must be valid for reads.
+#[no_mangle]
+pub unsafe extern "C" fn st_engine_set_config(engine: *mut StEngine, config: *const StFilterConfig) -> bool {
+    let (Some(engine), Some(config)) = (engine.as_mut(), config.as_ref()) else {
+        return false;
+    };
+    let (Some(kind), Some(mode), Some(listening)) =
+        (StFilterKind::parse(config.filter), StSpeakerMode::parse(config.mode), StListening::parse(config.listening))
+    else {
+        return false;
+    };
+    let (Some(yaw), Some(pitch), Some(roll)) = (config.yaw.parse(), config.pitch.parse(), config.roll.parse()) else {
+        return false;
+    };
+    // clamp() passes NaN through, which would stick in the engine
+    let values = [
+        config.smoothing_factor,
+        config.radius,
+        config.width,
+        config.rear_attenuation_db,
+        config.rear_rolloff_db,
+        config.crosstalk_cancel,
+    ];
+    if !values.iter().all(|v| v.is_finite()) {
+        return false;
+    }
+    let e = &mut engine.inner;
+    e.smoothed.factor = config.smoothing_factor.clamp(0.0, MAX_SMOOTHING_FACTOR);
+    if kind != e.smoothed.kind {
+        e.smoothed.set_kind(kind);
+    }
+    e.radius = config.radius.clamp(MIN_RADIUS, MAX_RADIUS);
+    e.width = config.width.clamp(MIN_WIDTH, MAX_WIDTH);
+    e.mode = mode;
+    e.reverb_enabled = config.reverb_enabled;
+    e.yaw = yaw;
+    e.pitch = pitch;
+    e.roll = roll;
+    e.rear_attenuation_db = config.rear_attenuation_db.clamp(0.0, MAX_REAR_DB);
+    e.rear_rolloff_db = config.rear_rolloff_db.clamp(0.0, MAX_REAR_DB);
+    e.listening = listening;
+    e.crosstalk_cancel = config.crosstalk_cancel.clamp(0.0, MAX_CROSSTALK_CANCEL);
+    true
+}
+
+/// Reset the smoothing filter so it restarts at the next fed pose, keeping the configuration.
+///
+/// # Safety
+/// `engine` must be a live handle or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn st_engine_reset(engine: *mut StEngine) {
+    if let Some(engine) = engine.as_mut() {
+        engine.inner.reset();
+    }
+}
+
+/// Treat the most recently fed pose as straight ahead.
+///
+/// # Safety
+/// `engine` must be a live handle or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn st_engine_recenter(engine: *mut StEngine) {
+    if let Some(engine) = engine.as_mut() {
+        engine.inner.recenter();
+    }
+}
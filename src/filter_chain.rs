@@ -0,0 +1,191 @@
+// managed filter-chain: instead of installing conf/99-spatializer.conf into
+// pipewire.conf.d, spatial-track runs it itself as a separate pipewire client
+// (`pipewire -c <file>`, the way pipewire's own filter-chain examples run).
+// the nodes exist exactly as long as spatial-track does:
+//
+// - an already present node (installed config, another instance) is reused
+//   and left alone
+// - the client is restarted with a backoff when it exits, e.g. because
+//   pipewire itself restarted
+// - on exit it is stopped, and it gets SIGTERM from the kernel if
+//   spatial-track dies without cleaning up, so no orphaned nodes pile up
+
+use std::fs;
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use spatial_track::process::stop_child;
+
+use crate::pipewire::find_spatializer_node;
+
+const FILTER_CHAIN_CONF: &str = include_str!("../conf/99-spatializer.conf");
+const CONF_FILE_NAME: &str = "spatializer.conf";
+
+// what a standalone pipewire client needs before the filter-chain module
+const CLIENT_MODULES: &str = "\
+context.properties = { log.level = 0 }
+context.spa-libs = {
+    audio.convert.* = audioconvert/libspa-audioconvert
+    support.*       = support/libspa-support
+}
+context.modules = [
+    { name = libpipewire-module-rt flags = [ ifexists nofail ] }
+    { name = libpipewire-module-protocol-native }
+    { name = libpipewire-module-client-node }
+    { name = libpipewire-module-adapter }
+";
+
+// how long a fresh client gets to show its node
+const LOAD_TIMEOUT: Duration = Duration::from_secs(3);
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+// a run at least this long counts as healthy and resets the backoff
+const STABLE_RUN: Duration = Duration::from_secs(30);
+const STOP_TIMEOUT: Duration = Duration::from_secs(1);
+
+pub enum ChainStatus {
+    // our own client
+    Loaded { pid: u32, since: Instant },
+    // the node was already there, not ours to stop
+    Reused,
+    // the client exited, loading again at `restart_at`
+    Waiting { restart_at: Instant, exit: Option<ExitStatus> },
+}
+
+pub struct FilterChain {
+    node_name: String,
+    conf_path: PathBuf,
+    child: Option<Child>,
+    backoff: Duration,
+    pub status: ChainStatus,
+    pub restarts: u32,
+    pub last_error: Option<String>,
+}
+
+impl FilterChain {
+    // reuse a running filter-chain or start one and wait for its node
+    pub fn load(node_name: &str) -> Result<Self, String> {
+        let conf_path = runtime_dir().join(CONF_FILE_NAME);
+        let mut chain = Self {
+            node_name: node_name.to_string(),
+            conf_path,
+            child: None,
+            backoff: MIN_BACKOFF,
+            status: ChainStatus::Reused,
+            restarts: 0,
+            last_error: None,
+        };
+        if find_spatializer_node(node_name).is_some() {
+            return Ok(chain);
+        }
+
+        write_conf(&chain.conf_path)
+            .map_err(|e| format!("Failed to write {}: {}", chain.conf_path.display(), e))?;
+        chain.spawn(Instant::now())?;
+        let started = Instant::now();
+        while find_spatializer_node(node_name).is_none() {
+            if let Some(Ok(Some(exit))) = chain.child.as_mut().map(|c| c.try_wait()) {
+                chain.child = None;
+                return Err(format!(
+                    "Failed to load the filter-chain: pipewire {}, run `pipewire -c {}` to see why",
+                    exit,
+                    chain.conf_path.display()
+                ));
+            }
+            if started.elapsed() > LOAD_TIMEOUT {
+                let waited = LOAD_TIMEOUT.as_secs();
+                return Err(format!("Failed to load the filter-chain: no '{}' node after {}s", node_name, waited));
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        Ok(chain)
+    }
+
+    // notice an exited client and load it again when due, never blocks
+    pub fn poll(&mut self, now: Instant) {
+        match self.status {
+            ChainStatus::Loaded { since, .. } => {
+                let Some(ref mut child) = self.child else { return };
+                let Ok(Some(exit)) = child.try_wait() else { return };
+                self.child = None;
+                if now.duration_since(since) >= STABLE_RUN {
+                    self.backoff = MIN_BACKOFF;
+                }
+                self.status = ChainStatus::Waiting { restart_at: now + self.backoff, exit: Some(exit) };
+                self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+            }
+            ChainStatus::Waiting { restart_at, .. } if now >= restart_at => {
+                self.restarts += 1;
+                // someone else may have brought the node back meanwhile
+                if find_spatializer_node(&self.node_name).is_some() {
+                    self.status = ChainStatus::Reused;
+                } else if let Err(e) = self.spawn(now) {
+                    self.last_error = Some(e);
+                    self.status = ChainStatus::Waiting { restart_at: now + self.backoff, exit: None };
+                    self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn spawn(&mut self, now: Instant) -> Result<(), String> {
+        let mut command = Command::new("pipewire");
+        command.arg("-c").arg(&self.conf_path).stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+        // the client goes down with us even if we are killed
+        die_with_parent(&mut command);
+        let child = command.spawn().map_err(|e| format!("Failed to run pipewire: {}", e))?;
+        self.status = ChainStatus::Loaded { pid: child.id(), since: now };
+        self.child = Some(child);
+        self.last_error = None;
+        Ok(())
+    }
+}
+
+impl Drop for FilterChain {
+    // stop our client and wait for it, so the nodes are gone when we exit
+    fn drop(&mut self) {
+        if let Some(ref mut child) = self.child {
+            stop_child(child, STOP_TIMEOUT);
+        }
+    }
+}
+
+// SIGTERM for the spawned process when spatial-track dies, cleaned up or not
+pub fn die_with_parent(command: &mut Command) {
+    // SAFETY: the hook runs between fork and exec and only calls prctl, which is
+    // async-signal-safe, and reads errno
+    unsafe {
+        command.pre_exec(|| {
+            if libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGTERM) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+// $XDG_RUNTIME_DIR/spatial-track, the file only has to live as long as the session
+fn runtime_dir() -> PathBuf {
+    let base = match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => std::env::temp_dir(),
+    };
+    base.join("spatial-track")
+}
+
+// the installed config's filter-chain module, prefixed with the client setup
+fn write_conf(path: &PathBuf) -> io::Result<()> {
+    let modules = FILTER_CHAIN_CONF
+        .split_once("context.modules = [")
+        .map(|(_, rest)| rest)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "filter-chain config has no context.modules"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, format!("{}{}", CLIENT_MODULES, modules))
+}
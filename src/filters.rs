@@ -0,0 +1,137 @@
+// alternative pose filters next to the plain exponential smoothing, one
+// instance per axis: the one euro filter (casiez et al. 2012) smooths hard
+// while the head is still and follows quickly once it moves, the kalman
+// filter tracks angle and angular velocity with a constant velocity model.
+// FilterComparison runs all kinds on the same input for tuning.
+
+use std::time::Instant;
+
+use crate::engine::{FilterKind, SmoothedState};
+
+// one euro: cutoff in hz at rest, its growth per °/s of speed, and the
+// cutoff of the speed estimate itself
+pub const ONE_EURO_MIN_CUTOFF: f64 = 1.0;
+pub const ONE_EURO_BETA: f64 = 0.02;
+const ONE_EURO_SPEED_CUTOFF: f64 = 1.0;
+
+// kalman: how much the angular velocity may change (°/s² squared) against
+// how noisy the tracker angle is (° squared)
+pub const KALMAN_PROCESS_NOISE: f64 = 1000.0;
+pub const KALMAN_MEASUREMENT_NOISE: f64 = 1.0;
+
+fn lowpass_alpha(cutoff: f64, dt: f64) -> f64 {
+    let tau = 1.0 / (2.0 * std::f64::consts::PI * cutoff);
+    1.0 / (1.0 + tau / dt)
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct OneEuro {
+    value: f64,
+    speed: f64,
+}
+
+impl OneEuro {
+    pub fn reset(&mut self, value: f64) {
+        *self = Self { value, speed: 0.0 };
+    }
+
+    pub fn update(&mut self, x: f64, dt: f64) -> f64 {
+        let raw_speed = (x - self.value) / dt;
+        self.speed += lowpass_alpha(ONE_EURO_SPEED_CUTOFF, dt) * (raw_speed - self.speed);
+        let cutoff = ONE_EURO_MIN_CUTOFF + ONE_EURO_BETA * self.speed.abs();
+        self.value += lowpass_alpha(cutoff, dt) * (x - self.value);
+        self.value
+    }
+
+    pub fn shift(&mut self, delta: f64) {
+        self.value += delta;
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct Kalman {
+    angle: f64,
+    velocity: f64,
+    // covariance [[angle, angle/velocity], [velocity/angle, velocity]]
+    p: [[f64; 2]; 2],
+}
+
+impl Kalman {
+    pub fn reset(&mut self, angle: f64) {
+        *self = Self { angle, velocity: 0.0, p: [[KALMAN_MEASUREMENT_NOISE, 0.0], [0.0, KALMAN_PROCESS_NOISE]] };
+    }
+
+    pub fn update(&mut self, measured: f64, dt: f64) -> f64 {
+        // predict with constant velocity, acceleration as process noise
+        self.angle += self.velocity * dt;
+        let [[p00, p01], [p10, p11]] = self.p;
+        let q = KALMAN_PROCESS_NOISE;
+        let (dt2, dt3, dt4) = (dt * dt, dt * dt * dt, dt * dt * dt * dt);
+        let p00 = p00 + dt * (p10 + p01) + dt2 * p11 + q * dt4 / 4.0;
+        let p01 = p01 + dt * p11 + q * dt3 / 2.0;
+        let p10 = p10 + dt * p11 + q * dt3 / 2.0;
+        let p11 = p11 + q * dt2;
+
+        // correct with the measured angle
+        let s = p00 + KALMAN_MEASUREMENT_NOISE;
+        let (k0, k1) = (p00 / s, p10 / s);
+        let innovation = measured - self.angle;
+        self.angle += k0 * innovation;
+        self.velocity += k1 * innovation;
+        self.p = [[(1.0 - k0) * p00, (1.0 - k0) * p01], [p10 - k1 * p00, p11 - k1 * p01]];
+        self.angle
+    }
+
+    pub fn shift(&mut self, delta: f64) {
+        self.angle += delta;
+    }
+}
+
+// every filter kind on the same input, to compare them live
+pub struct FilterComparison {
+    pub filters: Vec<SmoothedState>,
+}
+
+impl FilterComparison {
+    pub fn new(factor: f64) -> Self {
+        let filters = FilterKind::ALL
+            .iter()
+            .map(|&kind| {
+                let mut s = SmoothedState::new();
+                s.factor = factor;
+                s.set_kind(kind);
+                s
+            })
+            .collect();
+        Self { filters }
+    }
+
+    pub fn update(&mut self, (yaw, pitch, roll): (f64, f64, f64), time: Instant) {
+        for f in &mut self.filters {
+            f.update(yaw, pitch, roll, time);
+        }
+    }
+
+    // keep the ema's per-axis factors in line with the engine's
+    pub fn set_axis_factors(&mut self, factors: [Option<f64>; 3]) {
+        for f in &mut self.filters {
+            f.axis_factors = factors;
+        }
+    }
+
+    pub fn get(&self, kind: FilterKind) -> Option<&SmoothedState> {
+        self.filters.iter().find(|f| f.kind == kind)
+    }
+
+    pub fn shift(&mut self, delta: (f64, f64, f64)) {
+        for f in &mut self.filters {
+            f.shift(delta);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for f in &mut self.filters {
+            f.reset();
+        }
+    }
+}
@@ -0,0 +1,191 @@
+// game integration: the smoothed pose and spatial state as a fixed binary
+// record (StGameState in ffi.rs / include/spatial_track.h) that game mods and
+// engine plugins can poll every frame without a second opentrack instance:
+//
+// - shared memory: /dev/shm/<name>, mapped read-only by the game and
+//   updated in place under a seqlock
+// - udp: the same bytes as one datagram per update to a local port
+//
+// the file is removed again on exit, with its magic zeroed first so a game
+// that still has it mapped notices.
+
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::mem;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::os::unix::io::AsRawFd;
+use std::path::PathBuf;
+use std::ptr;
+use std::sync::atomic::{fence, AtomicU32, Ordering};
+use std::time::Instant;
+
+use crate::ffi::{StGameState, StSpatialState, ST_GAME_MAGIC, ST_GAME_VERSION};
+use crate::sink::{Frame, OutputSink};
+
+const SHM_DIR: &str = "/dev/shm";
+
+struct SharedState {
+    path: PathBuf,
+    state: *mut StGameState,
+}
+
+impl SharedState {
+    fn create(name: &str) -> Result<Self, String> {
+        if name.is_empty() || name.contains('/') {
+            return Err(format!("invalid shared memory name '{}'", name));
+        }
+        let path = PathBuf::from(SHM_DIR).join(name);
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let size = mem::size_of::<StGameState>();
+        file.set_len(size as u64).map_err(|e| format!("Failed to resize {}: {}", path.display(), e))?;
+        // SAFETY: a fresh shared mapping of the whole file, which was just sized to
+        // one StGameState; the fd may be closed afterwards, the mapping stays
+        let state = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                size,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if state == libc::MAP_FAILED {
+            let e = std::io::Error::last_os_error();
+            fs::remove_file(&path).ok();
+            return Err(format!("Failed to map {}: {}", path.display(), e));
+        }
+        Ok(Self { path, state: state as *mut StGameState })
+    }
+
+    // seqlock write: odd sequence, the record, even sequence
+    fn write(&mut self, record: &StGameState) {
+        let writing = record.sequence.wrapping_sub(1);
+        // SAFETY: state points to the live mapping until drop, the mapping is page
+        // aligned so the repr(C) record and its u32 sequence are aligned, and
+        // &mut self makes this the only writer in the process
+        let sequence = unsafe { AtomicU32::from_ptr(ptr::addr_of_mut!((*self.state).sequence)) };
+        sequence.store(writing, Ordering::Relaxed);
+        fence(Ordering::Release);
+        // SAFETY: as above; the game only reads, and rejects the copy if the
+        // sequence changed while it read
+        unsafe {
+            ptr::write_volatile(self.state, StGameState { sequence: writing, ..*record });
+        }
+        sequence.store(record.sequence, Ordering::Release);
+    }
+}
+
+impl Drop for SharedState {
+    fn drop(&mut self) {
+        // SAFETY: state is the mapping of exactly this size from create and is
+        // not used after being unmapped here
+        unsafe {
+            ptr::write_volatile(ptr::addr_of_mut!((*self.state).magic), 0);
+            libc::munmap(self.state as *mut libc::c_void, mem::size_of::<StGameState>());
+        }
+        fs::remove_file(&self.path).ok();
+    }
+}
+
+pub struct GameExport {
+    shm: Option<SharedState>,
+    udp: Option<(UdpSocket, SocketAddr)>,
+    // sequence of the last record, always even
+    sequence: u32,
+    pub errors: u64,
+}
+
+impl GameExport {
+    // shm is a file name under /dev/shm, udp a "host:port" target
+    pub fn new(shm: Option<&str>, udp: Option<&str>) -> Result<Self, String> {
+        let shm = shm.map(SharedState::create).transpose()?;
+        let udp = match udp {
+            Some(target) => {
+                let addr = target
+                    .to_socket_addrs()
+                    .map_err(|e| format!("invalid game udp target '{}': {}", target, e))?
+                    .next()
+                    .ok_or_else(|| format!("game udp target '{}' did not resolve", target))?;
+                let bind = if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+                let socket = UdpSocket::bind(bind).map_err(|e| format!("Failed to bind game udp socket: {}", e))?;
+                socket.set_nonblocking(true).ok();
+                Some((socket, addr))
+            }
+            None => None,
+        };
+        Ok(Self { shm, udp, sequence: 0, errors: 0 })
+    }
+}
+
+impl OutputSink for GameExport {
+    fn name(&self) -> &'static str {
+        "game"
+    }
+
+    fn send(&mut self, frame: &Frame) {
+        self.sequence = self.sequence.wrapping_add(2);
+        let pose = frame.pose;
+        let record = StGameState {
+            magic: ST_GAME_MAGIC,
+            version: ST_GAME_VERSION,
+            sequence: self.sequence,
+            size: mem::size_of::<StGameState>() as u32,
+            timestamp_us: monotonic_us(pose.time),
+            translation: pose.translation,
+            raw_yaw: pose.yaw,
+            raw_pitch: pose.pitch,
+            raw_roll: pose.roll,
+            yaw: frame.smoothed[0],
+            pitch: frame.smoothed[1],
+            roll: frame.smoothed[2],
+            velocity: frame.motion.velocity,
+            intensity: frame.motion.intensity,
+            spatial: StSpatialState::from(frame.spatial),
+        };
+
+        if let Some(ref mut shm) = self.shm {
+            shm.write(&record);
+        }
+        if let Some((ref socket, addr)) = self.udp {
+            // repr(C) without padding, so the struct is its wire format
+            // SAFETY: record is a local that outlives bytes, and every byte of it
+            // is initialized since there is no padding
+            let bytes = unsafe {
+                std::slice::from_raw_parts(&record as *const StGameState as *const u8, mem::size_of::<StGameState>())
+            };
+            if socket.send_to(bytes, addr).is_err() {
+                self.errors += 1;
+            }
+        }
+    }
+
+    fn status(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("\x1B[1;37mGame:\x1B[0m")?;
+        if let Some(ref shm) = self.shm {
+            write!(f, " {} ", shm.path.display())?;
+        }
+        if let Some((_, addr)) = self.udp {
+            write!(f, " udp {} ", addr)?;
+        }
+        write!(f, " seq \x1B[1;37m{}\x1B[0m  err {}", self.sequence, self.errors)
+    }
+}
+
+// CLOCK_MONOTONIC (what Instant uses on linux) at `time`, so games can
+// compare it with their own clock
+fn monotonic_us(time: Instant) -> u64 {
+    let mut now = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // SAFETY: now is a valid timespec for the duration of the call
+    unsafe {
+        libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut now);
+    }
+    let now_us = now.tv_sec as u64 * 1_000_000 + now.tv_nsec as u64 / 1000;
+    now_us.saturating_sub(time.elapsed().as_micros() as u64)
+}
@@ -0,0 +1,93 @@
+// eased transitions for jumps in the head pose. recentering or tracking
+// coming back after a gap moves the smoothed pose in one step; instead of
+// snapping the speakers there, the difference to what was shown before is
+// faded out along an easing curve, while the pose underneath keeps moving.
+
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_GLIDE: Duration = Duration::from_millis(400);
+pub const MAX_GLIDE: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Easing {
+    Linear,
+    // fast start, gentle landing
+    EaseOut,
+    // gentle start and landing
+    EaseInOut,
+}
+
+impl Easing {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "linear" => Some(Easing::Linear),
+            "ease-out" => Some(Easing::EaseOut),
+            "ease-in-out" => Some(Easing::EaseInOut),
+            _ => None,
+        }
+    }
+
+    // progress 0..1 -> eased progress 0..1
+    pub fn apply(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+pub struct Glide {
+    pub easing: Easing,
+    // zero = snap
+    pub duration: Duration,
+    // shown minus actual pose at the jump, faded to 0 over the glide
+    offset: [f64; 3],
+    started: Option<Instant>,
+    pending: bool,
+    last: Option<[f64; 3]>,
+}
+
+impl Glide {
+    pub fn new(easing: Easing, duration: Duration) -> Self {
+        Self { easing, duration, offset: [0.0; 3], started: None, pending: false, last: None }
+    }
+
+    // the pose is about to jump: the next `apply` glides from what was
+    // shown last to the new pose. nothing to glide from before the first pose
+    pub fn start(&mut self) {
+        self.pending = self.last.is_some() && !self.duration.is_zero();
+    }
+
+    // the pose to show for `pose` (yaw, pitch, roll) at `now`
+    pub fn apply(&mut self, pose: [f64; 3], now: Instant) -> [f64; 3] {
+        if self.pending {
+            // `last` is the in-between position if a glide was already running
+            let last = self.last.unwrap_or(pose);
+            self.offset = [last[0] - pose[0], last[1] - pose[1], last[2] - pose[2]];
+            self.started = Some(now);
+            self.pending = false;
+        }
+
+        let shown = match self.started {
+            Some(started) => {
+                let t = now.duration_since(started).as_secs_f64() / self.duration.as_secs_f64();
+                if t >= 1.0 {
+                    self.started = None;
+                    pose
+                } else {
+                    let remaining = 1.0 - self.easing.apply(t);
+                    [
+                        pose[0] + self.offset[0] * remaining,
+                        pose[1] + self.offset[1] * remaining,
+                        pose[2] + self.offset[2] * remaining,
+                    ]
+                }
+            }
+            None => pose,
+        };
+        self.last = Some(shown);
+        shown
+    }
+}
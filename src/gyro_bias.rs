@@ -0,0 +1,187 @@
+// gyro bias (drift) compensation for imu-style trackers.
+//
+// a gyro bias shows up in the fused orientation as a slow, steady rotation
+// while the head is actually still. we fit a line through a window of
+// samples: low residual noise means the user is holding still, and the
+// slope of that line is the remaining drift in deg/s, which gets added to
+// the bias estimate and subtracted from every later pose.
+//
+// bias is stored per input device in the state dir so it survives restarts.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::state;
+
+// explicit "hold still" calibration length
+pub const BIAS_CALIBRATION_TIME: Duration = Duration::from_secs(5);
+
+// rolling window for automatic re-calibration
+const DRIFT_WINDOW: Duration = Duration::from_secs(10);
+
+// residual std dev (degrees) below which the head counts as still
+const STILL_NOISE: f64 = 0.15;
+
+// auto re-calibration only kicks in between these drift rates (deg/s);
+// anything faster is more likely a slow deliberate turn than bias
+const MIN_AUTO_DRIFT: f64 = 0.02;
+const MAX_AUTO_DRIFT: f64 = 1.0;
+
+const MIN_SAMPLES: usize = 50;
+
+const BIAS_FILE: &str = "gyro_bias";
+
+pub struct DriftCompensator {
+    device: String,
+    pub bias: [f64; 3], // yaw, pitch, roll in deg/s
+    pub auto: bool,
+    offset: [f64; 3],
+    last_time: Option<Instant>,
+    calibrating_since: Option<Instant>,
+    samples: VecDeque<(Instant, [f64; 3])>,
+    pub last_event: Option<String>,
+}
+
+impl DriftCompensator {
+    // loads the stored bias for `device` if there is one
+    pub fn new(device: &str, auto: bool) -> Self {
+        let bias = match state::load_record(BIAS_FILE, device) {
+            Some(v) if v.len() == 3 => [v[0], v[1], v[2]],
+            _ => [0.0; 3],
+        };
+        Self {
+            device: device.to_string(),
+            bias,
+            auto,
+            offset: [0.0; 3],
+            last_time: None,
+            calibrating_since: None,
+            samples: VecDeque::with_capacity(1024),
+            last_event: None,
+        }
+    }
+
+    pub fn start_calibration(&mut self, now: Instant) {
+        self.calibrating_since = Some(now);
+        self.samples.clear();
+        self.last_event = None;
+    }
+
+    pub fn calibration_remaining(&self, now: Instant) -> Option<Duration> {
+        let since = self.calibrating_since?;
+        Some(BIAS_CALIBRATION_TIME.saturating_sub(now.duration_since(since)))
+    }
+
+    // subtract the accumulated drift from a raw pose and update the estimate
+    pub fn apply(&mut self, pose: [f64; 3], now: Instant) -> [f64; 3] {
+        if let Some(last) = self.last_time {
+            let dt = now.duration_since(last).as_secs_f64();
+            for (offset, bias) in self.offset.iter_mut().zip(self.bias) {
+                *offset += bias * dt;
+            }
+        }
+        self.last_time = Some(now);
+
+        let corrected = [pose[0] - self.offset[0], pose[1] - self.offset[1], pose[2] - self.offset[2]];
+        if corrected.iter().all(|v| v.is_finite()) {
+            self.observe(corrected, now);
+        }
+        corrected
+    }
+
+    fn observe(&mut self, corrected: [f64; 3], now: Instant) {
+        self.samples.push_back((now, corrected));
+
+        let window = if self.calibrating_since.is_some() { BIAS_CALIBRATION_TIME } else { DRIFT_WINDOW };
+        while let Some(&(t, _)) = self.samples.front() {
+            if now.duration_since(t) > window {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if let Some(since) = self.calibrating_since {
+            if now.duration_since(since) < BIAS_CALIBRATION_TIME {
+                return;
+            }
+            self.calibrating_since = None;
+            match self.fit() {
+                Some((drift, noise)) if noise < STILL_NOISE => {
+                    self.adjust(drift);
+                    self.last_event = Some("bias calibrated".to_string());
+                }
+                Some(_) => self.last_event = Some("moved during calibration".to_string()),
+                None => self.last_event = Some("not enough samples".to_string()),
+            }
+            self.samples.clear();
+            return;
+        }
+
+        if !self.auto || self.samples.len() < MIN_SAMPLES {
+            return;
+        }
+        // only decide once a full window of stillness is available
+        let span = now.duration_since(self.samples[0].0);
+        if span < DRIFT_WINDOW.mul_f64(0.9) {
+            return;
+        }
+        if let Some((drift, noise)) = self.fit() {
+            let worst = drift.iter().fold(0.0_f64, |m, d| m.max(d.abs()));
+            if noise < STILL_NOISE && (MIN_AUTO_DRIFT..MAX_AUTO_DRIFT).contains(&worst) {
+                self.adjust(drift);
+                self.last_event = Some("drift detected, bias updated".to_string());
+                self.samples.clear();
+            }
+        }
+    }
+
+    fn adjust(&mut self, drift: [f64; 3]) {
+        for (bias, d) in self.bias.iter_mut().zip(drift) {
+            *bias += d;
+        }
+        state::save_record(BIAS_FILE, &self.device, &self.bias).ok();
+    }
+
+    // least squares slope per axis (deg/s) and the worst residual std dev,
+    // from running sums so no per-sample buffer is needed. times count from
+    // the first sample and angles from its pose, which keeps the sums small
+    fn fit(&self) -> Option<([f64; 3], f64)> {
+        let n = self.samples.len();
+        if n < MIN_SAMPLES {
+            return None;
+        }
+        let (t0, p0) = self.samples[0];
+        let (mut sx, mut sxx) = (0.0, 0.0);
+        let (mut sy, mut sxy, mut syy) = ([0.0; 3], [0.0; 3], [0.0; 3]);
+        for (t, p) in &self.samples {
+            let x = t.duration_since(t0).as_secs_f64();
+            sx += x;
+            sxx += x * x;
+            for axis in 0..3 {
+                let y = p[axis] - p0[axis];
+                sy[axis] += y;
+                sxy[axis] += x * y;
+                syy[axis] += y * y;
+            }
+        }
+        let n = n as f64;
+        let var_x = sxx - sx * sx / n;
+        if var_x <= f64::EPSILON {
+            return None;
+        }
+
+        let mut slopes = [0.0; 3];
+        let mut noise: f64 = 0.0;
+        for axis in 0..3 {
+            let cov = sxy[axis] - sx * sy[axis] / n;
+            let var_y = syy[axis] - sy[axis] * sy[axis] / n;
+            let slope = cov / var_x;
+            // what the line leaves unexplained; rounding can push it just below 0
+            let residual = (var_y - slope * cov).max(0.0);
+            slopes[axis] = slope;
+            noise = noise.max((residual / n).sqrt());
+        }
+        Some((slopes, noise))
+    }
+}
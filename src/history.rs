@@ -0,0 +1,51 @@
+// per-second event counts over the last minute. a tracker that stalls for
+// half a second only shows up as a brief fps dip live; keeping the counts
+// lets the dashboard draw them as a graph so stalls are still visible later.
+
+use std::time::Instant;
+
+pub const HISTORY_SECONDS: usize = 60;
+
+pub struct RateHistory {
+    start: Instant,
+    // seconds since start of the bucket currently being filled
+    second: u64,
+    // ring of counts, one slot per second plus the one being filled
+    buckets: [u32; HISTORY_SECONDS + 1],
+}
+
+impl RateHistory {
+    pub fn new(now: Instant) -> Self {
+        Self { start: now, second: 0, buckets: [0; HISTORY_SECONDS + 1] }
+    }
+
+    pub fn record(&mut self, now: Instant) {
+        self.advance(now);
+        self.buckets[self.slot(self.second)] += 1;
+    }
+
+    // move to the current second, seconds without events count as 0
+    pub fn advance(&mut self, now: Instant) {
+        let second = now.saturating_duration_since(self.start).as_secs();
+        if second.saturating_sub(self.second) > HISTORY_SECONDS as u64 {
+            self.buckets = [0; HISTORY_SECONDS + 1];
+            self.second = second;
+            return;
+        }
+        while self.second < second {
+            self.second += 1;
+            let slot = self.slot(self.second);
+            self.buckets[slot] = 0;
+        }
+    }
+
+    // counts of the completed seconds, oldest first (up to HISTORY_SECONDS)
+    pub fn completed(&self) -> impl Iterator<Item = u32> + '_ {
+        let len = (self.second as usize).min(HISTORY_SECONDS);
+        (self.second - len as u64..self.second).map(|s| self.buckets[self.slot(s)])
+    }
+
+    fn slot(&self, second: u64) -> usize {
+        (second % (HISTORY_SECONDS as u64 + 1)) as usize
+    }
+}
@@ -0,0 +1,90 @@
+// orientation math shared by the imu inputs (ble, serial): quaternions from
+// the sensor's fusion to yaw/pitch/roll, and the mounting correction for a
+// sensor that is not sitting upright and facing forward on the head.
+//
+// quaternions are [w, x, y, z], z up; angles use the z-y-x convention.
+
+pub type Quaternion = [f64; 4];
+
+pub const IDENTITY: Quaternion = [1.0, 0.0, 0.0, 0.0];
+
+pub fn multiply(a: Quaternion, b: Quaternion) -> Quaternion {
+    let [aw, ax, ay, az] = a;
+    let [bw, bx, by, bz] = b;
+    [
+        aw * bw - ax * bx - ay * by - az * bz,
+        aw * bx + ax * bw + ay * bz - az * by,
+        aw * by - ax * bz + ay * bw + az * bx,
+        aw * bz + ax * by - ay * bx + az * bw,
+    ]
+}
+
+pub fn conjugate([w, x, y, z]: Quaternion) -> Quaternion {
+    [w, -x, -y, -z]
+}
+
+// degrees -> quaternion
+pub fn from_euler(yaw: f64, pitch: f64, roll: f64) -> Quaternion {
+    let (sy, cy) = (yaw.to_radians() / 2.0).sin_cos();
+    let (sp, cp) = (pitch.to_radians() / 2.0).sin_cos();
+    let (sr, cr) = (roll.to_radians() / 2.0).sin_cos();
+    [
+        cr * cp * cy + sr * sp * sy,
+        sr * cp * cy - cr * sp * sy,
+        cr * sp * cy + sr * cp * sy,
+        cr * cp * sy - sr * sp * cy,
+    ]
+}
+
+// quaternion -> [yaw, pitch, roll] in degrees, None when it is not a rotation
+pub fn to_euler(q: Quaternion) -> Option<[f64; 3]> {
+    let norm = q.iter().map(|c| c * c).sum::<f64>().sqrt();
+    if !norm.is_finite() || norm < 1e-6 {
+        return None;
+    }
+    let [w, x, y, z] = q.map(|c| c / norm);
+    let yaw = (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z));
+    let pitch = (2.0 * (w * y - z * x)).clamp(-1.0, 1.0).asin();
+    let roll = (2.0 * (w * x + y * z)).atan2(1.0 - 2.0 * (x * x + y * y));
+    Some([yaw.to_degrees(), pitch.to_degrees(), roll.to_degrees()])
+}
+
+// how the sensor sits on the head: the rotation from "upright, facing
+// forward" to its actual position. the correction undoes it, so head yaw
+// stays yaw even when the board is taped sideways to a headband strap
+#[derive(Clone, Copy, Debug)]
+pub struct Mounting {
+    rotation: Quaternion,
+}
+
+impl Default for Mounting {
+    fn default() -> Self {
+        Self { rotation: IDENTITY }
+    }
+}
+
+impl Mounting {
+    // "<roll>,<pitch>,<yaw>" in degrees or one of the named positions
+    pub fn parse(spec: &str) -> Option<Self> {
+        let [roll, pitch, yaw] = match spec {
+            "upright" => [0.0, 0.0, 0.0],
+            "upside-down" => [180.0, 0.0, 0.0],
+            "left" => [90.0, 0.0, 0.0],
+            "right" => [-90.0, 0.0, 0.0],
+            "backwards" => [0.0, 0.0, 180.0],
+            _ => {
+                let angles: Vec<f64> = spec.split(',').map(|a| a.trim().parse().ok()).collect::<Option<_>>()?;
+                angles.try_into().ok()?
+            }
+        };
+        [roll, pitch, yaw]
+            .iter()
+            .all(|a: &f64| a.is_finite())
+            .then(|| Self { rotation: from_euler(yaw, pitch, roll) })
+    }
+
+    // sensor orientation -> head orientation
+    pub fn correct(&self, sensor: Quaternion) -> Quaternion {
+        multiply(sensor, conjugate(self.rotation))
+    }
+}
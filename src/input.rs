@@ -0,0 +1,168 @@
+// tracker inputs. every source turns whatever the tracker speaks into
+// timestamped poses; which one is used comes from a uri like
+// "opentrack-udp://127.0.0.1:4242", so new kinds of trackers only need
+// another InputSource impl and a scheme in `open`.
+
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use crate::ble_input::BleInput;
+use crate::demo::DemoInput;
+use crate::packet_layout::{PacketLayout, LAYOUT_PARAMS};
+use crate::relay::OPENTRACK_PACKET_SIZE;
+use crate::serial_imu::SerialImu;
+use crate::tcp_input::{self, OpenTrackTcp};
+
+pub const DEFAULT_INPUT: &str = "opentrack-udp://127.0.0.1:4242";
+
+// a gap this long between poses counts as lost tracking; the filters then
+// restart at the next pose instead of gliding over from where tracking stopped
+pub const TRACKING_LOSS_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Copy, Debug)]
+pub struct Pose {
+    // x, y, z in cm
+    pub translation: [f64; 3],
+    // degrees, positive yaw = looking left, positive pitch = looking up
+    pub yaw: f64,
+    pub pitch: f64,
+    pub roll: f64,
+    // when the pose was received
+    pub time: Instant,
+}
+
+impl Pose {
+    // true if this pose is the first one after lost tracking
+    pub fn resumes_after(&self, previous: Option<Instant>) -> bool {
+        previous.is_some_and(|t| self.time.duration_since(t) > TRACKING_LOSS_TIMEOUT)
+    }
+
+    // [x, y, z, yaw, pitch, roll], the opentrack field order
+    pub fn to_array(&self) -> [f64; 6] {
+        let [x, y, z] = self.translation;
+        [x, y, z, self.yaw, self.pitch, self.roll]
+    }
+}
+
+pub trait InputSource {
+    // wait up to `timeout` for the next pose, Ok(None) when nothing arrived.
+    // a zero timeout only returns what is already queued
+    fn recv(&mut self, timeout: Duration) -> Result<Option<Pose>, String>;
+
+    // the uri the source was opened with, also used as its device id
+    fn uri(&self) -> &str;
+
+    // the device the poses currently come from, which differs from uri()
+    // while a failover input is on a fallback
+    fn active_uri(&self) -> &str {
+        self.uri()
+    }
+
+    // short human readable description for the dashboard
+    fn describe(&self) -> String;
+
+    // something to tell the user about since the last call, such as a
+    // switch to another source
+    fn take_event(&mut self) -> Option<String> {
+        None
+    }
+}
+
+pub fn open(uri: &str) -> Result<Box<dyn InputSource>, String> {
+    let (scheme, rest) = uri
+        .split_once("://")
+        .ok_or_else(|| format!("invalid input '{}', expected <scheme>://<address>", uri))?;
+    match scheme {
+        "opentrack-udp" | "opentrack-tcp" | "opentrack-noise" => {
+            let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+            let (mut key, mut layout_params) = (None, Vec::new());
+            for param in query.split('&').filter(|p| !p.is_empty()) {
+                match param.split_once('=') {
+                    Some(("key", file)) if scheme == "opentrack-noise" => key = Some(file),
+                    Some((name, value)) if LAYOUT_PARAMS.contains(&name) => layout_params.push((name, value)),
+                    _ => return Err(format!("unknown input parameter '{}' in '{}'", param, uri)),
+                }
+            }
+            let layout = PacketLayout::from_params(&layout_params)?;
+            match scheme {
+                "opentrack-udp" => Ok(Box::new(OpenTrackUdp::bind(uri, address, layout)?)),
+                "opentrack-tcp" => Ok(Box::new(OpenTrackTcp::bind(uri, address, None, layout)?)),
+                _ => {
+                    let key = key.ok_or_else(|| format!("input '{}' needs a pre-shared key, e.g. {}?key=<file>", uri, uri))?;
+                    Ok(Box::new(OpenTrackTcp::bind(uri, address, Some(tcp_input::load_key(key)?), layout)?))
+                }
+            }
+        }
+        "ble" => Ok(Box::new(BleInput::new(uri, rest)?)),
+        "serial-imu" => Ok(Box::new(SerialImu::new(uri, rest)?)),
+        "demo" => Ok(Box::new(DemoInput::new(uri, rest)?)),
+        other => Err(format!(
+            "unknown input type '{}' (supported: opentrack-udp, opentrack-tcp, opentrack-noise, ble, serial-imu, demo)",
+            other
+        )),
+    }
+}
+
+// opentrack "UDP over network" output
+pub struct OpenTrackUdp {
+    uri: String,
+    addr: SocketAddr,
+    socket: UdpSocket,
+    timeout: Option<Duration>,
+    layout: PacketLayout,
+}
+
+impl OpenTrackUdp {
+    pub fn bind(uri: &str, address: &str, layout: PacketLayout) -> Result<Self, String> {
+        let addr = address
+            .to_socket_addrs()
+            .map_err(|e| format!("invalid input address '{}': {}", address, e))?
+            .next()
+            .ok_or_else(|| format!("input address '{}' did not resolve", address))?;
+        let socket = UdpSocket::bind(addr).map_err(|e| format!("Failed to bind socket: {}", e))?;
+        Ok(Self { uri: uri.to_string(), addr, socket, timeout: None, layout })
+    }
+}
+
+impl InputSource for OpenTrackUdp {
+    fn recv(&mut self, timeout: Duration) -> Result<Option<Pose>, String> {
+        if self.timeout != Some(timeout) {
+            let set = if timeout.is_zero() {
+                self.socket.set_nonblocking(true)
+            } else {
+                self.socket.set_nonblocking(false).and_then(|_| self.socket.set_read_timeout(Some(timeout)))
+            };
+            set.map_err(|e| format!("Failed to configure input socket: {}", e))?;
+            self.timeout = Some(timeout);
+        }
+
+        let mut buf = [0u8; OPENTRACK_PACKET_SIZE];
+        match self.socket.recv_from(&mut buf) {
+            Ok((OPENTRACK_PACKET_SIZE, _)) => match self.layout.decode(&buf) {
+                Some([x, y, z, yaw, pitch, roll]) => {
+                    Ok(Some(Pose { translation: [x, y, z], yaw, pitch, roll, time: Instant::now() }))
+                }
+                // values that aren't a pose, skip
+                None => Ok(None),
+            },
+            // bad packet size, skip
+            Ok(_) => Ok(None),
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut | io::ErrorKind::Interrupted) => {
+                Ok(None)
+            }
+            Err(e) => Err(format!("Failed to read from {}: {}", self.addr, e)),
+        }
+    }
+
+    fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    fn describe(&self) -> String {
+        match self.layout.name() {
+            "opentrack" => format!("OpenTrack UDP {}", self.addr),
+            layout => format!("OpenTrack UDP {} ({} layout)", self.addr, layout),
+        }
+    }
+}
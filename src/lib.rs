@@ -0,0 +1,38 @@
+pub mod artnet;
+pub mod ble_input;
+pub mod calibration;
+pub mod default_sink;
+pub mod demo;
+pub mod engine;
+pub mod failover;
+pub mod ffi;
+pub mod filters;
+pub mod game_export;
+pub mod glide;
+pub mod gyro_bias;
+pub mod history;
+pub mod imu;
+pub mod input;
+pub mod loudness;
+pub mod mapping;
+pub mod media_control;
+pub mod motion;
+pub mod multi_input;
+pub mod output_zones;
+pub mod packet_layout;
+pub mod process;
+pub mod profile;
+pub mod relay;
+pub mod rerun_log;
+pub mod room;
+pub mod serial_imu;
+pub mod session_lock;
+pub mod session_record;
+pub mod sink;
+pub mod smoothing_tune;
+pub mod state;
+pub mod supervisor;
+pub mod tcp_input;
+pub mod uinput;
+pub mod volume_guard;
+pub mod zmq_pub;
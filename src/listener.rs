@@ -0,0 +1,118 @@
+// additional listeners: every extra tracker input drives its own filter-chain
+// node with its own smoothing and mapping state, e.g. a second person with
+// their own headphones and their own opentrack instance.
+
+use std::time::{Duration, Instant};
+
+use spatial_track::engine::Engine;
+use spatial_track::glide::Glide;
+use spatial_track::input::{self, InputSource, Pose};
+use spatial_track::motion::Motion;
+use spatial_track::sink::{Frame, OutputSink};
+
+use crate::output_stage::{OutputStage, Pauses};
+use crate::pipewire::{PipewireSink, VolumeControl};
+use crate::UPDATE_RATE_MS;
+
+pub struct ListenerSpec {
+    pub input: String,
+    pub node_name: String,
+}
+
+impl ListenerSpec {
+    // "<port>=<node>", "<addr:port>=<node>" or "<input uri>=<node>"
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (input, node_name) = spec
+            .rsplit_once('=')
+            .ok_or_else(|| format!("invalid listener '{}', expected <[addr:]port>=<node name>", spec))?;
+        let input = if input.contains("://") {
+            input.to_string()
+        } else if input.chars().all(|c| c.is_ascii_digit()) {
+            format!("opentrack-udp://127.0.0.1:{}", input)
+        } else {
+            format!("opentrack-udp://{}", input)
+        };
+        if node_name.is_empty() {
+            return Err(format!("listener '{}' has no node name", spec));
+        }
+        Ok(Self { input, node_name: node_name.to_string() })
+    }
+}
+
+pub struct Listener {
+    pub input: Box<dyn InputSource>,
+    pub pipewire: PipewireSink,
+    pub engine: Engine,
+    glide: Glide,
+    stage: OutputStage,
+    pub packets: u64,
+    last_pose: Option<Pose>,
+    last_packet: Option<Instant>,
+    last_update: Option<Instant>,
+}
+
+impl Listener {
+    pub fn bind(spec: &ListenerSpec, engine: Engine, glide: Glide, stage: OutputStage) -> Result<Self, String> {
+        let input = input::open(&spec.input).map_err(|e| format!("listener {}: {}", spec.node_name, e))?;
+
+        Ok(Self {
+            input,
+            pipewire: PipewireSink::new(&spec.node_name, VolumeControl::Filter),
+            engine,
+            glide,
+            stage,
+            packets: 0,
+            last_pose: None,
+            last_packet: None,
+            last_update: None,
+        })
+    }
+
+    // drain pending packets and push the result to this listener's node,
+    // through the same output stage and pauses as the main pipeline. while
+    // the session is paused the neutral state is sent even without poses
+    pub fn poll(&mut self, pauses: Pauses) {
+        let now = Instant::now();
+        self.pipewire.poll(now);
+
+        while let Ok(Some(pose)) = self.input.recv(Duration::ZERO) {
+            self.packets += 1;
+            if pose.resumes_after(self.last_packet) {
+                self.engine.reset();
+                self.glide.start();
+            }
+            self.engine.feed_pose(pose.yaw, pose.pitch, pose.roll, pose.time);
+            self.engine.feed_lean(pose.translation[2]);
+            self.last_packet = Some(pose.time);
+            self.last_pose = Some(pose);
+        }
+
+        if self.last_update.is_some_and(|t| now.duration_since(t) < Duration::from_millis(UPDATE_RATE_MS)) {
+            return;
+        }
+        let dt = self.last_update.map_or(0.0, |t| now.duration_since(t).as_secs_f64());
+        let (pose, smoothed, spatial) = match self.last_pose.take() {
+            Some(pose) => {
+                let s = &self.engine.smoothed;
+                let shown = self.glide.apply([s.yaw, s.pitch, s.roll], now);
+                let (spatial, _) = self.stage.process(&self.engine, shown, pauses, 1.0, dt, now);
+                (pose, shown, spatial)
+            }
+            None if pauses.session => {
+                let (spatial, _) = self.stage.neutral(&self.engine, 1.0, now);
+                (Pose { translation: [0.0; 3], yaw: 0.0, pitch: 0.0, roll: 0.0, time: now }, [0.0; 3], spatial)
+            }
+            None => return,
+        };
+        self.pipewire.send(&Frame {
+            pose: &pose,
+            smoothed,
+            // only pipewire sees listener frames, it does not use motion
+            motion: Motion::default(),
+            spatial: &spatial,
+            force: false,
+            latency_ms: 0.0,
+        });
+        self.last_update = Some(now);
+    }
+}
@@ -0,0 +1,173 @@
+// loudness leveling: a monitor tap on the spatializer's input measures how
+// loud the incoming audio is, and a slow gain pulls it towards a target level
+// so a quiet video and a loud game end up comparable. the gain is folded into
+// the speaker gains the panner sends anyway, so it rides along with distance
+// and rear attenuation instead of fighting them.
+//
+// the level is the rms of both channels over 400ms blocks (no k-weighting),
+// blocks below the gate (silence, pauses) leave the estimate alone.
+//
+// the leveling is global on purpose, not per stream: pipewire mixes every app
+// into the spatializer's single input before we see it, and the only gain we
+// own sits after that mix. leveling apps separately would mean a tap and a
+// volume per app stream, i.e. writing other programs' stream volumes, which
+// spatial-track never does. a quiet app next to a loud one stays quieter.
+
+use std::io::{BufReader, Read};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const MIN_TARGET_DB: f64 = -40.0;
+pub const MAX_TARGET_DB: f64 = -6.0;
+
+const SAMPLE_RATE: u32 = 48000;
+const BLOCK: Duration = Duration::from_millis(400);
+// quieter blocks are silence, not quiet content
+const GATE_DB: f64 = -50.0;
+// time constant of the loudness estimate
+const LOUDNESS_TIME: f64 = 3.0;
+// limits and speed of the applied correction
+const MAX_BOOST_DB: f64 = 12.0;
+const MAX_CUT_DB: f64 = 20.0;
+const GAIN_RATE_DB: f64 = 3.0;
+// pw-record exits with pipewire, try again after this long
+const RESTART_DELAY: Duration = Duration::from_secs(2);
+
+pub struct Leveler {
+    source: String,
+    target_db: f64,
+    monitor: Option<(Child, Receiver<f64>)>,
+    restart_at: Option<Instant>,
+    last_poll: Option<Instant>,
+    // smoothed level of the gated blocks, None until the first one
+    pub loudness_db: Option<f64>,
+    // correction currently applied
+    pub gain_db: f64,
+    pub error: Option<String>,
+}
+
+impl Leveler {
+    // measure the monitor of the `source` sink and level it to `target_db` dBFS
+    pub fn new(source: &str, target_db: f64) -> Self {
+        Self {
+            source: source.to_string(),
+            target_db,
+            monitor: None,
+            restart_at: Some(Instant::now()),
+            last_poll: None,
+            loudness_db: None,
+            gain_db: 0.0,
+            error: None,
+        }
+    }
+
+    // linear gain to multiply the speaker gains with
+    pub fn gain(&self) -> f64 {
+        10f64.powf(self.gain_db / 20.0)
+    }
+
+    // take new measurements and move the gain, never blocks
+    pub fn poll(&mut self, now: Instant) {
+        let dt = self.last_poll.map_or(0.0, |t| now.duration_since(t).as_secs_f64());
+        self.last_poll = Some(now);
+
+        if self.restart_at.is_some_and(|t| now >= t) {
+            self.restart_at = None;
+            match start_monitor(&self.source) {
+                Ok(monitor) => {
+                    self.monitor = Some(monitor);
+                    self.error = None;
+                }
+                Err(e) => {
+                    self.error = Some(e);
+                    self.restart_at = Some(now + RESTART_DELAY);
+                }
+            }
+        }
+
+        if let Some((_, ref levels)) = self.monitor {
+            let mut exited = false;
+            loop {
+                match levels.try_recv() {
+                    Ok(level) => self.loudness_db = update_loudness(self.loudness_db, level),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        exited = true;
+                        break;
+                    }
+                }
+            }
+            if exited {
+                if let Some((mut child, _)) = self.monitor.take() {
+                    child.kill().ok();
+                    child.wait().ok();
+                }
+                self.error = Some("pw-record exited".to_string());
+                self.restart_at = Some(now + RESTART_DELAY);
+            }
+        }
+
+        let Some(loudness) = self.loudness_db else { return };
+        let wanted = (self.target_db - loudness).clamp(-MAX_CUT_DB, MAX_BOOST_DB);
+        let step = GAIN_RATE_DB * dt;
+        self.gain_db += (wanted - self.gain_db).clamp(-step, step);
+    }
+}
+
+impl Drop for Leveler {
+    fn drop(&mut self) {
+        if let Some((ref mut child, _)) = self.monitor {
+            child.kill().ok();
+            child.wait().ok();
+        }
+    }
+}
+
+// the loudness estimate after one block, gated blocks leave it alone
+fn update_loudness(loudness_db: Option<f64>, level_db: f64) -> Option<f64> {
+    if level_db < GATE_DB {
+        return loudness_db;
+    }
+    let alpha = 1.0 - (-BLOCK.as_secs_f64() / LOUDNESS_TIME).exp();
+    Some(match loudness_db {
+        Some(l) => l + alpha * (level_db - l),
+        None => level_db,
+    })
+}
+
+// raw f32 stereo from the sink monitor, block levels in dBFS from a reader thread
+fn start_monitor(source: &str) -> Result<(Child, Receiver<f64>), String> {
+    let mut child = Command::new("pw-record")
+        .args(["--target", source, "-P", "{ stream.capture.sink = true node.passive = true }"])
+        .args(["--media-name", "spatial-track loudness", "--format", "f32"])
+        .args(["--rate", &SAMPLE_RATE.to_string(), "--channels", "2", "-"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run pw-record: {}", e))?;
+    let stdout = child.stdout.take().ok_or("Failed to read pw-record output")?;
+
+    let (tx, levels) = mpsc::channel();
+    thread::spawn(move || {
+        let block_samples = (SAMPLE_RATE as f64 * BLOCK.as_secs_f64()) as usize * 2;
+        let mut reader = BufReader::new(stdout);
+        let mut sample = [0u8; 4];
+        let (mut sum, mut count) = (0.0, 0);
+        while reader.read_exact(&mut sample).is_ok() {
+            let x = f32::from_le_bytes(sample) as f64;
+            sum += x * x;
+            count += 1;
+            if count == block_samples {
+                let level = 10.0 * (sum / count as f64).max(1e-12).log10();
+                if tx.send(level).is_err() {
+                    return;
+                }
+                (sum, count) = (0.0, 0);
+            }
+        }
+    });
+    Ok((child, levels))
+}
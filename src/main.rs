@@ -1,6 +1,5 @@
+use std::fmt::Write as _;
 use std::io::{stdout, Write};
-use std::net::UdpSocket;
-use std::process::{Command, Stdio};
 use std::time::{Duration, Instant};
 
 use crossterm::{
@@ -10,129 +9,68 @@ use crossterm::{
     ExecutableCommand,
 };
 
+use spatial_track::artnet::ArtNetOutput;
+use spatial_track::calibration::{Calibration, Calibrator};
+use spatial_track::default_sink::{DefaultSinkWatcher, DeviceClass};
+use spatial_track::demo::DemoSound;
+use spatial_track::failover::FailoverInput;
+use spatial_track::filters::FilterComparison;
+use spatial_track::game_export::GameExport;
+use spatial_track::glide::Glide;
+use spatial_track::gyro_bias::DriftCompensator;
+use spatial_track::history::RateHistory;
+use spatial_track::input::{self, InputSource, Pose};
+use spatial_track::loudness::Leveler;
+use spatial_track::media_control::MediaController;
+use spatial_track::motion::MotionTracker;
+use spatial_track::multi_input::MultiInput;
+use spatial_track::output_zones::OutputSwitcher;
+use spatial_track::profile::Profile;
+use spatial_track::relay::Relay;
+use spatial_track::rerun_log::RerunSink;
+use spatial_track::session_lock::SessionWatcher;
+use spatial_track::session_record::SessionRecorder;
+use spatial_track::sink::{Frame, Sinks};
+use spatial_track::smoothing_tune::{SmoothingProposal, SmoothingTuner};
+use spatial_track::supervisor::TrackerSupervisor;
+use spatial_track::uinput::{MouseLook, VirtualJoystick};
+use spatial_track::volume_guard::VolumeGuard;
+use spatial_track::zmq_pub::ZmqPublisher;
+use spatial_track::engine::{
+    Engine, FilterKind, Listening, OutputSmoother, SpatialState, SpeakerMode, MAX_RADIUS, MAX_WIDTH, MIN_RADIUS, MIN_WIDTH,
+};
 
-// smoothing: higher = smoother but more latency (0.0 - 0.99)
-const SMOOTHING_FACTOR: f64 = 0.65;
+mod bench;
+mod config;
+mod dashboard;
+mod filter_chain;
+mod listener;
+mod output_stage;
+mod pipewire;
+mod plain_status;
+mod room_sink;
+mod theme;
+mod watchdog;
+use dashboard::{box_line, box_row, get_visible_width, render_dashboard, Screen, BOX_WIDTH};
+use listener::Listener;
+use output_stage::{OutputStage, Pauses};
+use pipewire::{find_spatializer_node, PipewireSink};
+use plain_status::{PlainStatus, Tracking, STATUS_INTERVAL};
+use room_sink::RoomSink;
+use config::{CalibrationMode, Config, DriftCorrection, FilterChainMode, MediaControlMode, ParseOutcome};
+use filter_chain::{ChainStatus, FilterChain};
 
 // min time between updates (20ms = ~50fps)
 const UPDATE_RATE_MS: u64 = 20;
 
-// only send command if angle changes by this many degrees
-const CHANGE_THRESHOLD: f64 = 0.5;
-
-// default radius, can change at runtime
-const DEFAULT_RADIUS: f64 = 1.5;
-const MIN_RADIUS: f64 = 0.1;
-const MAX_RADIUS: f64 = 10.0;
+// keyboard step sizes for radius and width
 const RADIUS_STEP: f64 = 0.1;
-
-// dynamic reverb wet/dry mix depending on distance
-const MIN_REVERB: f64 = 0.05;  // closest
-const MAX_REVERB: f64 = 0.60;  // farthest
-
-// speaker angles for front and back modes (base angles at 100% width)
-const FRONT_LEFT_ANGLE: f64 = 45.0;   // +45° (front-left) - wider for less focus
-const FRONT_RIGHT_ANGLE: f64 = -45.0; // -45° (front-right)
-const BACK_LEFT_ANGLE: f64 = 135.0;   // +135° (back-left)
-const BACK_RIGHT_ANGLE: f64 = -135.0; // -135° (back-right)
-
-// stereo width control: adjusts speaker separation
-const DEFAULT_WIDTH: f64 = 1.0;  // 100% = full separation
-const MIN_WIDTH: f64 = 0.3;      // 30% = narrow (more focused)
-const MAX_WIDTH: f64 = 1.5;      // 150% = extra wide (very diffuse)
 const WIDTH_STEP: f64 = 0.1;
 
 // node name to search for in pipewire
 const SPATIALIZER_NODE_NAME: &str = "effect_input.spatializer";
-
-// ==============================================================================
-// DATA STRUCTURES
-// ==============================================================================
-
-#[derive(Clone, Copy, PartialEq)]
-enum SpeakerMode {
-    Front,
-    Back,
-}
-
-impl SpeakerMode {
-    fn label(&self) -> &'static str {
-        match self {
-            SpeakerMode::Front => "FRONT",
-            SpeakerMode::Back => "BACK",
-        }
-    }
-
-    fn base_angles(&self) -> (f64, f64) {
-        match self {
-            SpeakerMode::Front => (BACK_LEFT_ANGLE, BACK_RIGHT_ANGLE),
-            SpeakerMode::Back => (FRONT_LEFT_ANGLE, FRONT_RIGHT_ANGLE),
-        }
-    }
-}
-
-struct SmoothedState {
-    yaw: f64,
-    pitch: f64,
-    roll: f64,
-}
-
-impl SmoothedState {
-    fn new() -> Self {
-        Self { yaw: 0.0, pitch: 0.0, roll: 0.0 }
-    }
-
-    // apply exponential smoothing
-    fn update(&mut self, raw_yaw: f64, raw_pitch: f64, raw_roll: f64) {
-        self.yaw = SMOOTHING_FACTOR * self.yaw + (1.0 - SMOOTHING_FACTOR) * raw_yaw;
-        self.pitch = SMOOTHING_FACTOR * self.pitch + (1.0 - SMOOTHING_FACTOR) * raw_pitch;
-        self.roll = SMOOTHING_FACTOR * self.roll + (1.0 - SMOOTHING_FACTOR) * raw_roll;
-    }
-}
-
-// holds the calculated positions for the virtual speakers relative to head
-struct SpatialState {
-    left_az: f64,
-    right_az: f64,
-    elevation: f64,
-    radius: f64,
-    gain: f64, // volume scaling based on radius (1.0 / radius)
-    reverb_gain: f64, // wet signal amount (0.0 - 1.0)
-}
-
-impl SpatialState {
-    fn from_head_tracking(yaw: f64, pitch: f64, radius: f64, mode: SpeakerMode, reverb_enabled: bool, width: f64) -> Self {
-        // get base speaker angles based on mode
-        let (left_base, right_base) = mode.base_angles();
-
-        // width > 1.0 = wider (diffused), width < 1.0 = narrower (focused)
-        let left_base_scaled = left_base * width;
-        let right_base_scaled = right_base * width;
-
-        // relative azimuth = base_pos - head_yaw
-        let left_az = left_base_scaled - yaw;
-        let right_az = right_base_scaled - yaw;
-
-        // pitch is inverted (looking up moves the source down relative to eyes)
-        let elevation = -pitch;
-
-        // calculate gain: inverse relationship with radius
-        // at radius 1.0 = 100% gain, radius 2.0 = 50% gain, etc.
-        // clamp to reasonable range
-        let gain = (1.0 / radius).clamp(0.1, 2.0);
-
-        // calculate reverb gain using square-root curve for natural progression
-        // sqrt gives more reverb early on, then tapers - matches physical acoustics
-        let reverb_gain = if reverb_enabled {
-            let normalized = ((radius - MIN_RADIUS) / (MAX_RADIUS - MIN_RADIUS)).clamp(0.0, 1.0);
-            MIN_REVERB + normalized.sqrt() * (MAX_REVERB - MIN_REVERB)
-        } else {
-            0.0 // reverb disabled
-        };
-
-        Self { left_az, right_az, elevation, radius, gain, reverb_gain }
-    }
-}
+// playback side of the filter-chain, the stream that gets moved between sinks
+const SPATIALIZER_OUTPUT_NAME: &str = "effect_output.spatializer";
 
 // ==============================================================================
 // DISPLAY HELPERS
@@ -147,338 +85,245 @@ fn clear_screen() {
         .ok();
 }
 
-// helper: calculate string width ignoring ansi color codes
-// fixes border alignment by counting emojis as 2 width
-fn get_visible_width(s: &str) -> usize {
-    let mut width = 0;
-    let mut inside_ansi = false;
-    for c in s.chars() {
-        if c == '\x1B' {
-            inside_ansi = true;
-            continue;
+// ==============================================================================
+// MAIN
+// ==============================================================================
+
+fn main() {
+    let config = match Config::from_args() {
+        Ok(ParseOutcome::Run(config)) => config,
+        Ok(ParseOutcome::Help) => {
+            println!("{}", config::USAGE);
+            return;
         }
-        if inside_ansi {
-            if c == 'm' {
-                inside_ansi = false;
-            }
-            continue;
+        Ok(ParseOutcome::Bench(frames)) => {
+            bench::run(frames);
+            return;
         }
-        // account for double-width emojis used in headers
-        match c {
-             '🎧' | '🧭' | '🔊' | '📐' |  '📡' | '📈'  => width += 2,
-            _ => width += 1,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
         }
-    }
-    width
-}
+    };
 
-// render an azimuth position bar showing where a speaker is relative to center
-fn render_azimuth_bar(azimuth: f64, width: usize) -> String {
-    let mut bar = String::with_capacity(width + 20);
-    bar.push('[');
-
-    // map azimuth (-180..180) to bar position
-    // clamp to reasonable range for display
-    let clamped = azimuth.clamp(-90.0, 90.0);
-    let normalized = (clamped + 90.0) / 180.0; // 0..1
-    let pos = (normalized * (width - 1) as f64).round() as usize;
-    let center_idx = width / 2;
-
-    for i in 0..width {
-        if i == pos {
-            bar.push_str("\x1B[1;33m◆\x1B[0m"); // speaker position marker
-        } else if i == center_idx {
-            bar.push_str("\x1B[90m│\x1B[0m"); // center line
-        } else {
-            bar.push(' ');
-        }
+    // enable raw mode for keyboard input; plain status lines stay in the
+    // normal screen's scrollback
+    terminal::enable_raw_mode().expect("Failed to enable raw mode");
+    if !config.plain_status {
+        stdout().execute(EnterAlternateScreen).expect("Failed to enter alternate screen");
     }
 
-    bar.push(']');
-    bar
-}
+    // make sure we cleanup on exit
+    let result = run_main_loop(&config);
 
-// render an elevation indicator
-fn render_elevation_indicator(elevation: f64) -> &'static str {
-    if elevation > 10.0 {
-        "⬆ Above"
-    } else if elevation < -10.0 {
-        "⬇ Below"
-    } else {
-        "━ Level"
+    // cleanup terminal
+    terminal::disable_raw_mode().ok();
+    if !config.plain_status {
+        stdout().execute(LeaveAlternateScreen).ok();
     }
-}
 
-fn render_dashboard(
-    smoothed: &SmoothedState,
-    raw_yaw: f64,
-    raw_pitch: f64,
-    raw_roll: f64,
-    spatial: &SpatialState,
-    fps: f64,
-    node_id: &Option<String>,
-    latency_ms: f64,
-    packets: u64,
-    mode: SpeakerMode,
-    reverb_enabled: bool,
-    width: f64,
-) {
-    clear_screen();
-
-    let draw_row = |content: &str| {
-        let inner_target = 66;
-        let visible = get_visible_width(content);
-        let padding = if inner_target > visible { inner_target - visible } else { 0 };
-        print!("\x1B[1;96m║\x1B[0m{}{}\x1B[1;96m║\x1B[0m\r\n", content, " ".repeat(padding));
-    };
-
-    let pad_field = |text: String, width: usize| -> String {
-        let vis = get_visible_width(&text);
-        let p = if width > vis { width - vis } else { 0 };
-        format!("{}{}", text, " ".repeat(p))
-    };
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
 
-    print!("\x1B[1;96m╔══════════════════════════════════════════════════════════════════╗\x1B[0m\r\n");
+// engine with the mapping settings from the config applied
+fn configured_engine(config: &Config) -> Engine {
+    let mut engine = Engine::new();
+    engine.yaw = config.yaw;
+    engine.pitch = config.pitch;
+    engine.roll = config.roll;
+    engine.pitch_target = config.pitch_target;
+    engine.rear_attenuation_db = config.rear_attenuation_db;
+    engine.rear_rolloff_db = config.rear_rolloff_db;
+    engine.lean_width = config.lean_width;
+    engine.smoothed.kind = config.filter;
+    engine.smoothed.axis_factors = config.smoothing;
+    engine.listening = config.listening.unwrap_or(Listening::Headphones);
+    engine.crosstalk_cancel = config.crosstalk_cancel;
+    engine
+}
 
-    let title = "\x1B[1;37m🎧 SPATIAL AUDIO ENGINE (HRTF STEREO)\x1B[0m";
-    let t_vis = get_visible_width(title);
-    let t_pad = (66 - t_vis) / 2;
-    print!("\x1B[1;96m║\x1B[0m{}{}{}\x1B[1;96m║\x1B[0m\r\n", " ".repeat(t_pad), title, " ".repeat(66 - t_vis - t_pad));
-    print!("\x1B[1;96m╠══════════════════════════════════════════════════════════════════╣\x1B[0m\r\n");
+// output smoothing and volume guard as configured, one per pipeline
+fn output_stage(config: &Config) -> OutputStage {
+    let smoother = OutputSmoother::new(config.output_smoothing);
+    OutputStage::new(smoother, VolumeGuard::new(config.max_volume, config.max_volume_step_db))
+}
 
-    draw_row(&format!("  {}", "\x1B[1;33m🧭 HEAD TRACKING\x1B[0m"));
-    draw_row("");
-    draw_row(&format!("    \x1B[90mRAW:\x1B[0m     Yaw={:>7.1}°  Pitch={:>7.1}°  Roll={:>7.1}°",
-                      raw_yaw, raw_pitch, raw_roll));
-    draw_row(&format!("    \x1B[1;37mSMOOTH:\x1B[0m  Yaw={:>7.1}°  Pitch={:>7.1}°  Roll={:>7.1}°",
-                      smoothed.yaw, smoothed.pitch, smoothed.roll));
+fn build_sinks(config: &Config) -> Result<Sinks, String> {
+    let frame_rate = Duration::from_millis(UPDATE_RATE_MS);
+    let mut sinks = Sinks::new();
 
-    draw_row("");
-    print!("\x1B[1;96m╠══════════════════════════════════════════════════════════════════╣\x1B[0m\r\n");
+    if config.pipewire {
+        let pipewire = PipewireSink::new(SPATIALIZER_NODE_NAME, config.volume_control.clone());
+        sinks.add(Box::new(pipewire), config.sink_interval("pipewire", frame_rate));
+    }
+    // the same audio on several room devices, panned by head yaw
+    if !config.room_zones.is_empty() {
+        let room = RoomSink::new(&config.room_zones, SPATIALIZER_NODE_NAME)?;
+        sinks.add(Box::new(room), config.sink_interval("room", frame_rate));
+    }
+    // forward packets to downstream opentrack listeners
+    if !config.relay_targets.is_empty() {
+        let relay = Relay::new(&config.relay_targets, config.relay_smoothed)?;
+        sinks.add(Box::new(relay), config.sink_interval("relay", Duration::ZERO));
+    }
+    // head-following lights
+    if let Some(ref target) = config.artnet_target {
+        let artnet = ArtNetOutput::new(target, config.artnet_universe, config.artnet_channel)?;
+        sinks.add(Box::new(artnet), config.sink_interval("artnet", frame_rate));
+    }
+    // head look for sims through a virtual joystick
+    if config.joystick {
+        let joystick = VirtualJoystick::new(config.joystick_axes)?;
+        sinks.add(Box::new(joystick), config.sink_interval("joystick", frame_rate));
+    }
+    // zeromq state stream
+    if let Some(ref endpoint) = config.zmq_endpoint {
+        sinks.add(Box::new(ZmqPublisher::bind(endpoint)?), config.sink_interval("zmq", frame_rate));
+    }
+    // shared memory / udp record for game mods
+    if config.game_shm.is_some() || config.game_udp.is_some() {
+        let game = GameExport::new(config.game_shm.as_deref(), config.game_udp.as_deref())?;
+        sinks.add(Box::new(game), config.sink_interval("game", Duration::ZERO));
+    }
+    // rerun timeline for offline filter tuning
+    if config.rerun || config.rerun_save.is_some() {
+        let rerun = RerunSink::spawn(config.rerun_save.as_deref())?;
+        sinks.add(Box::new(rerun), config.sink_interval("rerun", Duration::ZERO));
+    }
+    Ok(sinks)
+}
 
-    let mode_color = match mode {
-        SpeakerMode::Front => "\x1B[1;32m",
-        SpeakerMode::Back => "\x1B[1;33m",
+fn run_main_loop(config: &Config) -> Result<(), String> {
+    // the startup box, left out with --plain-status
+    let startup_row = |content: &str| {
+        if !config.plain_status {
+            print!("{}\r\n", box_row(config.theme, content));
+        }
     };
-    draw_row(&format!("  \x1B[1;35m🔊 VIRTUAL SPEAKERS\x1B[0m  [{}{}°\x1B[0m]", mode_color, mode.label()));
-    draw_row("");
-
-    let adjust_display_azimuth = |a: f64| -> f64 {
-        let mut x = a;
-        // normalize to -180..180
-        while x <= -180.0 { x += 360.0; }
-        while x > 180.0 { x -= 360.0; }
-        // so it doesnt clamp to the end
-        if x > 90.0 {
-            x -= 180.0;
-        } else if x < -90.0 {
-            x += 180.0;
+    let startup_line = |line: &str| {
+        if !config.plain_status {
+            print!("{}\r\n", box_line(config.theme, line));
         }
-        x
     };
+    if !config.plain_status {
+        clear_screen();
+    }
+    startup_line("\x1B[1;96m╔══════════════════════════════════════════════════════════════════╗\x1B[0m");
+    let title = "\x1B[1;37m🎧 SPATIAL AUDIO ENGINE\x1B[0m";
+    let t_pad = (BOX_WIDTH - get_visible_width(&config.theme.paint(title))) / 2;
+    startup_row(&format!("{}{}", " ".repeat(t_pad), title));
+    startup_line("\x1B[1;96m╠══════════════════════════════════════════════════════════════════╣\x1B[0m");
+    startup_row("");
+    startup_row(&format!("  🔌 Opening {}...", config.input));
+    for extra in &config.extra_inputs {
+        startup_row(&format!("     and {}", extra));
+    }
+    for fallback in &config.fallback_inputs {
+        startup_row(&format!("     then {}", fallback));
+    }
+    stdout().flush().ok();
 
-    let left_display = adjust_display_azimuth(spatial.right_az);
-    let right_display = adjust_display_azimuth(spatial.left_az);
-
-    let l_bar = render_azimuth_bar(left_display, 24);
-    draw_row(&format!("    \x1B[1;34mLeft Speaker:\x1B[0m  {}  {:>+6.1}°", l_bar, left_display));
-
-    let r_bar = render_azimuth_bar(right_display, 24);
-    draw_row(&format!("    \x1B[1;35mRight Speaker:\x1B[0m {}  {:>+6.1}°", r_bar, right_display));
-
-    draw_row("");
-
-    let elev_indicator = render_elevation_indicator(spatial.elevation);
-    draw_row(&format!("    \x1B[1;37mElevation:\x1B[0m {:>+6.1}°  {}", spatial.elevation, elev_indicator));
-
-    let gain_pct = spatial.gain * 100.0;
-    draw_row(&format!("    \x1B[1;37mRadius:\x1B[0m    {:>6.2}m  (Gain: {:>3.0}%)", spatial.radius, gain_pct));
-
-    let reverb_pct = spatial.reverb_gain * 100.0;
-    let reverb_status = if reverb_enabled { "\x1B[1;32mON\x1B[0m" } else { "\x1B[1;31mOFF\x1B[0m" };
-    draw_row(&format!("    \x1B[1;37mReverb:\x1B[0m   {:>6.1}%  [{}]", reverb_pct, reverb_status));
-
-    draw_row("");
-    print!("\x1B[1;96m╠══════════════════════════════════════════════════════════════════╣\x1B[0m\r\n");
-
-    draw_row(&format!("  {}", "\x1B[1;33m📐 STEREO FIELD\x1B[0m"));
-    draw_row("");
-
-    let width_pct = width * 100.0;
-    let width_desc = if width >= 1.2 {
-        "\x1B[1;36mVery Wide\x1B[0m"
-    } else if width >= 0.8 {
-        "\x1B[1;37mNormal\x1B[0m"
+    let main_input: Box<dyn InputSource> = if config.extra_inputs.is_empty() {
+        input::open(&config.input)?
     } else {
-        "\x1B[1;33mNarrow\x1B[0m"
+        let uris: Vec<String> = std::iter::once(&config.input).chain(&config.extra_inputs).cloned().collect();
+        Box::new(MultiInput::open(&uris)?)
     };
-    draw_row(&format!("    \x1B[1;37mWidth:\x1B[0m    {:>6.0}%  ({})", width_pct, width_desc));
-
-    let sep_angle = (spatial.left_az - spatial.right_az).abs();
-    draw_row(&format!("    \x1B[1;37mSeparation:\x1B[0m {:>5.1}°  (speaker spread)", sep_angle));
-
-    draw_row("");
-    print!("\x1B[1;96m╠══════════════════════════════════════════════════════════════════╣\x1B[0m\r\n");
-
-    draw_row(&format!("  {}", "\x1B[1;32m📡 CONNECTION\x1B[0m"));
-    draw_row("");
-
-    let status = match node_id {
-        Some(id) => format!("\x1B[1;32m✓ LINKED\x1B[0m to Node \x1B[1;37m{}\x1B[0m ({})", id, SPATIALIZER_NODE_NAME),
-        None => format!("\x1B[1;31m✗ SEARCHING\x1B[0m for '{}'...", SPATIALIZER_NODE_NAME),
+    let mut input: Box<dyn InputSource> = if config.fallback_inputs.is_empty() {
+        main_input
+    } else {
+        let fallbacks = config.fallback_inputs.iter().map(|uri| input::open(uri));
+        let inputs = std::iter::once(Ok(main_input)).chain(fallbacks).collect::<Result<Vec<_>, String>>()?;
+        Box::new(FailoverInput::new(inputs, config.failover_timeout)?)
     };
-    draw_row(&format!("    {}", status));
-
-    draw_row("");
-    print!("\x1B[1;96m╠══════════════════════════════════════════════════════════════════╣\x1B[0m\r\n");
-
-    draw_row(&format!("  {}", "\x1B[1;34m📈 STATS\x1B[0m"));
-    draw_row("");
-
-    let col_width = 25;
-
-    let fps_str = pad_field(format!("FPS: \x1B[1;37m{:>5.1}\x1B[0m", fps), col_width);
-    let lat_str = format!("Latency: \x1B[1;37m{:>5.2}ms\x1B[0m", latency_ms);
-    draw_row(&format!("    {}  │  {}", fps_str, lat_str));
-
-    let pkts_str = pad_field(format!("Packets: \x1B[1;37m{}\x1B[0m", packets), col_width);
-    let thresh_str = format!("Threshold: \x1B[1;37m{:.1}°\x1B[0m", CHANGE_THRESHOLD);
-    draw_row(&format!("    {}  │  {}", pkts_str, thresh_str));
-
-    let smooth_str = pad_field(format!("Smoothing: \x1B[1;37m{:.0}%\x1B[0m", SMOOTHING_FACTOR * 100.0), col_width);
-    draw_row(&format!("    {}  │", smooth_str));
-
-    draw_row("");
-    print!("\x1B[1;96m╠══════════════════════════════════════════════════════════════════╣\x1B[0m\r\n");
-
-    draw_row(&format!("  {}", "\x1B[1;90m⌨ CONTROLS\x1B[0m"));
-    draw_row("    \x1B[90m↑/↓\x1B[0m Radius   \x1B[90m←/→\x1B[0m Width   \x1B[90mW\x1B[0m Front   \x1B[90mS\x1B[0m Back");
-    draw_row("    \x1B[90mR\x1B[0m Reverb   \x1B[90mQ/Esc\x1B[0m Quit");
-    print!("\x1B[1;96m╚══════════════════════════════════════════════════════════════════╝\x1B[0m\r\n");
-}
-
-// ==============================================================================
-// PIPEWIRE CONTROL
-// ==============================================================================
-
-fn find_spatializer_node() -> Option<String> {
-        // run 'pw-cli ls Node'
-    let output = Command::new("pw-cli").args(["ls", "Node"]).output().ok()?;
-    let text = String::from_utf8_lossy(&output.stdout);
-
-    let mut current_id = String::new();
-
-    // simple state machine parser (no external deps)
-    for line in text.lines() {
-        let trim = line.trim();
-        if trim.starts_with("id") {
-            // "id 36, type PipeWire:Interface:Node..."
-            if let Some(id_part) = trim.split_whitespace().nth(1) {
-                current_id = id_part.trim_matches(',').to_string();
-            }
+    // last failover between the inputs, for the dashboard
+    let mut input_event: Option<(String, Instant)> = None;
+    let mut input_switched = false;
+    // what the dashboard shows about the inputs and when it was asked for:
+    // refreshed once a second and on a switch, not described anew every frame
+    let several_inputs = !config.fallback_inputs.is_empty() || !config.extra_inputs.is_empty();
+    let mut input_status: Option<(String, Instant)> = None;
+    startup_row("  \x1B[1;32m✓ Input ready!\x1B[0m");
+
+    // managed mode: our own filter-chain, gone again when we exit
+    let mut filter_chain = match config.filter_chain {
+        FilterChainMode::Managed if config.pipewire => {
+            let chain = FilterChain::load(SPATIALIZER_NODE_NAME)?;
+            let loaded = match chain.status {
+                ChainStatus::Reused => "\x1B[1;32m✓ Filter-chain already running, reusing it\x1B[0m",
+                _ => "\x1B[1;32m✓ Filter-chain loaded!\x1B[0m",
+            };
+            startup_row(&format!("  {}", loaded));
+            Some(chain)
         }
-        // check for our target node name
-        if trim.contains("node.name") && trim.contains(SPATIALIZER_NODE_NAME) {
-            return Some(current_id);
-        }
-    }
-    None
-}
-
-fn update_pipewire(id: &str, spatial: &SpatialState) {
-    // build the json for the stereo filter-chain
-    // sets params for both 'spat_left' and 'spat_right' nodes
-    // uses dynamic radius and includes gain for reverb simulation
-    let dry_gain = 1.0 - spatial.reverb_gain;
-    let json_payload = format!(
-        "{{ \"params\": [ \
-            \"spat_left:Azimuth\", {:.2}, \
-            \"spat_left:Elevation\", {:.2}, \
-            \"spat_left:Radius\", {:.2}, \
-            \"spat_left:Gain\", {:.2}, \
-            \"spat_right:Azimuth\", {:.2}, \
-            \"spat_right:Elevation\", {:.2}, \
-            \"spat_right:Radius\", {:.2}, \
-            \"spat_right:Gain\", {:.2}, \
-            \"final_mix_l:Gain 1\", {:.2}, \
-            \"final_mix_l:Gain 2\", {:.2}, \
-            \"final_mix_r:Gain 1\", {:.2}, \
-            \"final_mix_r:Gain 2\", {:.2} \
-        ] }}",
-        spatial.left_az, spatial.elevation, spatial.radius, spatial.gain,
-        spatial.right_az, spatial.elevation, spatial.radius, spatial.gain,
-        dry_gain, spatial.reverb_gain,
-        dry_gain, spatial.reverb_gain
-    );
-
-    // spawn async (fire and forget) to prevent frame drops
-    // redirect stdout/stderr to null to prevent tui artifacts
-    Command::new("pw-cli")
-        .args(["set-param", id, "Props", &json_payload])
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()
-        .ok();
-}
-
-// ==============================================================================
-// MAIN
-// ==============================================================================
-
-fn main() {
-    // enable raw mode for keyboard input
-    terminal::enable_raw_mode().expect("Failed to enable raw mode");
-    stdout().execute(EnterAlternateScreen).expect("Failed to enter alternate screen");
-
-    // make sure we cleanup on exit
-    let result = run_main_loop();
+        _ => None,
+    };
 
-    // cleanup terminal
-    terminal::disable_raw_mode().ok();
-    stdout().execute(LeaveAlternateScreen).ok();
+    // what the spatializer outputs, recorded until we exit
+    let mut recorder = match config.record {
+        Some(ref path) => {
+            let recorder = SessionRecorder::start(SPATIALIZER_OUTPUT_NAME, path)?;
+            startup_row(&format!("  ⏺ Recording to {}", path.display()));
+            Some(recorder)
+        }
+        None => None,
+    };
 
-    if let Err(e) = result {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
+    // demo: play the test sound into the spatializer for as long as we run
+    let _demo_sound = if config.demo { Some(DemoSound::spawn(SPATIALIZER_NODE_NAME)?) } else { None };
+
+    startup_row("");
+    startup_row(&format!("  🔍 Searching for '{}'...", SPATIALIZER_NODE_NAME));
+    startup_row("  \x1B[1;33m⏳ Waiting for tracker data...\x1B[0m");
+    startup_row(&format!("     Listening on {}", input.describe()));
+    startup_row("");
+    startup_line("\x1B[1;96m╚══════════════════════════════════════════════════════════════════╝\x1B[0m");
+    if config.plain_status {
+        print!("spatial-track: listening on {}, q quits, space recenters\r\n", input.describe());
     }
-}
-
-fn run_main_loop() -> Result<(), String> {
-    clear_screen();
-    print!("\x1B[1;96m╔══════════════════════════════════════════════════════════════════╗\x1B[0m\r\n");
-    print!("\x1B[1;96m║\x1B[0m{:^66}\x1B[1;96m║\x1B[0m\r\n", "\x1B[1;37m🎧 SPATIAL AUDIO ENGINE\x1B[0m");
-    print!("\x1B[1;96m╠══════════════════════════════════════════════════════════════════╣\x1B[0m\r\n");
-    print!("\x1B[1;96m║\x1B[0m{:66}\x1B[1;96m║\x1B[0m\r\n", "");
-    print!("\x1B[1;96m║\x1B[0m  {:<64}\x1B[1;96m║\x1B[0m\r\n", "🔌 Binding to UDP port 4242...");
     stdout().flush().ok();
 
-    let socket = match UdpSocket::bind("127.0.0.1:4242") {
-        Ok(s) => {
-            print!("\x1B[1;96m║\x1B[0m  {:<64}\x1B[1;96m║\x1B[0m\r\n", "\x1B[1;32m✓ Socket bound successfully!\x1B[0m");
-            s
+    let mut engine = configured_engine(config);
+
+    // center, calibration and listening mode from the last run with this
+    // profile and input
+    let profile = Profile::new(&config.profile, input.uri());
+    if config.reset_profile {
+        profile.reset().map_err(|e| format!("Failed to reset profile '{}': {}", config.profile, e))?;
+    }
+    if let Some(center) = profile.center() {
+        engine.center = center;
+    }
+    // an explicit --listening becomes the profile's new choice
+    match config.listening {
+        Some(listening) => {
+            profile.save_listening(listening).ok();
         }
-        Err(e) => {
-            return Err(format!("Failed to bind socket: {}", e));
+        None => {
+            if let Some(listening) = profile.listening() {
+                engine.listening = listening;
+            }
         }
-    };
-
-    socket.set_read_timeout(Some(Duration::from_millis(10))).ok();
-
-    print!("\x1B[1;96m║\x1B[0m{:66}\x1B[1;96m║\x1B[0m\r\n", "");
-    print!("\x1B[1;96m║\x1B[0m  {:<64}\x1B[1;96m║\x1B[0m\r\n",
-             format!("🔍 Searching for '{}'...", SPATIALIZER_NODE_NAME));
-    print!("\x1B[1;96m║\x1B[0m  {:<64}\x1B[1;96m║\x1B[0m\r\n", "\x1B[1;33m⏳ Waiting for OpenTrack data...\x1B[0m");
-    print!("\x1B[1;96m║\x1B[0m     {:<61}\x1B[1;96m║\x1B[0m\r\n", "Make sure OpenTrack is sending UDP to 127.0.0.1:4242");
-    print!("\x1B[1;96m║\x1B[0m{:66}\x1B[1;96m║\x1B[0m\r\n", "");
-    print!("\x1B[1;96m╚══════════════════════════════════════════════════════════════════╝\x1B[0m\r\n");
-    stdout().flush().ok();
+    }
+    let saved_calibration = profile.calibration();
+    if let Some(c) = saved_calibration {
+        apply_calibration(&mut engine, c);
+    }
+    if let Some(factors) = profile.smoothing() {
+        engine.smoothed.axis_factors = factors.map(Some);
+    }
 
-    let mut buf = [0u8; 48];
-    let mut smoothed = SmoothedState::new();
+    // extra listeners, each with its own engine and filter-chain node
+    let mut listeners = Vec::with_capacity(config.listeners.len());
+    for spec in &config.listeners {
+        let glide = Glide::new(config.center_curve, config.center_glide);
+        listeners.push(Listener::bind(spec, configured_engine(config), glide, output_stage(config))?);
+    }
 
     // state tracking
-    let mut cached_node_id: Option<String> = None;
-    let mut last_node_search = Instant::now();
     let mut last_update_time = Instant::now();
 
     // fps calculation
@@ -488,136 +333,536 @@ fn run_main_loop() -> Result<(), String> {
 
     // packet counter
     let mut packet_count: u64 = 0;
+    let mut last_packet: Option<Instant> = None;
+
+    // tuning: every filter kind on the same input, F switches which one drives the audio
+    let mut comparison = config.compare_filters.then(|| FilterComparison::new(engine.smoothed.factor));
+    if let Some(ref mut comparison) = comparison {
+        comparison.set_axis_factors(engine.smoothed.axis_factors);
+    }
 
-    // don't spam pipewire if head hasn't moved
-    let mut last_sent_yaw: f64 = f64::MAX;
-    let mut last_sent_pitch: f64 = f64::MAX;
-    let mut last_sent_radius: f64 = f64::MAX;
+    // head angular velocity for the dashboard and sinks
+    let mut motion = MotionTracker::new();
+
+    // eases recenter and tracking-loss jumps instead of snapping
+    let mut glide = Glide::new(config.center_curve, config.center_glide);
+    // output smoothing and volume guard after the engine
+    let mut stage = output_stage(config);
+    // slow gain towards --loudness-target, measured on the spatializer input
+    let mut leveler = match config.loudness_target {
+        Some(target) if config.pipewire => Some(Leveler::new(SPATIALIZER_NODE_NAME, target)),
+        _ => None,
+    };
 
-    // latency tracking
-    let mut latency_samples: Vec<f64> = Vec::with_capacity(30);
-    let mut avg_latency_ms: f64 = 0.0;
+    // last minute of packet and update rates for the stats graphs
+    let mut packet_rate = RateHistory::new(Instant::now());
+    let mut update_rate = RateHistory::new(Instant::now());
 
     // raw values for display (set on first packet)
     let (mut raw_yaw, mut raw_pitch, mut raw_roll): (f64, f64, f64);
 
-    // dynamic state: radius, speaker mode, and width
-    let mut current_radius: f64 = DEFAULT_RADIUS;
-    let mut speaker_mode: SpeakerMode = SpeakerMode::Front;
-    let mut reverb_enabled: bool = false; // off by default
-    let mut current_width: f64 = DEFAULT_WIDTH;
+    // dashboard look, T switches
+    let mut theme = config.theme;
+    // the rendered dashboard, its buffers reused from update to update
+    let mut screen = Screen::new(theme);
+    let mut screen_text = String::new();
+    // --plain-status: text lines instead of the dashboard
+    let mut plain_status = config.plain_status.then(PlainStatus::new);
+    let mut last_spatial: Option<SpatialState> = None;
 
     // flag to force update when user changes settings
     let mut force_update = false;
 
+    // sensitivity calibration, starts with the first packet unless disabled
+    let mut calibrator = Calibrator::new();
+    // a stored calibration is reused, C still starts a new one
+    let mut calibration_pending = config.calibration != CalibrationMode::Off && saved_calibration.is_none();
+
+    // smoothing analysis, N starts it and Y applies the proposal
+    let mut tuner = SmoothingTuner::new();
+    let mut tune_pending = config.tune_smoothing;
+
+    // accessibility mode: head gestures as a media controller
+    let mut media = match config.media_control {
+        MediaControlMode::Off => None,
+        _ => Some(MediaController::new(Instant::now())),
+    };
+
+    // everything that consumes the computed state, each at its own rate
+    let mut sinks = build_sinks(config)?;
+
+    // gyro bias compensation for drifting imu trackers
+    let mut drift = match config.drift_correction {
+        DriftCorrection::Off => None,
+        mode => Some(DriftCompensator::new(input.active_uri(), mode == DriftCorrection::Auto)),
+    };
+
+    // bring the tracker up once the port is ours
+    let mut tracker = config.tracker_command.as_deref().map(TrackerSupervisor::start);
+
+    // head sectors -> output sinks
+    let mut output = if config.output_zones.is_empty() {
+        None
+    } else {
+        Some(OutputSwitcher::new(config.output_zones.clone()))
+    };
+
+    // follow the default output device, spatialization pauses on hdmi
+    let mut default_sink = config.follow_default_sink.then(|| DefaultSinkWatcher::new(config.sink_classes.clone()));
+    let mut spatial_paused = false;
+
+    // screen lock / idle bypasses the panner
+    let mut session =
+        (config.pause_on_lock || config.pause_on_idle).then(|| SessionWatcher::new(config.pause_on_lock, config.pause_on_idle));
+    let mut session_paused = false;
+    let mut last_neutral: Option<Instant> = None;
+
+    // optional virtual mouse driven by head yaw/pitch
+    let mut mouse_paused = false;
+    let (mut mouse_look, mouse_error) = if config.mouse_look {
+        match MouseLook::new(config.mouse_gain) {
+            Ok(m) => (Some(m), None),
+            Err(e) => (None, Some(e.to_string())),
+        }
+    } else {
+        (None, None)
+    };
+    // the dashboard's mouse line, written into the same buffer every frame
+    let mut mouse_line = String::new();
+
     loop {
         // 1. handle keyboard input (non-blocking)
         if event::poll(Duration::from_secs(0)).unwrap_or(false) {
             if let Ok(Event::Key(key_event)) = event::read() {
-                match handle_key_event(key_event, &mut current_radius, &mut speaker_mode, &mut reverb_enabled, &mut current_width) {
+                match handle_key_event(key_event, &mut engine) {
                     KeyAction::Quit => break,
                     KeyAction::Changed => {
+                        if let Some(ref mut status) = plain_status {
+                            status.announce(&format!(
+                                "{} speakers, radius {:.1} m, width {:.0}%, reverb {}",
+                                engine.mode.label().to_lowercase(),
+                                engine.radius,
+                                engine.width * 100.0,
+                                if engine.reverb_enabled { "on" } else { "off" }
+                            ));
+                        }
                         force_update = true;
                     }
+                    KeyAction::Calibrate => {
+                        calibrator.start(Instant::now());
+                        calibration_pending = false;
+                    }
+                    KeyAction::ApplyCalibration => {
+                        if let Some(c) = calibrator.take_proposal() {
+                            apply_calibration(&mut engine, c);
+                            profile.save_calibration(c).ok();
+                            sinks.event("calibration_applied", "");
+                            if let Some(ref mut status) = plain_status {
+                                status.announce(&format!(
+                                    "calibration applied, sensitivity yaw {:.2} pitch {:.2}",
+                                    c.yaw_sensitivity, c.pitch_sensitivity
+                                ));
+                            }
+                            force_update = true;
+                        }
+                    }
+                    KeyAction::TuneSmoothing => {
+                        tuner.start(Instant::now());
+                        tune_pending = false;
+                    }
+                    KeyAction::ApplySmoothing => {
+                        if let Some(p) = tuner.take_proposal() {
+                            apply_smoothing(&mut engine, comparison.as_mut(), p);
+                            profile.save_smoothing(p.factors()).ok();
+                            sinks.event("smoothing_applied", "");
+                            if let Some(ref mut status) = plain_status {
+                                let [y, p, r] = p.factors();
+                                status.announce(&format!("smoothing applied, yaw {:.2} pitch {:.2} roll {:.2}", y, p, r));
+                            }
+                            force_update = true;
+                        }
+                    }
+                    KeyAction::Recenter => {
+                        let shift = engine.recenter();
+                        if let Some(ref mut comparison) = comparison {
+                            comparison.shift(shift);
+                        }
+                        glide.start();
+                        profile.save_center(engine.center).ok();
+                        sinks.event("recenter", "");
+                        if let Some(ref mut m) = mouse_look {
+                            m.reset();
+                        }
+                        if let Some(ref mut status) = plain_status {
+                            status.announce("recentered");
+                        }
+                        force_update = true;
+                    }
+                    KeyAction::CalibrateBias => {
+                        if let Some(ref mut drift) = drift {
+                            drift.start_calibration(Instant::now());
+                        }
+                    }
+                    KeyAction::CycleFilter => {
+                        // take over the compared filter's state so it drives the audio seamlessly
+                        let next = engine.smoothed.kind.next();
+                        match comparison.as_ref().and_then(|c| c.get(next)) {
+                            Some(filter) => engine.smoothed = filter.clone(),
+                            None => engine.smoothed.set_kind(next),
+                        }
+                        sinks.event("filter", next.label());
+                        if let Some(ref mut status) = plain_status {
+                            status.announce(&format!("filter {}", next.label()));
+                        }
+                        force_update = true;
+                    }
+                    KeyAction::CycleTheme => {
+                        theme = theme.next();
+                        force_update = true;
+                    }
+                    KeyAction::ToggleMouse => {
+                        mouse_paused = !mouse_paused;
+                        if let Some(ref mut m) = mouse_look {
+                            m.reset();
+                        }
+                    }
                     KeyAction::None => {}
                 }
             }
         }
 
-        // 2. periodically search for node id if not found
-        if cached_node_id.is_none() && last_node_search.elapsed().as_secs() > 2 {
-            cached_node_id = find_spatializer_node();
-            last_node_search = Instant::now();
+        sinks.poll(Instant::now());
+
+        if let Some(ref mut chain) = filter_chain {
+            chain.poll(Instant::now());
         }
 
-        // 3. read udp packet
-        match socket.recv_from(&mut buf) {
-            Ok((48, _)) => {
-                packet_count += 1;
+        if let Some(ref mut recorder) = recorder {
+            recorder.poll(Instant::now());
+        }
 
-                // parse opentrack data: [x, y, z, yaw, pitch, roll] as f64
-                let data: [f64; 6] = unsafe { std::mem::transmute(buf) };
-                raw_yaw = data[3];
-                raw_pitch = data[4];
-                raw_roll = data[5];
+        if let Some(ref mut leveler) = leveler {
+            leveler.poll(Instant::now());
+        }
 
-                // apply smoothing
-                smoothed.update(raw_yaw, raw_pitch, raw_roll);
+        if let Some(ref mut tracker) = tracker {
+            tracker.poll(Instant::now());
+        }
 
-                // 4. rate limit updates
-                if last_update_time.elapsed() < Duration::from_millis(UPDATE_RATE_MS) && !force_update {
-                    continue;
+        // the default output device changed to another kind of device
+        if let Some(ref mut watcher) = default_sink {
+            if let Some(class) = watcher.poll(Instant::now()) {
+                match class {
+                    DeviceClass::Headphones => engine.listening = Listening::Headphones,
+                    DeviceClass::Speakers => engine.listening = Listening::Speakers,
+                    DeviceClass::Hdmi => {}
                 }
+                spatial_paused = class == DeviceClass::Hdmi;
+                sinks.event("default_sink", class.label());
+                force_update = true;
+            }
+        }
 
-                // calculate spatial positions with current radius, mode, and width
-                let spatial = SpatialState::from_head_tracking(
-                    smoothed.yaw,
-                    smoothed.pitch,
-                    current_radius,
-                    speaker_mode,
-                    reverb_enabled,
-                    current_width,
-                );
+        // the screen locked or unlocked
+        if let Some(ref mut watcher) = session {
+            if let Some(paused) = watcher.poll() {
+                session_paused = paused;
+                sinks.event("session", watcher.label());
+                force_update = true;
+                if let Some(ref mut status) = plain_status {
+                    let text = if paused { format!("session {}, speakers held in front", watcher.label()) } else { "session active, tracking".to_string() };
+                    status.announce(&text);
+                }
+            }
+        }
+
+        // instructions and results of the smoothing analysis
+        if let Some(text) = tuner.take_announcement() {
+            if let Some(ref mut status) = plain_status {
+                status.announce(&text);
+            }
+        }
+
+        // the failover input switched to another source
+        if let Some(event) = input.take_event() {
+            sinks.event("input", &event);
+            if let Some(ref mut status) = plain_status {
+                status.announce(&event);
+            }
+            input_event = Some((event, Instant::now()));
+            input_switched = true;
+            input_status = None;
+            // the bias (and the fit so far) belongs to the device we switched away from
+            if let Some(ref mut drift) = drift {
+                *drift = DriftCompensator::new(input.active_uri(), drift.auto);
+            }
+            force_update = true;
+        }
+
+        // nobody at the desk: the tracker may well have stopped sending, so the
+        // neutral speakers are sent from here instead of waiting for poses
+        let update_due = |t: Option<Instant>| t.is_none_or(|t| t.elapsed() >= Duration::from_millis(UPDATE_RATE_MS));
+        if session_paused && update_due(last_packet) && update_due(last_neutral) {
+            let now = Instant::now();
+            let gain = leveler.as_ref().map_or(1.0, |l| l.gain());
+            let (spatial, limited) = stage.neutral(&engine, gain, now);
+            if let Some(reason) = limited {
+                sinks.event("volume_limited", reason);
+            }
+            let pose = Pose { translation: [0.0; 3], yaw: 0.0, pitch: 0.0, roll: 0.0, time: now };
+            sinks.send(&Frame {
+                pose: &pose,
+                smoothed: [0.0; 3],
+                motion: motion.motion,
+                spatial: &spatial,
+                force: force_update,
+                latency_ms: sinks.latency_ms().unwrap_or(0.0),
+            });
+            last_spatial = Some(spatial);
+            last_neutral = Some(now);
+        }
+
+        // extra listeners: their own engine, the same output stage and the
+        // pauses that concern every output (exclusive media control doesn't)
+        for l in listeners.iter_mut() {
+            l.poll(Pauses { session: session_paused, center: spatial_paused });
+        }
+
+        if let Some(ref mut status) = plain_status {
+            let now = Instant::now();
+            let tracking = match last_packet {
+                None => Tracking::Waiting,
+                Some(t) if now.duration_since(t) > input::TRACKING_LOSS_TIMEOUT => Tracking::Lost,
+                Some(_) => Tracking::Active,
+            };
+            // the notes only get put together when a line may go out
+            if status.due(now, tracking) {
+                let mut notes: Vec<String> = sinks.iter().filter(|s| s.error().is_some()).map(|s| format!("{} error", s.name())).collect();
+                if spatial_paused {
+                    notes.push("paused on hdmi".to_string());
+                }
+                if session_paused {
+                    notes.push(format!("paused, session {}", session.as_ref().map_or("locked", |w| w.label())));
+                }
+                if calibrator.is_running() {
+                    notes.push("calibrating".to_string());
+                }
+                if tuner.phase().is_some() {
+                    notes.push("analyzing smoothing".to_string());
+                }
+                if stage.guard.last_limit.is_some_and(|(_, at)| now.duration_since(at) < STATUS_INTERVAL) {
+                    notes.push("volume limited".to_string());
+                }
+                status.report(now, tracking, last_spatial.as_ref(), &notes);
+            }
+        }
+
+        // 2. read the next pose
+        match input.recv(Duration::from_millis(10)) {
+            Ok(Some(pose)) => {
+                packet_count += 1;
+                packet_rate.record(pose.time);
+
+                raw_yaw = pose.yaw;
+                raw_pitch = pose.pitch;
+                raw_roll = pose.roll;
+                let now = pose.time;
+
+                // tracking came back after a gap or from another source: restart
+                // the filter at the new pose
+                let since_last = last_packet.map_or(0.0, |t| now.duration_since(t).as_secs_f64());
+                let switched = std::mem::take(&mut input_switched);
+                if pose.resumes_after(last_packet) || switched {
+                    engine.reset();
+                    if let Some(ref mut comparison) = comparison {
+                        comparison.reset();
+                    }
+                    glide.start();
+                    motion.reset();
+                    if let Some(ref mut m) = mouse_look {
+                        m.reset();
+                    }
+                }
+                last_packet = Some(now);
+
+                // remove accumulated gyro drift before anything else sees the pose
+                let [yaw, pitch, roll] = match drift {
+                    Some(ref mut drift) => drift.apply([raw_yaw, raw_pitch, raw_roll], now),
+                    None => [raw_yaw, raw_pitch, raw_roll],
+                };
+                motion.update([yaw, pitch, roll], now);
+
+                // record motion range for calibration
+                if calibration_pending {
+                    calibrator.start(now);
+                    calibration_pending = false;
+                }
+                let current = Calibration {
+                    yaw_sensitivity: engine.yaw.sensitivity,
+                    pitch_sensitivity: engine.pitch.sensitivity,
+                };
+                if calibrator.observe(yaw, pitch, current, now).is_some()
+                    && config.calibration == CalibrationMode::Auto
+                {
+                    if let Some(c) = calibrator.take_proposal() {
+                        apply_calibration(&mut engine, c);
+                        profile.save_calibration(c).ok();
+                        sinks.event("calibration_applied", "auto");
+                        force_update = true;
+                    }
+                }
 
-                // 5. send to pipewire (only if changed enough to avoid spamming, or forced)
-                if let Some(ref id) = cached_node_id {
-                    let yaw_changed = (smoothed.yaw - last_sent_yaw).abs() > CHANGE_THRESHOLD;
-                    let pitch_changed = (smoothed.pitch - last_sent_pitch).abs() > CHANGE_THRESHOLD;
-                    let radius_changed = (current_radius - last_sent_radius).abs() > 0.01;
-
-                    if yaw_changed || pitch_changed || radius_changed || force_update {
-                        let start = Instant::now();
-                        update_pipewire(id, &spatial);
-                        let cmd_latency = start.elapsed().as_secs_f64() * 1000.0;
-
-                        // track latency samples for averaging
-                        latency_samples.push(cmd_latency);
-                        if latency_samples.len() > 30 {
-                            latency_samples.remove(0);
+                // apply smoothing (relative to the recentered pose)
+                engine.feed_pose(yaw, pitch, roll, pose.time);
+                engine.feed_lean(pose.translation[2]);
+                if let Some(ref mut comparison) = comparison {
+                    comparison.update(engine.input, pose.time);
+                }
+
+                // noise and lag for the smoothing analysis, on the recentered input
+                if tune_pending {
+                    tuner.start(now);
+                    tune_pending = false;
+                }
+                let (iy, ip, ir) = engine.input;
+                if tuner.observe([iy, ip, ir], engine.smoothed.factors(), now).is_some() {
+                    force_update = true;
+                }
+
+                // head gestures -> media actions (not rate limited so dwell timing stays accurate)
+                if let Some(ref mut media) = media {
+                    if let Some(action) = media.update(engine.smoothed.yaw, engine.smoothed.pitch, now) {
+                        media.execute(action);
+                        sinks.event("media_action", action.label());
+                    }
+                }
+
+                if let Some(ref mut output) = output {
+                    if let Some(sink) = output.update(engine.smoothed.yaw, now).map(str::to_string) {
+                        // rare, so the blocking lookup is fine here
+                        if let Some(id) = find_spatializer_node(SPATIALIZER_OUTPUT_NAME) {
+                            output.move_stream(&id);
                         }
-                        avg_latency_ms = latency_samples.iter().sum::<f64>() / latency_samples.len() as f64;
+                        sinks.event("output_switched", &sink);
+                    }
+                }
 
-                        last_sent_yaw = smoothed.yaw;
-                        last_sent_pitch = smoothed.pitch;
-                        last_sent_radius = current_radius;
+                if let Some(ref mut m) = mouse_look {
+                    if !mouse_paused {
+                        m.update(engine.smoothed.yaw, engine.smoothed.pitch).ok();
                     }
                 }
 
+                // glide over recenter/tracking-loss jumps
+                let s = &engine.smoothed;
+                let shown = glide.apply([s.yaw, s.pitch, s.roll], now);
+
+                // 3. calculate spatial positions with current radius, mode, and width
+                // (exclusive media control and a paused output keep the speakers centered,
+                // a locked session also restores the volume)
+                let pauses = Pauses {
+                    session: session_paused,
+                    center: config.media_control == MediaControlMode::Exclusive || spatial_paused,
+                };
+                let fade = output.as_ref().map_or(1.0, |o| o.fade(Instant::now()));
+                let gain = fade * leveler.as_ref().map_or(1.0, |l| l.gain());
+                let (spatial, limited) = stage.process(&engine, shown, pauses, gain, since_last, now);
+                if let Some(reason) = limited {
+                    sinks.event("volume_limited", reason);
+                }
+
+                // hand the state to every output sink
+                let latency_ms = sinks.latency_ms().unwrap_or(0.0);
+                sinks.send(&Frame {
+                    pose: &pose,
+                    smoothed: shown,
+                    motion: motion.motion,
+                    spatial: &spatial,
+                    force: force_update,
+                    latency_ms,
+                });
+                last_spatial = Some(spatial);
+
+                // 4. rate limit dashboard updates
+                if plain_status.is_some()
+                    || last_update_time.elapsed() < Duration::from_millis(UPDATE_RATE_MS) && !force_update
+                {
+                    continue;
+                }
+
                 force_update = false;
 
-                // 6. fps calculation
+                // 5. fps calculation
                 frame_count += 1;
+                update_rate.record(Instant::now());
+                packet_rate.advance(Instant::now());
                 if last_fps_calc.elapsed() >= Duration::from_secs(1) {
                     current_fps = frame_count as f64 / last_fps_calc.elapsed().as_secs_f64();
                     frame_count = 0;
                     last_fps_calc = Instant::now();
                 }
 
-                // 7. render dashboard
+                // 6. render dashboard
+                let mouse_status = match (&mouse_look, &mouse_error) {
+                    (Some(m), _) if !mouse_paused => {
+                        mouse_line.clear();
+                        write!(mouse_line, "\x1B[1;32mACTIVE\x1B[0m (gain {:.0}/°)", m.gain).ok();
+                        Some(mouse_line.as_str())
+                    }
+                    (Some(_), _) => Some("\x1B[1;33mPAUSED\x1B[0m (M to resume)"),
+                    (None, Some(e)) => {
+                        mouse_line.clear();
+                        write!(mouse_line, "\x1B[1;31m✗\x1B[0m {}", e).ok();
+                        Some(mouse_line.as_str())
+                    }
+                    (None, None) => None,
+                };
+                if several_inputs && input_status.as_ref().is_none_or(|(_, at)| at.elapsed() >= Duration::from_secs(1)) {
+                    input_status = Some((input.describe(), Instant::now()));
+                }
                 render_dashboard(
-                    &smoothed,
+                    &mut screen,
+                    &engine,
                     raw_yaw,
                     raw_pitch,
                     raw_roll,
                     &spatial,
+                    &motion.motion,
                     current_fps,
-                    &cached_node_id,
-                    avg_latency_ms,
+                    latency_ms,
                     packet_count,
-                    speaker_mode,
-                    reverb_enabled,
-                    current_width,
+                    &packet_rate,
+                    &update_rate,
+                    &calibrator,
+                    &tuner,
+                    media.as_ref(),
+                    mouse_status,
+                    drift.as_ref(),
+                    &listeners,
+                    &sinks,
+                    output.as_ref(),
+                    tracker.as_ref(),
+                    default_sink.as_ref(),
+                    session.as_ref(),
+                    input_status.as_ref().map(|(describe, _)| (describe.as_str(), input_event.as_ref())),
+                    filter_chain.as_ref(),
+                    comparison.as_ref(),
+                    recorder.as_ref(),
+                    leveler.as_ref(),
+                    &stage.guard,
+                    theme,
                 );
+                // a terminal that doesn't report its size gets everything
+                let (cols, rows) = terminal::size().ok().filter(|&(c, r)| c > 0 && r > 0).unwrap_or((u16::MAX, u16::MAX));
+                screen.fit_into(cols as usize, rows as usize, &mut screen_text);
+                clear_screen();
+                print!("{}", screen_text);
                 stdout().flush().ok();
 
                 last_update_time = Instant::now();
             }
-            Ok(_) => continue, // bad packet size, skip
-            Err(e) => {
-                if e.kind() != std::io::ErrorKind::WouldBlock
-                    && e.kind() != std::io::ErrorKind::TimedOut {
-                    // don't print errors in raw mode, just continue
-                }
+            Ok(None) | Err(_) => {
+                // don't print errors in raw mode, just continue
                 // sleep a tiny bit to save cpu when no data
                 std::thread::sleep(Duration::from_millis(5));
             }
@@ -634,16 +879,34 @@ fn run_main_loop() -> Result<(), String> {
 enum KeyAction {
     Quit,
     Changed,
+    Calibrate,
+    ApplyCalibration,
+    TuneSmoothing,
+    ApplySmoothing,
+    ToggleMouse,
+    Recenter,
+    CalibrateBias,
+    CycleFilter,
+    CycleTheme,
     None,
 }
 
-fn handle_key_event(
-    key: KeyEvent,
-    radius: &mut f64,
-    mode: &mut SpeakerMode,
-    reverb_enabled: &mut bool,
-    width: &mut f64,
-) -> KeyAction {
+fn apply_calibration(engine: &mut Engine, c: Calibration) {
+    engine.yaw.sensitivity = c.yaw_sensitivity;
+    engine.pitch.sensitivity = c.pitch_sensitivity;
+}
+
+// the proposed factors drive the ema, which becomes the filter in use
+fn apply_smoothing(engine: &mut Engine, comparison: Option<&mut FilterComparison>, p: SmoothingProposal) {
+    engine.smoothed.axis_factors = p.factors().map(Some);
+    engine.smoothed.set_kind(FilterKind::Ema);
+    if let Some(comparison) = comparison {
+        comparison.set_axis_factors(engine.smoothed.axis_factors);
+    }
+}
+
+fn handle_key_event(key: KeyEvent, engine: &mut Engine) -> KeyAction {
+    let Engine { radius, mode, reverb_enabled, width, .. } = engine;
     match key.code {
         // quit keys
         KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => KeyAction::Quit,
@@ -693,6 +956,29 @@ fn handle_key_event(
             KeyAction::Changed
         }
 
+        // sensitivity calibration: c = start recording, a = apply proposal
+        KeyCode::Char('c') | KeyCode::Char('C') => KeyAction::Calibrate,
+        KeyCode::Char('a') | KeyCode::Char('A') => KeyAction::ApplyCalibration,
+
+        // smoothing analysis: n = start, y = apply proposal
+        KeyCode::Char('n') | KeyCode::Char('N') => KeyAction::TuneSmoothing,
+        KeyCode::Char('y') | KeyCode::Char('Y') => KeyAction::ApplySmoothing,
+
+        // recenter: space makes the current head pose straight ahead
+        KeyCode::Char(' ') => KeyAction::Recenter,
+
+        // gyro bias: g starts a hold-still calibration
+        KeyCode::Char('g') | KeyCode::Char('G') => KeyAction::CalibrateBias,
+
+        // mouse look pause/resume: m key
+        KeyCode::Char('m') | KeyCode::Char('M') => KeyAction::ToggleMouse,
+
+        // smoothing filter: f cycles ema -> one euro -> kalman
+        KeyCode::Char('f') | KeyCode::Char('F') => KeyAction::CycleFilter,
+
+        // dashboard theme: t cycles default -> light -> high-contrast -> mono -> ascii
+        KeyCode::Char('t') | KeyCode::Char('T') => KeyAction::CycleTheme,
+
         _ => KeyAction::None,
     }
 }
\ No newline at end of file
@@ -0,0 +1,103 @@
+// per-axis shaping of the smoothed head pose before it moves the speakers:
+// dead zone -> center magnet -> response curve -> sensitivity.
+// range limits are applied earlier, to the pose going into the filter.
+
+// angle at which the response curve meets the linear response again
+pub const CURVE_REFERENCE: f64 = 90.0;
+
+pub const MAX_DEAD_ZONE: f64 = 45.0;
+pub const MAX_MAGNET: f64 = 60.0;
+pub const MAX_LIMIT: f64 = 180.0;
+
+// how much of the motion the magnet swallows right at center (1.0 = all)
+const MAGNET_STRENGTH: f64 = 0.85;
+pub const MIN_CURVE: f64 = 0.2;
+pub const MAX_CURVE: f64 = 5.0;
+
+// what happens to head angles past the axis limit
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LimitMode {
+    // hold at the limit
+    Clamp,
+    // reflect back from the limit, 70° with a 60° limit becomes 50°
+    Fold,
+    // drop the axis from that pose, the last accepted value stays
+    Ignore,
+}
+
+impl LimitMode {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "clamp" => Some(LimitMode::Clamp),
+            "fold" => Some(LimitMode::Fold),
+            "ignore" => Some(LimitMode::Ignore),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AxisMapping {
+    // degrees around center that are ignored; output starts at 0 right after it
+    pub dead_zone: f64,
+    // output multiplier, 1.0 = head angle maps 1:1
+    pub sensitivity: f64,
+    // response exponent: 1.0 = linear, >1 = gentle near center, <1 = eager near center
+    pub curve: f64,
+    // degrees past the dead zone over which the output is pulled toward
+    // center; unlike the dead zone there is no flat spot and no sudden onset
+    pub magnet: f64,
+    // largest accepted angle from center, 0 = unlimited
+    pub limit: f64,
+    pub limit_mode: LimitMode,
+}
+
+impl AxisMapping {
+    pub const fn identity() -> Self {
+        Self { dead_zone: 0.0, sensitivity: 1.0, curve: 1.0, magnet: 0.0, limit: 0.0, limit_mode: LimitMode::Clamp }
+    }
+
+    // apply the range limit to a recentered head angle, None = ignore it
+    pub fn limit(&self, angle: f64) -> Option<f64> {
+        let l = self.limit;
+        if l <= 0.0 || angle.abs() <= l {
+            return Some(angle);
+        }
+        match self.limit_mode {
+            LimitMode::Clamp => Some(angle.clamp(-l, l)),
+            // triangle wave: bounces between -l and l however far past it goes
+            LimitMode::Fold => {
+                let t = (angle + l).rem_euclid(4.0 * l);
+                Some(if t <= 2.0 * l { t - l } else { 3.0 * l - t })
+            }
+            LimitMode::Ignore => None,
+        }
+    }
+
+    pub fn apply(&self, angle: f64) -> f64 {
+        let magnitude = magnetize((angle.abs() - self.dead_zone).max(0.0), self.magnet);
+        let shaped = if self.curve == 1.0 {
+            magnitude
+        } else {
+            CURVE_REFERENCE * (magnitude / CURVE_REFERENCE).powf(self.curve)
+        };
+        angle.signum() * shaped * self.sensitivity
+    }
+}
+
+// inside the magnet range the slope starts at 1 - MAGNET_STRENGTH and rises
+// to meet the identity at the edge with matching slope, so the pull fades
+// out smoothly: f(m) = m - s * m * (1 - m/r)^2. monotonic for s <= 1
+fn magnetize(magnitude: f64, range: f64) -> f64 {
+    if range <= 0.0 || magnitude >= range {
+        return magnitude;
+    }
+    let rest = 1.0 - magnitude / range;
+    magnitude - MAGNET_STRENGTH * magnitude * rest * rest
+}
+
+impl Default for AxisMapping {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
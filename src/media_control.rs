@@ -0,0 +1,237 @@
+// accessibility mode: sustained head positions and simple gestures drive
+// system volume, mute and mpris playback instead of needing the keyboard.
+//
+// hold look up/down     -> volume up/down (repeats while held)
+// hold look left/right  -> previous/next track
+// quick nod             -> play/pause
+// head shake            -> mute toggle
+//
+// angles follow the engine convention: positive yaw = looking left,
+// positive pitch = looking up.
+
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+// how long a position has to be held before it triggers
+pub const DWELL_TIME: Duration = Duration::from_millis(1500);
+// volume keeps stepping at this interval while the head stays up/down
+pub const REPEAT_INTERVAL: Duration = Duration::from_millis(750);
+
+// zone thresholds in degrees
+pub const YAW_ZONE: f64 = 30.0;
+pub const PITCH_ZONE: f64 = 20.0;
+// head has to come back inside this before a one-shot action can fire again
+pub const CENTER_ZONE: f64 = 10.0;
+
+// nod/shake: minimum excursion and the window it has to happen in
+const GESTURE_AMPLITUDE: f64 = 12.0;
+const GESTURE_WINDOW: Duration = Duration::from_millis(1200);
+const SHAKE_EXCURSIONS: usize = 3;
+
+const VOLUME_STEP: &str = "5%";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MediaAction {
+    VolumeUp,
+    VolumeDown,
+    ToggleMute,
+    PlayPause,
+    Next,
+    Previous,
+}
+
+impl MediaAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MediaAction::VolumeUp => "Volume Up",
+            MediaAction::VolumeDown => "Volume Down",
+            MediaAction::ToggleMute => "Mute",
+            MediaAction::PlayPause => "Play/Pause",
+            MediaAction::Next => "Next Track",
+            MediaAction::Previous => "Previous Track",
+        }
+    }
+
+    fn repeats(&self) -> bool {
+        matches!(self, MediaAction::VolumeUp | MediaAction::VolumeDown)
+    }
+
+    fn command(&self) -> Command {
+        let up = format!("{}+", VOLUME_STEP);
+        let down = format!("{}-", VOLUME_STEP);
+        let (program, args): (&str, Vec<&str>) = match self {
+            MediaAction::VolumeUp => ("wpctl", vec!["set-volume", "-l", "1.0", "@DEFAULT_AUDIO_SINK@", &up]),
+            MediaAction::VolumeDown => ("wpctl", vec!["set-volume", "@DEFAULT_AUDIO_SINK@", &down]),
+            MediaAction::ToggleMute => ("wpctl", vec!["set-mute", "@DEFAULT_AUDIO_SINK@", "toggle"]),
+            MediaAction::PlayPause => ("playerctl", vec!["play-pause"]),
+            MediaAction::Next => ("playerctl", vec!["next"]),
+            MediaAction::Previous => ("playerctl", vec!["previous"]),
+        };
+        let mut command = Command::new(program);
+        command.args(args).stdout(Stdio::null()).stderr(Stdio::null());
+        command
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Zone {
+    Center,
+    Up,
+    Down,
+    Left,
+    Right,
+    // outside the center but not far enough for a zone
+    Between,
+}
+
+impl Zone {
+    fn classify(yaw: f64, pitch: f64) -> Zone {
+        if pitch >= PITCH_ZONE {
+            Zone::Up
+        } else if pitch <= -PITCH_ZONE {
+            Zone::Down
+        } else if yaw >= YAW_ZONE {
+            Zone::Left
+        } else if yaw <= -YAW_ZONE {
+            Zone::Right
+        } else if yaw.abs() < CENTER_ZONE && pitch.abs() < CENTER_ZONE {
+            Zone::Center
+        } else {
+            Zone::Between
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Zone::Center => "CENTER",
+            Zone::Up => "LOOK UP",
+            Zone::Down => "LOOK DOWN",
+            Zone::Left => "LOOK LEFT",
+            Zone::Right => "LOOK RIGHT",
+            Zone::Between => "-",
+        }
+    }
+
+    pub fn hold_action(&self) -> Option<MediaAction> {
+        match self {
+            Zone::Up => Some(MediaAction::VolumeUp),
+            Zone::Down => Some(MediaAction::VolumeDown),
+            Zone::Left => Some(MediaAction::Previous),
+            Zone::Right => Some(MediaAction::Next),
+            Zone::Center | Zone::Between => None,
+        }
+    }
+}
+
+pub struct MediaController {
+    zone: Zone,
+    zone_since: Instant,
+    last_fire: Option<Instant>,
+    armed: bool,
+    nod_down_at: Option<Instant>,
+    shake: Vec<(f64, Instant)>, // (sign, time) of alternating yaw excursions
+    pub last_action: Option<(MediaAction, Instant)>,
+    // wpctl/playerctl calls still running, reaped on update so they don't
+    // pile up as zombies
+    running: Vec<Child>,
+}
+
+impl MediaController {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            zone: Zone::Center,
+            zone_since: now,
+            last_fire: None,
+            armed: true,
+            nod_down_at: None,
+            shake: Vec::with_capacity(SHAKE_EXCURSIONS),
+            last_action: None,
+            running: Vec::new(),
+        }
+    }
+
+    pub fn zone(&self) -> Zone {
+        self.zone
+    }
+
+    // 0..1 progress toward the dwell time of the current zone (None if it has no action)
+    pub fn dwell_progress(&self, now: Instant) -> Option<f64> {
+        self.zone.hold_action()?;
+        if self.last_fire.is_some() {
+            return Some(1.0);
+        }
+        let held = now.duration_since(self.zone_since).as_secs_f64();
+        Some((held / DWELL_TIME.as_secs_f64()).min(1.0))
+    }
+
+    // feed the smoothed head pose, returns an action when one triggers
+    pub fn update(&mut self, yaw: f64, pitch: f64, now: Instant) -> Option<MediaAction> {
+        self.running.retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+
+        let zone = Zone::classify(yaw, pitch);
+        if zone != self.zone {
+            self.zone = zone;
+            self.zone_since = now;
+            self.last_fire = None;
+        }
+        if zone == Zone::Center {
+            self.armed = true;
+        }
+
+        let action = self.detect_gesture(yaw, pitch, now).or_else(|| self.detect_hold(now))?;
+        self.last_action = Some((action, now));
+        Some(action)
+    }
+
+    // fire and forget, same as the pw-cli calls; the child is only kept for reaping
+    pub fn execute(&mut self, action: MediaAction) {
+        if let Ok(child) = action.command().spawn() {
+            self.running.push(child);
+        }
+    }
+
+    fn detect_hold(&mut self, now: Instant) -> Option<MediaAction> {
+        let action = self.zone.hold_action()?;
+        let due = match self.last_fire {
+            None => now.duration_since(self.zone_since) >= DWELL_TIME && (self.armed || action.repeats()),
+            Some(t) => action.repeats() && now.duration_since(t) >= REPEAT_INTERVAL,
+        };
+        if !due {
+            return None;
+        }
+        self.last_fire = Some(now);
+        if !action.repeats() {
+            self.armed = false;
+        }
+        Some(action)
+    }
+
+    fn detect_gesture(&mut self, yaw: f64, pitch: f64, now: Instant) -> Option<MediaAction> {
+        // nod: quick dip below the amplitude and back up to center
+        if pitch <= -GESTURE_AMPLITUDE {
+            self.nod_down_at.get_or_insert(now);
+        } else if pitch > -CENTER_ZONE {
+            if let Some(t) = self.nod_down_at.take() {
+                if now.duration_since(t) <= GESTURE_WINDOW {
+                    self.shake.clear();
+                    return Some(MediaAction::PlayPause);
+                }
+            }
+        }
+
+        // shake: alternating left/right excursions inside the window
+        self.shake.retain(|&(_, t)| now.duration_since(t) <= GESTURE_WINDOW);
+        if yaw.abs() >= GESTURE_AMPLITUDE {
+            let sign = yaw.signum();
+            if self.shake.last().map(|&(s, _)| s != sign).unwrap_or(true) {
+                self.shake.push((sign, now));
+            }
+            if self.shake.len() >= SHAKE_EXCURSIONS {
+                self.shake.clear();
+                return Some(MediaAction::ToggleMute);
+            }
+        }
+
+        None
+    }
+}
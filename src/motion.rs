@@ -0,0 +1,63 @@
+// angular velocity per axis and a combined motion intensity, from the
+// tracker poses. raw differences between packets are very noisy, so the
+// velocity is low-pass filtered; intensity squashes the overall angular
+// speed into 0..1 for meters and anything that wants to adapt to how much
+// the head is moving (smoothing, gestures, update rates).
+
+use std::time::Instant;
+
+use crate::engine::wrap_degrees;
+
+// low-pass time constant for the velocity estimate, seconds
+const VELOCITY_TIME_CONSTANT: f64 = 0.1;
+
+// angular speed (deg/s) that counts as full intensity, a brisk head turn
+pub const FULL_INTENSITY_SPEED: f64 = 180.0;
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Motion {
+    // yaw, pitch, roll in deg/s
+    pub velocity: [f64; 3],
+    // 0 = still, 1 = turning at FULL_INTENSITY_SPEED or faster
+    pub intensity: f64,
+}
+
+impl Motion {
+    // combined angular speed in deg/s
+    pub fn speed(&self) -> f64 {
+        let [y, p, r] = self.velocity;
+        (y * y + p * p + r * r).sqrt()
+    }
+}
+
+#[derive(Default)]
+pub struct MotionTracker {
+    pub motion: Motion,
+    last: Option<([f64; 3], Instant)>,
+}
+
+impl MotionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, pose: [f64; 3], now: Instant) -> Motion {
+        if let Some((last, at)) = self.last {
+            let dt = now.duration_since(at).as_secs_f64();
+            if dt > 0.0 {
+                let a = 1.0 - (-dt / VELOCITY_TIME_CONSTANT).exp();
+                for ((v, new), old) in self.motion.velocity.iter_mut().zip(pose).zip(last) {
+                    *v += a * (wrap_degrees(new - old) / dt - *v);
+                }
+                self.motion.intensity = (self.motion.speed() / FULL_INTENSITY_SPEED).min(1.0);
+            }
+        }
+        self.last = Some((pose, now));
+        self.motion
+    }
+
+    // forget the previous pose, e.g. after a tracking dropout
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
@@ -0,0 +1,68 @@
+// several inputs read at the same time as one, e.g. an ipv6 socket for a
+// phone next to an ipv4 one for opentrack on this machine, each with its own
+// packet layout. unlike failover there is no preference: every pose is passed
+// on as it arrives, from whichever socket it came in on, the same as several
+// senders on one socket.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::input::{self, InputSource, Pose};
+
+// how often the sources are checked while waiting for a pose
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+pub struct MultiInput {
+    sources: Vec<Box<dyn InputSource>>,
+    // poses received per source, to see which socket the tracker reaches
+    packets: Vec<u64>,
+    // the source asked first on the next poll, so a busy one can't starve the rest
+    next: usize,
+}
+
+impl MultiInput {
+    // the first uri is the main input
+    pub fn open(uris: &[String]) -> Result<Self, String> {
+        let sources = uris.iter().map(|uri| input::open(uri)).collect::<Result<Vec<_>, String>>()?;
+        if sources.is_empty() {
+            return Err("no input to listen on".to_string());
+        }
+        Ok(Self { packets: vec![0; sources.len()], sources, next: 0 })
+    }
+}
+
+impl InputSource for MultiInput {
+    fn recv(&mut self, timeout: Duration) -> Result<Option<Pose>, String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            for _ in 0..self.sources.len() {
+                let i = self.next;
+                self.next = (self.next + 1) % self.sources.len();
+                if let Some(pose) = self.sources[i].recv(Duration::ZERO)? {
+                    self.packets[i] += 1;
+                    return Ok(Some(pose));
+                }
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            thread::sleep(POLL_INTERVAL.min(deadline - now));
+        }
+    }
+
+    // the main input, which profiles and drift corrections are kept for
+    fn uri(&self) -> &str {
+        self.sources[0].uri()
+    }
+
+    // e.g. "OpenTrack UDP [::1]:4242 (1532 packets) + OpenTrack UDP 0.0.0.0:4243 (0 packets)"
+    fn describe(&self) -> String {
+        let sources = self.sources.iter().zip(&self.packets);
+        sources.map(|(s, packets)| format!("{} ({} packets)", s.describe(), packets)).collect::<Vec<_>>().join(" + ")
+    }
+
+    fn take_event(&mut self) -> Option<String> {
+        self.sources.iter_mut().find_map(|s| s.take_event())
+    }
+}
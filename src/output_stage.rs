@@ -0,0 +1,78 @@
+// the stage between the engine and the outputs, the same for the main
+// pipeline and every --listener: the pauses decide whether the speakers follow
+// the head at all, extra gains (zone fades, loudness leveling) are multiplied
+// in, and the output smoothing and the volume guard run last so nothing gets
+// past them.
+
+use std::time::Instant;
+
+use spatial_track::engine::{Engine, OutputSmoother, SpatialState};
+use spatial_track::volume_guard::VolumeGuard;
+
+#[derive(Clone, Copy, Default)]
+pub struct Pauses {
+    // nobody at the desk: speakers in front at full volume, without distance
+    // or rear attenuation
+    pub session: bool,
+    // hdmi output or exclusive media control: speakers centered, otherwise as
+    // configured
+    pub center: bool,
+}
+
+pub struct OutputStage {
+    // second, light smoothing stage on the final speaker values
+    smoother: OutputSmoother,
+    // ceiling and rise limit on whatever gain ends up being applied
+    pub guard: VolumeGuard,
+}
+
+impl OutputStage {
+    pub fn new(smoother: OutputSmoother, guard: VolumeGuard) -> Self {
+        Self { smoother, guard }
+    }
+
+    // speaker state for the glided head angles `shown`, `gain` is multiplied
+    // into the speaker gains. the second value is why the guard stepped in
+    pub fn process(
+        &mut self,
+        engine: &Engine,
+        shown: [f64; 3],
+        pauses: Pauses,
+        gain: f64,
+        dt: f64,
+        now: Instant,
+    ) -> (SpatialState, Option<&'static str>) {
+        let mut spatial = if pauses.session {
+            neutral_spatial(engine)
+        } else if pauses.center {
+            SpatialState::from_head_tracking(0.0, 0.0, engine.radius, engine.mode, engine.reverb_enabled, engine.width)
+        } else {
+            engine.spatial_at(shown[0], shown[1])
+        };
+        spatial.left_gain *= gain;
+        spatial.right_gain *= gain;
+        let mut spatial = self.smoother.update(&spatial, dt);
+        let limited = self.guard.apply(&mut spatial, now);
+        (spatial, limited)
+    }
+
+    // the session pause while no poses come in: the neutral state goes
+    // straight to the guard, there is no head movement to smooth
+    pub fn neutral(&mut self, engine: &Engine, gain: f64, now: Instant) -> (SpatialState, Option<&'static str>) {
+        let mut spatial = neutral_spatial(engine);
+        spatial.left_gain *= gain;
+        spatial.right_gain *= gain;
+        let limited = self.guard.apply(&mut spatial, now);
+        (spatial, limited)
+    }
+}
+
+// speakers in front at full volume, no panning
+fn neutral_spatial(engine: &Engine) -> SpatialState {
+    let mut spatial =
+        SpatialState::from_head_tracking(0.0, 0.0, engine.radius, engine.mode, engine.reverb_enabled, engine.width);
+    spatial.gain = 1.0;
+    spatial.left_gain = 1.0;
+    spatial.right_gain = 1.0;
+    spatial
+}
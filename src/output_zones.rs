@@ -0,0 +1,151 @@
+// orientation-based output switching: head yaw sectors map to sinks, e.g.
+// facing the desk -> headphones, facing the couch -> living room speakers.
+//
+// a new sector has to be faced for ZONE_DWELL before anything happens, and
+// the current sector is widened by ZONE_HYSTERESIS so glancing along its
+// edge does not flip back and forth. the switch itself fades the spatializer
+// out, moves its output stream to the new sink and fades back in.
+//
+// yaw follows the engine convention: positive = looking left.
+
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+pub const ZONE_DWELL: Duration = Duration::from_secs(3);
+pub const ZONE_HYSTERESIS: f64 = 5.0;
+pub const FADE_TIME: Duration = Duration::from_millis(300);
+
+#[derive(Clone)]
+pub struct OutputZone {
+    pub from: f64,
+    pub to: f64,
+    pub sink: String,
+}
+
+impl OutputZone {
+    // "<from>:<to>=<sink node name>", yaw degrees
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid output zone '{}', expected <from>:<to>=<sink>", spec);
+        let (range, sink) = spec.split_once('=').ok_or_else(invalid)?;
+        let (from, to) = range.split_once(':').ok_or_else(invalid)?;
+        let from: f64 = from.trim().parse().map_err(|_| invalid())?;
+        let to: f64 = to.trim().parse().map_err(|_| invalid())?;
+        if !(from.is_finite() && to.is_finite()) || from >= to {
+            return Err(format!("output zone '{}' needs <from> below <to>", spec));
+        }
+        if sink.is_empty() {
+            return Err(format!("output zone '{}' has no sink", spec));
+        }
+        Ok(Self { from, to, sink: sink.to_string() })
+    }
+
+    fn contains(&self, yaw: f64, margin: f64) -> bool {
+        yaw >= self.from - margin && yaw <= self.to + margin
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Phase {
+    Idle,
+    FadeOut(usize, Instant),
+    FadeIn(Instant),
+}
+
+pub struct OutputSwitcher {
+    zones: Vec<OutputZone>,
+    // zone whose sink the stream was last moved to
+    pub active: Option<usize>,
+    candidate: Option<(usize, Instant)>,
+    phase: Phase,
+    // pw-metadata calls still running, reaped on update so they don't pile
+    // up as zombies
+    moves: Vec<Child>,
+}
+
+impl OutputSwitcher {
+    pub fn new(zones: Vec<OutputZone>) -> Self {
+        Self { zones, active: None, candidate: None, phase: Phase::Idle, moves: Vec::new() }
+    }
+
+    pub fn zones(&self) -> &[OutputZone] {
+        &self.zones
+    }
+
+    // feed the smoothed yaw, returns the sink to move the stream to once the
+    // fade out has finished
+    pub fn update(&mut self, yaw: f64, now: Instant) -> Option<&str> {
+        self.moves.retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+
+        match self.phase {
+            Phase::FadeOut(target, since) => {
+                if now.duration_since(since) < FADE_TIME {
+                    return None;
+                }
+                self.active = Some(target);
+                self.candidate = None;
+                self.phase = Phase::FadeIn(now);
+                return Some(&self.zones[target].sink);
+            }
+            Phase::FadeIn(since) => {
+                if now.duration_since(since) >= FADE_TIME {
+                    self.phase = Phase::Idle;
+                }
+            }
+            Phase::Idle => {}
+        }
+
+        let staying = self.active.is_some_and(|i| self.zones[i].contains(yaw, ZONE_HYSTERESIS));
+        let facing = if staying { self.active } else { self.zones.iter().position(|z| z.contains(yaw, 0.0)) };
+
+        // looking somewhere without a zone keeps the current sink
+        let Some(facing) = facing.filter(|&i| Some(i) != self.active) else {
+            self.candidate = None;
+            return None;
+        };
+        match self.candidate {
+            Some((i, since)) if i == facing => {
+                if now.duration_since(since) >= ZONE_DWELL && matches!(self.phase, Phase::Idle) {
+                    self.phase = Phase::FadeOut(facing, now);
+                }
+            }
+            _ => self.candidate = Some((facing, now)),
+        }
+        None
+    }
+
+    // gain multiplier for the confirmation fade, 1.0 when not switching
+    pub fn fade(&self, now: Instant) -> f64 {
+        let progress = |since: Instant| (now.duration_since(since).as_secs_f64() / FADE_TIME.as_secs_f64()).min(1.0);
+        match self.phase {
+            Phase::Idle => 1.0,
+            Phase::FadeOut(_, since) => 1.0 - progress(since),
+            Phase::FadeIn(since) => progress(since),
+        }
+    }
+
+    pub fn is_fading(&self) -> bool {
+        !matches!(self.phase, Phase::Idle)
+    }
+
+    // zone being dwelled on and 0..1 progress toward switching to it
+    pub fn pending(&self, now: Instant) -> Option<(usize, f64)> {
+        if let Phase::FadeOut(target, _) = self.phase {
+            return Some((target, 1.0));
+        }
+        let (i, since) = self.candidate?;
+        Some((i, (now.duration_since(since).as_secs_f64() / ZONE_DWELL.as_secs_f64()).min(1.0)))
+    }
+
+    // point a stream node at the active zone's sink, wireplumber moves it over
+    pub fn move_stream(&mut self, stream_id: &str) {
+        let Some(i) = self.active else { return };
+        let child = Command::new("pw-metadata")
+            .args([stream_id, "target.object", &self.zones[i].sink])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        if let Ok(child) = child {
+            self.moves.push(child);
+        }
+    }
+}
@@ -0,0 +1,122 @@
+// which of the six doubles in a tracker packet is which, and in what units.
+// opentrack sends [x, y, z, yaw, pitch, roll] with translations in cm and
+// angles in degrees, other senders shuffle or scale them. the opentrack
+// inputs take the layout from their uri query:
+//
+//   ?layout=opentrack|facetracknoir|aitrack
+//   &order=<six of x,y,z,yaw,pitch,roll>&units=mm|cm|m&angles=deg|rad
+//   &scale=<field>:<factor>[,<field>:<factor>..]
+//
+// the preset goes first, the other parameters change parts of it. poses are
+// always converted to the opentrack order and units, so everything after the
+// input (relay included) keeps seeing opentrack packets.
+
+use crate::relay::{decode_pose, OPENTRACK_PACKET_SIZE};
+
+pub const FIELD_NAMES: [&str; 6] = ["x", "y", "z", "yaw", "pitch", "roll"];
+// the uri parameters handled here
+pub const LAYOUT_PARAMS: [&str; 5] = ["layout", "order", "units", "angles", "scale"];
+// largest value a pose field may have after conversion. far beyond any real
+// pose, and small enough that squares in the filters and response curves in
+// the mapping stay finite
+pub const MAX_VALUE: f64 = 1e5;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PacketLayout {
+    // opentrack field index of each packet slot
+    order: [usize; 6],
+    // cm per packet translation unit, degrees per packet angle unit
+    translation_unit: f64,
+    angle_unit: f64,
+    // extra factor per opentrack field, -1 flips an axis
+    scale: [f64; 6],
+}
+
+pub const OPENTRACK: PacketLayout =
+    PacketLayout { order: [0, 1, 2, 3, 4, 5], translation_unit: 1.0, angle_unit: 1.0, scale: [1.0; 6] };
+
+pub const PRESETS: [(&str, PacketLayout); 3] = [
+    ("opentrack", OPENTRACK),
+    // angles first
+    ("facetracknoir", PacketLayout { order: [3, 4, 5, 0, 1, 2], ..OPENTRACK }),
+    // opentrack order, translations in meters
+    ("aitrack", PacketLayout { translation_unit: 100.0, ..OPENTRACK }),
+];
+
+impl Default for PacketLayout {
+    fn default() -> Self {
+        OPENTRACK
+    }
+}
+
+impl PacketLayout {
+    // the layout the uri parameters describe, opentrack without any
+    pub fn from_params(params: &[(&str, &str)]) -> Result<Self, String> {
+        let mut layout = match params.iter().find(|(name, _)| *name == "layout") {
+            Some((_, preset)) => PRESETS.iter().find(|(name, _)| name == preset).map(|(_, layout)| *layout).ok_or_else(|| {
+                let names: Vec<&str> = PRESETS.iter().map(|(name, _)| *name).collect();
+                format!("unknown packet layout '{}' ({})", preset, names.join(", "))
+            })?,
+            None => OPENTRACK,
+        };
+        for &(name, value) in params {
+            match name {
+                "order" => {
+                    let fields: Option<Vec<usize>> = value.split(',').map(field_index).collect();
+                    let fields = fields.filter(|f| f.len() == 6 && (0..6).all(|i| f.contains(&i))).ok_or_else(|| {
+                        format!("invalid field order '{}', expected each of {} once", value, FIELD_NAMES.join(","))
+                    })?;
+                    layout.order.copy_from_slice(&fields);
+                }
+                "units" => {
+                    layout.translation_unit = match value {
+                        "mm" => 0.1,
+                        "cm" => 1.0,
+                        "m" => 100.0,
+                        _ => return Err(format!("unknown translation unit '{}' (mm, cm, m)", value)),
+                    }
+                }
+                "angles" => {
+                    layout.angle_unit = match value {
+                        "deg" => 1.0,
+                        "rad" => 180.0 / std::f64::consts::PI,
+                        _ => return Err(format!("unknown angle unit '{}' (deg, rad)", value)),
+                    }
+                }
+                "scale" => {
+                    for item in value.split(',') {
+                        let parsed = item
+                            .split_once(':')
+                            .and_then(|(field, factor)| Some((field_index(field)?, factor.parse::<f64>().ok()?)))
+                            .filter(|(_, factor)| factor.is_finite());
+                        let (field, factor) = parsed.ok_or_else(|| format!("invalid scale '{}', expected <field>:<factor>", item))?;
+                        layout.scale[field] = factor;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(layout)
+    }
+
+    // a packet as [x, y, z, yaw, pitch, roll] in cm and degrees. None when a
+    // field is not a number or out of range: anyone on the network can send
+    // one, and a single nan would stick in the filters for good
+    pub fn decode(&self, packet: &[u8; OPENTRACK_PACKET_SIZE]) -> Option<[f64; 6]> {
+        let mut pose = [0.0; 6];
+        for (&field, value) in self.order.iter().zip(decode_pose(packet)) {
+            let unit = if field < 3 { self.translation_unit } else { self.angle_unit };
+            pose[field] = value * unit * self.scale[field];
+        }
+        pose.iter().all(|v| v.abs() <= MAX_VALUE).then_some(pose)
+    }
+
+    // preset name, or "custom" after changes, for the dashboard
+    pub fn name(&self) -> &'static str {
+        PRESETS.iter().find(|(_, layout)| layout == self).map_or("custom", |(name, _)| name)
+    }
+}
+
+fn field_index(name: &str) -> Option<usize> {
+    FIELD_NAMES.iter().position(|&f| f == name.trim())
+}
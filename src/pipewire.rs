@@ -0,0 +1,323 @@
+// pipewire backend: finds the filter-chain node and pushes the spatial state
+// to it with pw-cli, as one output sink among the others.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Write};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use spatial_track::engine::{wrap_degrees, SpatialState};
+use spatial_track::sink::{Frame, OutputSink};
+
+use crate::watchdog::Watchdog;
+
+// only send command if angle changes by this many degrees
+pub const CHANGE_THRESHOLD: f64 = 0.5;
+
+// how often to look for the node while it is missing
+pub const NODE_SEARCH_INTERVAL: Duration = Duration::from_secs(2);
+
+// averaged for the dashboard
+const LATENCY_SAMPLES: usize = 30;
+
+// where the overall gain (distance, rear attenuation, zone fades) is applied
+#[derive(Clone, PartialEq, Debug)]
+pub enum VolumeControl {
+    // soft-volume inside the filter-chain, on the spatializer nodes
+    Filter,
+    // `Props volume` of the filter-chain's own output stream; the filter only
+    // keeps the left/right balance
+    Stream,
+    // `Props volume` of a dedicated node, e.g. a loopback behind the
+    // spatializer that nothing else touches
+    Node(String),
+}
+
+impl VolumeControl {
+    // filter | stream | node=<name>
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.split_once('=') {
+            Some(("node", name)) if !name.is_empty() => Some(VolumeControl::Node(name.to_string())),
+            Some(_) => None,
+            None => match value {
+                "filter" => Some(VolumeControl::Filter),
+                "stream" => Some(VolumeControl::Stream),
+                _ => None,
+            },
+        }
+    }
+
+    // node whose volume is set, None for the filter itself
+    fn node_name(&self) -> Option<&str> {
+        match self {
+            VolumeControl::Filter => None,
+            VolumeControl::Stream => Some(crate::SPATIALIZER_OUTPUT_NAME),
+            VolumeControl::Node(name) => Some(name),
+        }
+    }
+}
+
+// what was last pushed, to skip updates while the head is still
+#[derive(Clone, Copy)]
+struct Sent {
+    left_az: f64,
+    elevation: f64,
+    radius: f64,
+    left_gain: f64,
+    right_gain: f64,
+    stereo_mix: f64,
+    crosstalk: f64,
+}
+
+pub struct PipewireSink {
+    pub node_name: String,
+    pub node_id: Option<String>,
+    last_search: Option<Instant>,
+    // reaps pw-cli calls and backs off while pipewire is unreachable
+    backend: Watchdog,
+    last_sent: Option<Sent>,
+    latency_samples: VecDeque<f64>,
+    volume: VolumeControl,
+    volume_node_id: Option<String>,
+    last_volume: Option<f64>,
+    // the set-param json, rebuilt in place for every update
+    params: String,
+}
+
+impl PipewireSink {
+    pub fn new(node_name: &str, volume: VolumeControl) -> Self {
+        Self {
+            node_name: node_name.to_string(),
+            node_id: None,
+            last_search: None,
+            backend: Watchdog::new(),
+            last_sent: None,
+            latency_samples: VecDeque::with_capacity(LATENCY_SAMPLES),
+            volume,
+            volume_node_id: None,
+            last_volume: None,
+            params: String::new(),
+        }
+    }
+
+    fn changed(&self, frame: &Frame) -> bool {
+        let Some(last) = self.last_sent else { return true };
+        // compared on the speaker positions so output smoothing still settles
+        let spatial = frame.spatial;
+        wrap_degrees(spatial.left_az - last.left_az).abs() > CHANGE_THRESHOLD
+            || (spatial.elevation - last.elevation).abs() > CHANGE_THRESHOLD
+            || (spatial.radius - last.radius).abs() > 0.01
+            // gain fades (e.g. switching output devices)
+            || (spatial.left_gain - last.left_gain).abs() > 0.01
+            || (spatial.right_gain - last.right_gain).abs() > 0.01
+            || (spatial.stereo_mix - last.stereo_mix).abs() > 0.01
+            || (spatial.crosstalk - last.crosstalk).abs() > 0.01
+    }
+}
+
+impl OutputSink for PipewireSink {
+    fn name(&self) -> &'static str {
+        "pipewire"
+    }
+
+    fn send(&mut self, frame: &Frame) {
+        let Some(ref id) = self.node_id else { return };
+        if !(frame.force || self.changed(frame)) || !self.backend.ready(frame.pose.time) {
+            return;
+        }
+
+        let start = Instant::now();
+        let mut spatial = *frame.spatial;
+        // with a volume node the filter only keeps the balance, the louder
+        // speaker at full gain; until the node is found the filter does it all
+        if let Some(ref volume_id) = self.volume_node_id {
+            let volume = spatial.left_gain.max(spatial.right_gain);
+            if volume > 0.0 {
+                spatial.left_gain /= volume;
+                spatial.right_gain /= volume;
+            }
+            if frame.force || self.last_volume.is_none_or(|v| (v - volume).abs() > 0.005) {
+                self.backend.track(set_volume(volume_id, volume), start);
+                self.last_volume = Some(volume);
+            }
+        }
+        write_params(&mut self.params, &spatial);
+        self.backend.track(update_pipewire(id, &self.params), start);
+        if self.latency_samples.len() == LATENCY_SAMPLES {
+            self.latency_samples.pop_front();
+        }
+        self.latency_samples.push_back(start.elapsed().as_secs_f64() * 1000.0);
+
+        let spatial = frame.spatial;
+        self.last_sent = Some(Sent {
+            left_az: spatial.left_az,
+            elevation: spatial.elevation,
+            radius: spatial.radius,
+            left_gain: spatial.left_gain,
+            right_gain: spatial.right_gain,
+            stereo_mix: spatial.stereo_mix,
+            crosstalk: spatial.crosstalk,
+        });
+    }
+
+    fn poll(&mut self, now: Instant) -> Option<(&'static str, String)> {
+        self.backend.poll(now);
+        if self.backend.take_reconnect() {
+            // pipewire may have restarted, node ids are not stable across that
+            self.node_id = None;
+            self.volume_node_id = None;
+        }
+
+        let volume_missing = self.volume.node_name().is_some() && self.volume_node_id.is_none();
+        if (self.node_id.is_some() && !volume_missing)
+            || self.last_search.is_some_and(|t| now.duration_since(t) < NODE_SEARCH_INTERVAL)
+        {
+            return None;
+        }
+        self.last_search = Some(now);
+        if let Some(name) = self.volume.node_name() {
+            self.volume_node_id = find_spatializer_node(name);
+            self.last_volume = None;
+        }
+        if self.node_id.is_some() {
+            // only the volume node was missing, push the split gains
+            self.last_sent = None;
+            return None;
+        }
+        self.node_id = find_spatializer_node(&self.node_name);
+        // push the current state right away, also after a reconnect
+        self.last_sent = None;
+        self.node_id.clone().map(|id| ("node_linked", id))
+    }
+
+    fn status(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some(ref id) = self.node_id else {
+            return write!(f, "\x1B[1;31m✗ SEARCHING\x1B[0m for '{}'...", self.node_name);
+        };
+        write!(f, "\x1B[1;32m✓ LINKED\x1B[0m to Node \x1B[1;37m{}\x1B[0m ({})", id, self.node_name)?;
+        match (self.volume.node_name(), &self.volume_node_id) {
+            (None, _) => Ok(()),
+            (Some(_), Some(id)) => write!(f, "  vol \x1B[1;37m{}\x1B[0m", id),
+            (Some(name), None) => write!(f, "  vol \x1B[1;31m✗\x1B[0m '{}'", name),
+        }
+    }
+
+    fn error(&self) -> Option<Vec<String>> {
+        if !self.backend.is_down() {
+            return None;
+        }
+        let retry = match self.backend.retry_in(Instant::now()) {
+            Some(d) => format!("retrying in {}s", d.as_secs() + 1),
+            None => "retrying now".to_string(),
+        };
+        let mut lines = vec![format!("pw-cli failed {} times in a row, {}", self.backend.failures, retry)];
+        if let Some(ref e) = self.backend.last_error {
+            lines.push(format!("\x1B[1;31m{}\x1B[0m", e.chars().take(54).collect::<String>()));
+        }
+        Some(lines)
+    }
+
+    fn latency_ms(&self) -> Option<f64> {
+        if self.latency_samples.is_empty() {
+            return None;
+        }
+        Some(self.latency_samples.iter().sum::<f64>() / self.latency_samples.len() as f64)
+    }
+}
+
+pub fn find_spatializer_node(node_name: &str) -> Option<String> {
+    // run 'pw-cli ls Node'
+    let output = Command::new("pw-cli").args(["ls", "Node"]).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+
+    let mut current_id = String::new();
+    // quoted, so "x" doesn't match "x_2"
+    let quoted = format!("\"{}\"", node_name);
+
+    // simple state machine parser (no external deps)
+    for line in text.lines() {
+        let trim = line.trim();
+        if trim.starts_with("id") {
+            // "id 36, type PipeWire:Interface:Node..."
+            if let Some(id_part) = trim.split_whitespace().nth(1) {
+                current_id = id_part.trim_matches(',').to_string();
+            }
+        }
+        // check for our target node name
+        if trim.contains("node.name") && trim.contains(&quoted) {
+            return Some(current_id);
+        }
+    }
+    None
+}
+
+// the json for the stereo filter-chain, written over `out`
+pub fn write_params(out: &mut String, spatial: &SpatialState) {
+    // sets params for both 'spat_left' and 'spat_right' nodes
+    // uses dynamic radius and includes gain for reverb simulation
+    let dry_gain = 1.0 - spatial.reverb_gain;
+    // each channel keeps (1 + mix) / 2 of itself and takes the rest from the other
+    let (direct, cross) = ((1.0 + spatial.stereo_mix) / 2.0, (1.0 - spatial.stereo_mix) / 2.0);
+    out.clear();
+    write!(
+        out,
+        "{{ \"params\": [ \
+            \"spat_left:Azimuth\", {:.2}, \
+            \"spat_left:Elevation\", {:.2}, \
+            \"spat_left:Radius\", {:.2}, \
+            \"spat_left:Gain\", {:.2}, \
+            \"spat_right:Azimuth\", {:.2}, \
+            \"spat_right:Elevation\", {:.2}, \
+            \"spat_right:Radius\", {:.2}, \
+            \"spat_right:Gain\", {:.2}, \
+            \"final_mix_l:Gain 1\", {:.2}, \
+            \"final_mix_l:Gain 2\", {:.2}, \
+            \"final_mix_r:Gain 1\", {:.2}, \
+            \"final_mix_r:Gain 2\", {:.2}, \
+            \"shelf_left_l:Gain\", {:.2}, \
+            \"shelf_left_r:Gain\", {:.2}, \
+            \"shelf_right_l:Gain\", {:.2}, \
+            \"shelf_right_r:Gain\", {:.2}, \
+            \"cross_l:Gain 1\", {:.2}, \
+            \"cross_l:Gain 2\", {:.2}, \
+            \"cross_r:Gain 1\", {:.2}, \
+            \"cross_r:Gain 2\", {:.2}, \
+            \"ctc_l:Mult\", {:.2}, \
+            \"ctc_r:Mult\", {:.2} \
+        ] }}",
+        spatial.left_az, spatial.elevation, spatial.radius, spatial.left_gain,
+        spatial.right_az, spatial.elevation, spatial.radius, spatial.right_gain,
+        dry_gain, spatial.reverb_gain,
+        dry_gain, spatial.reverb_gain,
+        spatial.left_shelf_db, spatial.left_shelf_db,
+        spatial.right_shelf_db, spatial.right_shelf_db,
+        direct, cross,
+        direct, cross,
+        // the other speaker's signal is subtracted, so the multiplier is negative
+        -spatial.crosstalk, -spatial.crosstalk
+    )
+    .ok();
+}
+
+// params from write_params
+pub fn update_pipewire(id: &str, json_payload: &str) -> Result<Child, String> {
+
+    // spawn async to prevent frame drops, the watchdog reaps it later
+    // stdout goes to null to prevent tui artifacts, stderr is kept for errors
+    Command::new("pw-cli")
+        .args(["set-param", id, "Props", json_payload])
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run pw-cli: {}", e))
+}
+
+// overall volume for --volume-control stream/node, leaves channelVolumes alone
+pub fn set_volume(id: &str, volume: f64) -> Result<Child, String> {
+    Command::new("pw-cli")
+        .args(["set-param", id, "Props", &format!("{{ \"volume\": {:.3} }}", volume)])
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run pw-cli: {}", e))
+}
@@ -0,0 +1,105 @@
+// --plain-status: instead of redrawing the full-screen dashboard, print one
+// short line of text now and then, e.g.
+//
+//   tracking active, pan 30% right, volume 72%
+//
+// for screen readers, braille displays and logs. a line is only printed when
+// it says something new, at most every STATUS_INTERVAL; losing or regaining
+// tracking and key presses are reported right away.
+
+use std::io::{stdout, Write};
+use std::time::{Duration, Instant};
+
+use spatial_track::engine::SpatialState;
+
+pub const STATUS_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tracking {
+    Waiting,
+    Active,
+    Lost,
+}
+
+impl Tracking {
+    fn label(&self) -> &'static str {
+        match self {
+            Tracking::Waiting => "waiting for tracker",
+            Tracking::Active => "tracking active",
+            Tracking::Lost => "tracking lost",
+        }
+    }
+}
+
+pub struct PlainStatus {
+    last_line: String,
+    last_print: Option<Instant>,
+    last_tracking: Option<Tracking>,
+}
+
+impl PlainStatus {
+    pub fn new() -> Self {
+        Self { last_line: String::new(), last_print: None, last_tracking: None }
+    }
+
+    // a line of its own, e.g. "recentered"
+    pub fn announce(&mut self, text: &str) {
+        print_line(text);
+        // the next status goes out right away, it likely changed
+        self.last_print = None;
+    }
+
+    // whether report() would look at a new line: tracking changed or the
+    // interval is up
+    pub fn due(&self, now: Instant, tracking: Tracking) -> bool {
+        self.last_tracking != Some(tracking) || self.last_print.is_none_or(|t| now.duration_since(t) >= STATUS_INTERVAL)
+    }
+
+    // print the current status if it changed and is due
+    pub fn report(&mut self, now: Instant, tracking: Tracking, spatial: Option<&SpatialState>, notes: &[String]) {
+        if !self.due(now, tracking) {
+            return;
+        }
+        let line = describe(tracking, spatial, notes);
+        self.last_tracking = Some(tracking);
+        if line != self.last_line {
+            print_line(&line);
+            self.last_line = line;
+            self.last_print = Some(now);
+        }
+    }
+}
+
+// "tracking active, pan 30% right, volume 72%" plus the notes
+fn describe(tracking: Tracking, spatial: Option<&SpatialState>, notes: &[String]) -> String {
+    let mut parts = vec![tracking.label().to_string()];
+    if let Some(spatial) = spatial {
+        // middle of the two speakers as the dashboard shows them: the
+        // spatializer azimuths are turned by 180° (see SpeakerMode::base_angles),
+        // positive = left
+        let side = |az: f64| -az.to_radians().sin();
+        let front = |az: f64| -az.to_radians().cos();
+        let pan = (side(spatial.left_az) + side(spatial.right_az)) / 2.0;
+        // steps of 10% so small head movements do not produce new lines
+        let percent = (pan.abs() * 10.0).round() * 10.0;
+        let mut position = match percent as u32 {
+            0 => "pan centered".to_string(),
+            p if pan > 0.0 => format!("pan {}% left", p),
+            p => format!("pan {}% right", p),
+        };
+        if front(spatial.left_az) + front(spatial.right_az) < 0.0 {
+            position.push_str(" behind");
+        }
+        parts.push(position);
+        let volume = (spatial.left_gain + spatial.right_gain) / 2.0;
+        parts.push(format!("volume {:.0}%", (volume * 20.0).round() * 5.0));
+    }
+    parts.extend(notes.iter().cloned());
+    parts.join(", ")
+}
+
+// raw mode, so lines need the carriage return
+fn print_line(text: &str) {
+    print!("{}\r\n", text);
+    stdout().flush().ok();
+}
@@ -0,0 +1,52 @@
+// child process helpers shared by everything that runs an external tool for
+// as long as spatial-track does (pw-record, the filter-chain client, ...).
+
+use std::process::Child;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// SIGTERM, then SIGKILL if it is still running after `timeout`; reaped either way
+pub fn stop_child(child: &mut Child, timeout: Duration) {
+    if !matches!(child.try_wait(), Ok(None)) {
+        return;
+    }
+    // the child was just seen running, so it isn't reaped and its pid can't
+    // have been reused
+    stop(child, child.id() as libc::pid_t, timeout);
+}
+
+// the same for a child spawned with process_group(0), signalling its whole
+// group; only the child itself is waited for
+pub fn stop_group(child: &mut Child, timeout: Duration) {
+    // a group id is not reused while any member is left, and the leader isn't
+    // reaped before stop waits for it
+    stop(child, -(child.id() as libc::pid_t), timeout);
+}
+
+// pid as for kill(2): a process, or a group when negative
+fn stop(child: &mut Child, pid: libc::pid_t, timeout: Duration) {
+    // SAFETY: kill takes no pointers, and the callers make sure pid still
+    // names the child (or its group)
+    unsafe {
+        libc::kill(pid, libc::SIGTERM);
+    }
+    if !wait_timeout(child, timeout) {
+        // SAFETY: as above, the child is still running
+        unsafe {
+            libc::kill(pid, libc::SIGKILL);
+        }
+    }
+    child.wait().ok();
+}
+
+// wait up to `timeout` for the child to exit on its own, true once it has
+pub fn wait_timeout(child: &mut Child, timeout: Duration) -> bool {
+    let started = Instant::now();
+    while matches!(child.try_wait(), Ok(None)) {
+        if started.elapsed() > timeout {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    true
+}
@@ -0,0 +1,90 @@
+// per profile, per input device settings that survive restarts: the
+// recentered straight-ahead pose, the applied sensitivity calibration, the
+// applied smoothing proposal and whether the output goes to headphones or
+// speakers.
+// records are keyed "<profile>@<input uri>", so the same person can keep
+// separate centers for a webcam and an imu, and several people can share a
+// machine with --profile.
+
+use std::io;
+
+use crate::calibration::Calibration;
+use crate::engine::Listening;
+use crate::state;
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+const CENTER_FILE: &str = "center";
+const CALIBRATION_FILE: &str = "calibration";
+const LISTENING_FILE: &str = "listening";
+const SMOOTHING_FILE: &str = "smoothing";
+
+pub struct Profile {
+    key: String,
+}
+
+impl Profile {
+    pub fn new(profile: &str, device: &str) -> Self {
+        Self { key: format!("{}@{}", profile, device) }
+    }
+
+    // raw yaw, pitch, roll treated as straight ahead
+    pub fn center(&self) -> Option<(f64, f64, f64)> {
+        match state::load_record(CENTER_FILE, &self.key)?.as_slice() {
+            &[yaw, pitch, roll] => Some((yaw, pitch, roll)),
+            _ => None,
+        }
+    }
+
+    pub fn save_center(&self, (yaw, pitch, roll): (f64, f64, f64)) -> io::Result<()> {
+        state::save_record(CENTER_FILE, &self.key, &[yaw, pitch, roll])
+    }
+
+    pub fn calibration(&self) -> Option<Calibration> {
+        match state::load_record(CALIBRATION_FILE, &self.key)?.as_slice() {
+            &[yaw_sensitivity, pitch_sensitivity] => Some(Calibration { yaw_sensitivity, pitch_sensitivity }),
+            _ => None,
+        }
+    }
+
+    pub fn save_calibration(&self, c: Calibration) -> io::Result<()> {
+        state::save_record(CALIBRATION_FILE, &self.key, &[c.yaw_sensitivity, c.pitch_sensitivity])
+    }
+
+    // ema factor per axis: yaw, pitch, roll
+    pub fn smoothing(&self) -> Option<[f64; 3]> {
+        match state::load_record(SMOOTHING_FILE, &self.key)?.as_slice() {
+            &[yaw, pitch, roll] => Some([yaw, pitch, roll]),
+            _ => None,
+        }
+    }
+
+    pub fn save_smoothing(&self, factors: [f64; 3]) -> io::Result<()> {
+        state::save_record(SMOOTHING_FILE, &self.key, &factors)
+    }
+
+    // records only hold numbers: 0 = headphones, 1 = speakers
+    pub fn listening(&self) -> Option<Listening> {
+        match state::load_record(LISTENING_FILE, &self.key)?.as_slice() {
+            [0.0] => Some(Listening::Headphones),
+            [1.0] => Some(Listening::Speakers),
+            _ => None,
+        }
+    }
+
+    pub fn save_listening(&self, listening: Listening) -> io::Result<()> {
+        let mode = match listening {
+            Listening::Headphones => 0.0,
+            Listening::Speakers => 1.0,
+        };
+        state::save_record(LISTENING_FILE, &self.key, &[mode])
+    }
+
+    // forget everything stored for this profile and device
+    pub fn reset(&self) -> io::Result<()> {
+        state::remove_record(CENTER_FILE, &self.key)?;
+        state::remove_record(CALIBRATION_FILE, &self.key)?;
+        state::remove_record(SMOOTHING_FILE, &self.key)?;
+        state::remove_record(LISTENING_FILE, &self.key)
+    }
+}
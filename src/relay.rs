@@ -0,0 +1,106 @@
+// opentrack relay: re-emit received poses to other udp listeners so a
+// flight sim (or a second opentrack) keeps getting head tracking while
+// spatial-track owns port 4242.
+
+use std::fmt;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use crate::sink::{Frame, OutputSink};
+
+// opentrack "UDP over network" packet: six little-endian f64
+// [x, y, z, yaw, pitch, roll], translations in cm, angles in degrees
+pub const OPENTRACK_PACKET_SIZE: usize = 48;
+
+pub fn decode_pose(packet: &[u8; OPENTRACK_PACKET_SIZE]) -> [f64; 6] {
+    let mut pose = [0.0; 6];
+    for (value, chunk) in pose.iter_mut().zip(packet.chunks_exact(8)) {
+        *value = f64::from_le_bytes(chunk.try_into().unwrap_or_default());
+    }
+    pose
+}
+
+pub fn encode_pose(pose: &[f64; 6]) -> [u8; OPENTRACK_PACKET_SIZE] {
+    let mut packet = [0u8; OPENTRACK_PACKET_SIZE];
+    for (chunk, value) in packet.chunks_exact_mut(8).zip(pose) {
+        chunk.copy_from_slice(&value.to_le_bytes());
+    }
+    packet
+}
+
+pub struct Relay {
+    // one socket per address family the targets use
+    socket_v4: Option<UdpSocket>,
+    socket_v6: Option<UdpSocket>,
+    targets: Vec<SocketAddr>,
+    // forward the smoothed, recentered pose instead of the raw one
+    smoothed: bool,
+    pub sent: u64,
+    pub errors: u64,
+}
+
+impl Relay {
+    // targets are "host:port" strings, resolved once at startup
+    pub fn new(targets: &[String], smoothed: bool) -> Result<Self, String> {
+        let mut resolved = Vec::with_capacity(targets.len());
+        for target in targets {
+            let addr = target
+                .to_socket_addrs()
+                .map_err(|e| format!("invalid relay target '{}': {}", target, e))?
+                .next()
+                .ok_or_else(|| format!("relay target '{}' did not resolve", target))?;
+            resolved.push(addr);
+        }
+
+        let socket_v4 = bind_for(&resolved, "0.0.0.0:0", SocketAddr::is_ipv4)?;
+        let socket_v6 = bind_for(&resolved, "[::]:0", SocketAddr::is_ipv6)?;
+
+        Ok(Self { socket_v4, socket_v6, targets: resolved, smoothed, sent: 0, errors: 0 })
+    }
+
+    pub fn targets(&self) -> &[SocketAddr] {
+        &self.targets
+    }
+
+    // send one packet to every target, never blocks the main loop
+    pub fn send_packet(&mut self, packet: &[u8]) {
+        for target in &self.targets {
+            let socket = if target.is_ipv4() { &self.socket_v4 } else { &self.socket_v6 };
+            match socket.as_ref().map(|socket| socket.send_to(packet, target)) {
+                Some(Ok(_)) => self.sent += 1,
+                _ => self.errors += 1,
+            }
+        }
+    }
+}
+
+// a socket on `any` if some target is of that family
+fn bind_for(targets: &[SocketAddr], any: &str, family: fn(&SocketAddr) -> bool) -> Result<Option<UdpSocket>, String> {
+    if !targets.iter().any(family) {
+        return Ok(None);
+    }
+    let socket = UdpSocket::bind(any).map_err(|e| format!("Failed to bind relay socket: {}", e))?;
+    socket.set_nonblocking(true).ok();
+    Ok(Some(socket))
+}
+
+impl OutputSink for Relay {
+    fn name(&self) -> &'static str {
+        "relay"
+    }
+
+    fn send(&mut self, frame: &Frame) {
+        let mut pose = frame.pose.to_array();
+        if self.smoothed {
+            pose[3..].copy_from_slice(&frame.smoothed);
+        }
+        self.send_packet(&encode_pose(&pose));
+    }
+
+    fn status(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\x1B[1;37mRelay:\x1B[0m → {}", self.targets[0])?;
+        if self.targets.len() > 1 {
+            write!(f, " (+{})", self.targets.len() - 1)?;
+        }
+        write!(f, "  sent \x1B[1;37m{}\x1B[0m  err {}", self.sent, self.errors)
+    }
+}
@@ -0,0 +1,143 @@
+// rerun.io logging for tuning the filters offline: raw vs smoothed
+// orientation, speaker pan and pipewire latency on a proper timeline.
+//
+// the rerun sdk is a very large dependency, so instead of linking it we run
+// scripts/rerun_sink.py (embedded at build time) with python3 and feed it
+// plain text records over a pipe. the pipe is non-blocking: if python or the
+// viewer falls behind, records are dropped instead of stalling the tracker.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::os::fd::AsRawFd;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::engine::SpatialState;
+use crate::process::{stop_child, wait_timeout};
+use crate::sink::{Frame, OutputSink};
+
+const SINK_SCRIPT: &str = include_str!("../scripts/rerun_sink.py");
+// how long the script gets to flush a recording after its input closes, and
+// then to exit on SIGTERM
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(3);
+const STOP_TIMEOUT: Duration = Duration::from_secs(1);
+
+pub struct RerunSink {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    start: Instant,
+    line: String,
+    pub save_path: Option<String>,
+    pub logged: u64,
+    pub dropped: u64,
+}
+
+impl RerunSink {
+    // opens a live viewer, or writes a .rrd recording when save_path is set
+    pub fn spawn(save_path: Option<&str>) -> Result<Self, String> {
+        let mut cmd = Command::new("python3");
+        cmd.arg("-c").arg(SINK_SCRIPT);
+        if let Some(path) = save_path {
+            cmd.arg(path);
+        }
+        let mut child = cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to start rerun sink (python3): {}", e))?;
+
+        let stdin = child.stdin.take();
+        if let Some(ref pipe) = stdin {
+            let fd = pipe.as_raw_fd();
+            // SAFETY: fcntl takes no pointers here and fd is the open pipe owned by stdin
+            unsafe {
+                let flags = libc::fcntl(fd, libc::F_GETFL);
+                libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK);
+            }
+        }
+
+        Ok(Self {
+            child,
+            stdin,
+            start: Instant::now(),
+            line: String::with_capacity(128),
+            save_path: save_path.map(str::to_string),
+            logged: 0,
+            dropped: 0,
+        })
+    }
+
+    // false once python exited, e.g. because rerun-sdk is not installed
+    pub fn is_running(&self) -> bool {
+        self.stdin.is_some()
+    }
+
+    pub fn log_pose(&mut self, raw: [f64; 3], smoothed: [f64; 3]) {
+        self.line.clear();
+        self.line.push_str(&format!(
+            "pose {:.4} {:.3} {:.3} {:.3} {:.3} {:.3} {:.3}\n",
+            self.start.elapsed().as_secs_f64(),
+            raw[0], raw[1], raw[2], smoothed[0], smoothed[1], smoothed[2]
+        ));
+        self.write_line();
+    }
+
+    pub fn log_audio(&mut self, spatial: &SpatialState, latency_ms: f64) {
+        self.line.clear();
+        self.line.push_str(&format!(
+            "audio {:.4} {:.2} {:.2} {:.2} {:.3}\n",
+            self.start.elapsed().as_secs_f64(),
+            spatial.left_az, spatial.right_az, spatial.elevation, latency_ms
+        ));
+        self.write_line();
+    }
+
+    fn write_line(&mut self) {
+        let Some(ref mut stdin) = self.stdin else { return };
+        // a record either goes in whole or not at all (pipe writes up to
+        // PIPE_BUF are atomic), so a full pipe never leaves half a line
+        match stdin.write(self.line.as_bytes()) {
+            Ok(n) if n == self.line.len() => self.logged += 1,
+            Ok(_) => self.dropped += 1,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => self.dropped += 1,
+            Err(_) => {
+                // broken pipe: python is gone
+                self.stdin = None;
+                self.child.try_wait().ok();
+            }
+        }
+    }
+}
+
+impl OutputSink for RerunSink {
+    fn name(&self) -> &'static str {
+        "rerun"
+    }
+
+    fn send(&mut self, frame: &Frame) {
+        let pose = frame.pose;
+        self.log_pose([pose.yaw, pose.pitch, pose.roll], frame.smoothed);
+        self.log_audio(frame.spatial, frame.latency_ms);
+    }
+
+    fn status(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let target = self.save_path.as_deref().unwrap_or("viewer");
+        write!(f, "\x1B[1;37mRerun:\x1B[0m → {}  ", target)?;
+        if self.is_running() {
+            write!(f, "logged \x1B[1;37m{}\x1B[0m  dropped {}", self.logged, self.dropped)
+        } else {
+            f.write_str("\x1B[1;31m✗ sink exited\x1B[0m (pip install rerun-sdk)")
+        }
+    }
+}
+
+impl Drop for RerunSink {
+    fn drop(&mut self) {
+        // closing stdin lets the script flush the recording and exit
+        self.stdin = None;
+        if !wait_timeout(&mut self.child, FLUSH_TIMEOUT) {
+            stop_child(&mut self.child, STOP_TIMEOUT);
+        }
+    }
+}
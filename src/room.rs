@@ -0,0 +1,50 @@
+// room panning: several output devices placed around the listener (desk
+// speakers in front, a bookshelf pair behind, ...) all play at once and the
+// volume moves between them with head yaw, so the sound follows where you
+// face. unlike output zones nothing is switched, every sink gets a share.
+//
+// each sink's share is a cardioid around its direction, (1 + cos)/2 raised to
+// ROOM_FOCUS, normalized so the total power stays constant while turning.
+//
+// yaw follows the engine convention: positive = looking left.
+
+use crate::engine::wrap_degrees;
+
+// higher = sharper focus on the sink you face
+pub const ROOM_FOCUS: f64 = 2.0;
+
+#[derive(Clone)]
+pub struct RoomZone {
+    // direction of the sink from the listening position, degrees
+    pub azimuth: f64,
+    pub sink: String,
+}
+
+impl RoomZone {
+    // "<azimuth>=<sink node name>"
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid room zone '{}', expected <degrees>=<sink>", spec);
+        let (azimuth, sink) = spec.split_once('=').ok_or_else(invalid)?;
+        let azimuth: f64 = azimuth.trim().parse().map_err(|_| invalid())?;
+        if !azimuth.is_finite() {
+            return Err(invalid());
+        }
+        if sink.is_empty() {
+            return Err(format!("room zone '{}' has no sink", spec));
+        }
+        Ok(Self { azimuth: wrap_degrees(azimuth), sink: sink.to_string() })
+    }
+}
+
+// volume of every zone for a head yaw
+pub fn zone_gains(yaw: f64, zones: &[RoomZone]) -> Vec<f64> {
+    let shares: Vec<f64> = zones
+        .iter()
+        .map(|z| ((1.0 + wrap_degrees(z.azimuth - yaw).to_radians().cos()) / 2.0).powf(ROOM_FOCUS))
+        .collect();
+    let power = shares.iter().map(|g| g * g).sum::<f64>().sqrt();
+    if power <= f64::EPSILON {
+        return shares;
+    }
+    shares.iter().map(|g| g / power).collect()
+}
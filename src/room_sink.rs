@@ -0,0 +1,170 @@
+// room panning output: one pw-loopback per --room-zone copies what is played
+// into the spatializer (the monitor of its input sink, so the plain stereo,
+// not the binaural mix) to that zone's device. the loopbacks' stream volumes
+// follow the head, see spatial_track::room for the gains. the loopbacks are
+// ours: restarted when they exit and stopped with us.
+
+use std::fmt;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use spatial_track::room::{zone_gains, RoomZone};
+use spatial_track::sink::{Frame, OutputSink};
+
+use crate::filter_chain::die_with_parent;
+use crate::pipewire::{find_spatializer_node, set_volume, NODE_SEARCH_INTERVAL};
+use crate::watchdog::Watchdog;
+
+// pw-loopback exits with pipewire, start it again after this long
+const RESTART_DELAY: Duration = Duration::from_secs(2);
+// volume steps smaller than this are not sent
+const GAIN_THRESHOLD: f64 = 0.005;
+
+struct Loopback {
+    // node.name of the playback stream, to find its id
+    node_name: String,
+    child: Option<Child>,
+    restart_at: Option<Instant>,
+    node_id: Option<String>,
+    gain: f64,
+    last_sent: Option<f64>,
+}
+
+pub struct RoomSink {
+    zones: Vec<RoomZone>,
+    source: String,
+    loopbacks: Vec<Loopback>,
+    last_search: Option<Instant>,
+    backend: Watchdog,
+}
+
+impl RoomSink {
+    // start a loopback from the `source` sink's monitor to every zone's sink
+    pub fn new(zones: &[RoomZone], source: &str) -> Result<Self, String> {
+        let mut loopbacks = Vec::new();
+        for (i, zone) in zones.iter().enumerate() {
+            let node_name = format!("spatial_track.room.{}", i);
+            let child = spawn_loopback(source, &node_name, &zone.sink)?;
+            loopbacks.push(Loopback {
+                node_name,
+                child: Some(child),
+                restart_at: None,
+                node_id: None,
+                gain: 1.0,
+                last_sent: None,
+            });
+        }
+        Ok(Self {
+            zones: zones.to_vec(),
+            source: source.to_string(),
+            loopbacks,
+            last_search: None,
+            backend: Watchdog::new(),
+        })
+    }
+}
+
+impl OutputSink for RoomSink {
+    fn name(&self) -> &'static str {
+        "room"
+    }
+
+    fn send(&mut self, frame: &Frame) {
+        let gains = zone_gains(frame.smoothed[0], &self.zones);
+        let now = frame.pose.time;
+        let ready = self.backend.ready(now);
+        for (loopback, gain) in self.loopbacks.iter_mut().zip(gains) {
+            loopback.gain = gain;
+            let Some(ref id) = loopback.node_id else { continue };
+            if !ready || !(frame.force || loopback.last_sent.is_none_or(|g| (g - gain).abs() > GAIN_THRESHOLD)) {
+                continue;
+            }
+            self.backend.track(set_volume(id, gain), now);
+            loopback.last_sent = Some(gain);
+        }
+    }
+
+    fn poll(&mut self, now: Instant) -> Option<(&'static str, String)> {
+        self.backend.poll(now);
+        let reconnect = self.backend.take_reconnect();
+        for (loopback, zone) in self.loopbacks.iter_mut().zip(&self.zones) {
+            if reconnect {
+                loopback.node_id = None;
+            }
+            if let Some(ref mut child) = loopback.child {
+                if let Ok(Some(_)) = child.try_wait() {
+                    loopback.child = None;
+                    loopback.node_id = None;
+                    loopback.restart_at = Some(now + RESTART_DELAY);
+                }
+            }
+            if loopback.restart_at.is_some_and(|t| now >= t) {
+                loopback.restart_at = None;
+                match spawn_loopback(&self.source, &loopback.node_name, &zone.sink) {
+                    Ok(child) => loopback.child = Some(child),
+                    Err(e) => {
+                        self.backend.last_error = Some(e);
+                        loopback.restart_at = Some(now + RESTART_DELAY);
+                    }
+                }
+            }
+        }
+
+        let missing = self.loopbacks.iter().any(|l| l.child.is_some() && l.node_id.is_none());
+        if !missing || self.last_search.is_some_and(|t| now.duration_since(t) < NODE_SEARCH_INTERVAL) {
+            return None;
+        }
+        self.last_search = Some(now);
+        for loopback in self.loopbacks.iter_mut().filter(|l| l.child.is_some() && l.node_id.is_none()) {
+            loopback.node_id = find_spatializer_node(&loopback.node_name);
+            // a fresh stream starts at full volume, push the gain right away
+            loopback.last_sent = None;
+        }
+        None
+    }
+
+    fn status(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let linked = self.loopbacks.iter().filter(|l| l.node_id.is_some()).count();
+        if linked == self.loopbacks.len() {
+            f.write_str("\x1B[1;32m✓ ROOM\x1B[0m")?;
+        } else {
+            write!(f, "\x1B[1;33m… ROOM\x1B[0m {}/{} linked", linked, self.loopbacks.len())?;
+        }
+        for (l, zone) in self.loopbacks.iter().zip(&self.zones) {
+            write!(f, "  {:.0}° \x1B[1;37m{:.2}\x1B[0m", zone.azimuth, l.gain)?;
+        }
+        Ok(())
+    }
+
+    fn error(&self) -> Option<Vec<String>> {
+        if !self.backend.is_down() {
+            return None;
+        }
+        let mut lines = vec![format!("room volume failed {} times in a row", self.backend.failures)];
+        if let Some(ref e) = self.backend.last_error {
+            lines.push(format!("\x1B[1;31m{}\x1B[0m", e.chars().take(54).collect::<String>()));
+        }
+        Some(lines)
+    }
+}
+
+impl Drop for RoomSink {
+    fn drop(&mut self) {
+        for child in self.loopbacks.iter_mut().filter_map(|l| l.child.as_mut()) {
+            child.kill().ok();
+            child.wait().ok();
+        }
+    }
+}
+
+fn spawn_loopback(source: &str, node_name: &str, sink: &str) -> Result<Child, String> {
+    let mut command = Command::new("pw-loopback");
+    command
+        .arg(format!("--capture-props=stream.capture.sink=true target.object={} node.passive=true", source))
+        .arg(format!("--playback-props=node.name={} target.object={}", node_name, sink))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    die_with_parent(&mut command);
+    command.spawn().map_err(|e| format!("Failed to run pw-loopback: {}", e))
+}
@@ -0,0 +1,423 @@
+// usb/uart orientation sensors that do their own sensor fusion, read
+// directly instead of through opentrack:
+//
+//   serial-imu://<device|auto>[?driver=bno055|witmotion][&baud=<n>][&mount=..]
+//
+// - bno055 (bosch, on adafruit-style breakouts behind a usb-uart adapter):
+//   register protocol over uart, switched to ndof fusion and polled for its
+//   quaternion at 100 hz
+// - witmotion (wt901 and friends): streams 11 byte packets on its own, the
+//   quaternion packet is used when enabled, the angle packet otherwise
+//
+// with `auto` the usb-serial ports are searched for known adapters by usb
+// vendor/product id, which also picks the driver unless one is given. a
+// background thread owns the port and reopens it after unplugging.
+// mount corrects a sensor that is not upright, see imu::Mounting.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::imu::{self, Mounting, Quaternion};
+use crate::input::{InputSource, Pose};
+
+const REOPEN_DELAY: Duration = Duration::from_secs(2);
+// a streaming sensor that sends nothing for this long is gone
+const SILENCE_TIMEOUT: Duration = Duration::from_secs(2);
+
+// bno055 uart protocol and registers (datasheet section 4.7 and 4.3)
+const BNO_START: u8 = 0xAA;
+const BNO_WRITE: u8 = 0x00;
+const BNO_READ: u8 = 0x01;
+const BNO_READ_OK: u8 = 0xBB;
+const BNO_STATUS: u8 = 0xEE;
+const BNO_WRITE_SUCCESS: u8 = 0x01;
+const BNO_CHIP_ID: u8 = 0x00;
+const BNO_CHIP_ID_VALUE: u8 = 0xA0;
+const BNO_QUATERNION: u8 = 0x20;
+const BNO_OPR_MODE: u8 = 0x3D;
+const BNO_PWR_MODE: u8 = 0x3E;
+const BNO_MODE_CONFIG: u8 = 0x00;
+const BNO_MODE_NDOF: u8 = 0x0C;
+const BNO_POLL_INTERVAL: Duration = Duration::from_millis(10);
+// the uart interface drops requests now and then (bus overrun), retry those
+const BNO_RETRIES: usize = 3;
+
+// witmotion packets: 0x55, type, 8 data bytes, checksum
+const WIT_HEADER: u8 = 0x55;
+const WIT_ANGLE: u8 = 0x53;
+const WIT_QUATERNION: u8 = 0x59;
+const WIT_PACKET_SIZE: usize = 11;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Driver {
+    Bno055,
+    WitMotion,
+}
+
+impl Driver {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "bno055" => Some(Driver::Bno055),
+            "witmotion" => Some(Driver::WitMotion),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Driver::Bno055 => "bno055",
+            Driver::WitMotion => "witmotion",
+        }
+    }
+
+    fn default_baud(&self) -> u32 {
+        match self {
+            Driver::Bno055 => 115200,
+            Driver::WitMotion => 9600,
+        }
+    }
+}
+
+// usb-uart adapters the sensors usually come with: vendor, product, driver
+const KNOWN_ADAPTERS: [(u16, u16, Driver); 4] = [
+    // ch340, most witmotion usb modules
+    (0x1a86, 0x7523, Driver::WitMotion),
+    // cp2102, witmotion bluetooth/usb combo adapters
+    (0x10c4, 0xea60, Driver::WitMotion),
+    // ftdi ft232r / ft231x, the usual breakout cable for a bno055
+    (0x0403, 0x6001, Driver::Bno055),
+    (0x0403, 0x6015, Driver::Bno055),
+];
+
+struct Settings {
+    // None = autodetect on every (re)open
+    device: Option<PathBuf>,
+    driver: Option<Driver>,
+    baud: Option<u32>,
+    mount: Mounting,
+}
+
+pub struct SerialImu {
+    uri: String,
+    poses: Receiver<Pose>,
+    status: Arc<Mutex<String>>,
+}
+
+impl SerialImu {
+    // `rest` is everything after "serial-imu://"
+    pub fn new(uri: &str, rest: &str) -> Result<Self, String> {
+        let (device, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let mut settings = Settings {
+            device: (device != "auto").then(|| PathBuf::from(device)),
+            driver: None,
+            baud: None,
+            mount: Mounting::default(),
+        };
+        for param in query.split('&').filter(|p| !p.is_empty()) {
+            match param.split_once('=') {
+                Some(("driver", name)) => {
+                    let driver = Driver::parse(name).ok_or_else(|| format!("unknown imu driver '{}' (bno055, witmotion)", name))?;
+                    settings.driver = Some(driver);
+                }
+                Some(("baud", baud)) => {
+                    let baud = baud.parse().ok().filter(|b| baud_constant(*b).is_some());
+                    settings.baud = Some(baud.ok_or_else(|| format!("unsupported baud rate in '{}'", uri))?);
+                }
+                Some(("mount", spec)) => {
+                    settings.mount = Mounting::parse(spec).ok_or_else(|| format!("invalid mount '{}'", spec))?
+                }
+                _ => return Err(format!("unknown serial-imu parameter '{}' in '{}'", param, uri)),
+            }
+        }
+        if settings.device.is_some() && settings.driver.is_none() {
+            return Err(format!("input '{}' needs a driver, e.g. {}?driver=bno055 (or use serial-imu://auto)", uri, uri));
+        }
+
+        let status = Arc::new(Mutex::new("searching".to_string()));
+        let (tx, poses) = mpsc::channel();
+        let thread_status = Arc::clone(&status);
+        thread::spawn(move || run(settings, tx, thread_status));
+        Ok(Self { uri: uri.to_string(), poses, status })
+    }
+}
+
+impl InputSource for SerialImu {
+    fn recv(&mut self, timeout: Duration) -> Result<Option<Pose>, String> {
+        let pose = if timeout.is_zero() {
+            match self.poses.try_recv() {
+                Ok(pose) => Some(pose),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => return Err("serial imu thread stopped".to_string()),
+            }
+        } else {
+            match self.poses.recv_timeout(timeout) {
+                Ok(pose) => Some(pose),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return Err("serial imu thread stopped".to_string()),
+            }
+        };
+        Ok(pose)
+    }
+
+    fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    fn describe(&self) -> String {
+        let status = self.status.lock().map(|s| s.clone()).unwrap_or_default();
+        format!("serial IMU ({})", status)
+    }
+}
+
+// open, read and forward poses until the receiver is gone
+fn run(settings: Settings, poses: Sender<Pose>, status: Arc<Mutex<String>>) {
+    let set_status = |text: String| {
+        if let Ok(mut s) = status.lock() {
+            *s = text;
+        }
+    };
+    loop {
+        let result = find_port(&settings).and_then(|(path, driver)| {
+            let baud = settings.baud.unwrap_or(driver.default_baud());
+            let mut port = open_port(&path, baud)?;
+            set_status(format!("{} on {}", driver.label(), path.display()));
+            let send = |q: Quaternion| match imu::to_euler(settings.mount.correct(q)) {
+                Some([yaw, pitch, roll]) => poses.send(Pose { translation: [0.0; 3], yaw, pitch, roll, time: Instant::now() }).is_ok(),
+                None => true,
+            };
+            match driver {
+                Driver::Bno055 => read_bno055(&mut port, send),
+                Driver::WitMotion => read_witmotion(&mut port, send),
+            }
+        });
+        match result {
+            // the input was dropped
+            Ok(()) => return,
+            Err(e) => set_status(e),
+        }
+        thread::sleep(REOPEN_DELAY);
+    }
+}
+
+// the configured device, or the first usb-serial port with a known adapter
+fn find_port(settings: &Settings) -> Result<(PathBuf, Driver), String> {
+    if let (Some(device), Some(driver)) = (&settings.device, settings.driver) {
+        return Ok((device.clone(), driver));
+    }
+    let mut ports: Vec<PathBuf> = fs::read_dir("/sys/class/tty")
+        .map_err(|e| format!("Failed to list serial ports: {}", e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("ttyUSB") || n.starts_with("ttyACM")))
+        .collect();
+    ports.sort();
+    for port in ports {
+        let Some((vendor, product)) = usb_ids(&port.join("device")) else { continue };
+        let known = KNOWN_ADAPTERS.iter().find(|(v, p, _)| *v == vendor && *p == product);
+        if let Some(&(_, _, driver)) = known {
+            let name = port.file_name().unwrap_or_default();
+            return Ok((Path::new("/dev").join(name), settings.driver.unwrap_or(driver)));
+        }
+    }
+    Err("no known usb imu adapter found".to_string())
+}
+
+// idVendor/idProduct of the usb device above a tty's sysfs device
+fn usb_ids(device: &Path) -> Option<(u16, u16)> {
+    let mut dir = fs::canonicalize(device).ok()?;
+    loop {
+        let read = |name: &str| {
+            let text = fs::read_to_string(dir.join(name)).ok()?;
+            u16::from_str_radix(text.trim(), 16).ok()
+        };
+        if let (Some(vendor), Some(product)) = (read("idVendor"), read("idProduct")) {
+            return Some((vendor, product));
+        }
+        if !dir.pop() {
+            return None;
+        }
+    }
+}
+
+fn baud_constant(baud: u32) -> Option<libc::speed_t> {
+    Some(match baud {
+        9600 => libc::B9600,
+        19200 => libc::B19200,
+        38400 => libc::B38400,
+        57600 => libc::B57600,
+        115200 => libc::B115200,
+        230400 => libc::B230400,
+        460800 => libc::B460800,
+        921600 => libc::B921600,
+        _ => return None,
+    })
+}
+
+// raw 8n1 at `baud`, reads return after 100ms without data
+fn open_port(path: &Path, baud: u32) -> Result<File, String> {
+    let port = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NOCTTY)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let speed = baud_constant(baud).ok_or_else(|| format!("unsupported baud rate {}", baud))?;
+    // SAFETY: termios is plain data, so all zeroes is a valid value for
+    // tcgetattr to fill in; tio lives on the stack for every call that gets a
+    // pointer to it, and the fd stays open as long as port
+    unsafe {
+        let mut tio: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(port.as_raw_fd(), &mut tio) != 0 {
+            return Err(format!("{} is not a serial port", path.display()));
+        }
+        libc::cfmakeraw(&mut tio);
+        libc::cfsetspeed(&mut tio, speed);
+        tio.c_cflag |= libc::CLOCAL | libc::CREAD;
+        tio.c_cc[libc::VMIN] = 0;
+        tio.c_cc[libc::VTIME] = 1;
+        if libc::tcsetattr(port.as_raw_fd(), libc::TCSANOW, &tio) != 0 {
+            return Err(format!("Failed to configure {}", path.display()));
+        }
+        libc::tcflush(port.as_raw_fd(), libc::TCIOFLUSH);
+    }
+    Ok(port)
+}
+
+// exactly buf.len() bytes, or an error once the port stays silent
+fn read_full<P: Read>(port: &mut P, buf: &mut [u8]) -> Result<(), String> {
+    let started = Instant::now();
+    let mut filled = 0;
+    while filled < buf.len() {
+        match port.read(&mut buf[filled..]) {
+            Ok(0) if started.elapsed() > SILENCE_TIMEOUT => return Err("sensor stopped answering".to_string()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(format!("Failed to read from sensor: {}", e)),
+        }
+    }
+    Ok(())
+}
+
+fn bno_request<P: Read + Write>(port: &mut P, request: &[u8]) -> Result<Vec<u8>, String> {
+    let mut last_error = String::new();
+    for _ in 0..BNO_RETRIES {
+        port.write_all(request).map_err(|e| format!("Failed to write to sensor: {}", e))?;
+        let mut header = [0u8; 2];
+        read_full(port, &mut header)?;
+        match header {
+            [BNO_READ_OK, len] => {
+                let mut data = vec![0u8; len as usize];
+                read_full(port, &mut data)?;
+                return Ok(data);
+            }
+            [BNO_STATUS, BNO_WRITE_SUCCESS] if request[1] == BNO_WRITE => return Ok(Vec::new()),
+            [BNO_STATUS, code] => last_error = format!("bno055 error status 0x{:02x}", code),
+            _ => last_error = "bno055 sent garbage, wrong baud rate?".to_string(),
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    Err(last_error)
+}
+
+// fusion on, then poll the quaternion (w, x, y, z as i16, 2^14 = 1)
+fn read_bno055<P: Read + Write>(port: &mut P, mut send: impl FnMut(Quaternion) -> bool) -> Result<(), String> {
+    let chip = bno_request(port, &[BNO_START, BNO_READ, BNO_CHIP_ID, 1])?;
+    if chip.first() != Some(&BNO_CHIP_ID_VALUE) {
+        return Err("not a bno055 (wrong chip id)".to_string());
+    }
+    bno_request(port, &[BNO_START, BNO_WRITE, BNO_OPR_MODE, 1, BNO_MODE_CONFIG])?;
+    thread::sleep(Duration::from_millis(25));
+    bno_request(port, &[BNO_START, BNO_WRITE, BNO_PWR_MODE, 1, 0x00])?;
+    bno_request(port, &[BNO_START, BNO_WRITE, BNO_OPR_MODE, 1, BNO_MODE_NDOF])?;
+    thread::sleep(Duration::from_millis(20));
+
+    loop {
+        let data = bno_request(port, &[BNO_START, BNO_READ, BNO_QUATERNION, 8])?;
+        if data.len() == 8 {
+            let c = |i: usize| i16::from_le_bytes([data[i * 2], data[i * 2 + 1]]) as f64 / 16384.0;
+            if !send([c(0), c(1), c(2), c(3)]) {
+                return Ok(());
+            }
+        }
+        thread::sleep(BNO_POLL_INTERVAL);
+    }
+}
+
+// packets as they stream in
+fn read_witmotion<P: Read>(port: &mut P, mut send: impl FnMut(Quaternion) -> bool) -> Result<(), String> {
+    let mut parser = WitMotionParser::new();
+    let mut chunk = [0u8; 128];
+    let mut last_data = Instant::now();
+    loop {
+        match port.read(&mut chunk) {
+            Ok(0) => {}
+            Ok(n) => {
+                if !parser.push(&chunk[..n], &mut send) {
+                    return Ok(());
+                }
+                last_data = Instant::now();
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(format!("Failed to read from sensor: {}", e)),
+        }
+        if last_data.elapsed() > SILENCE_TIMEOUT {
+            return Err("sensor stopped sending".to_string());
+        }
+    }
+}
+
+// cuts the witmotion byte stream into packets, resyncing after garbage. the
+// quaternion (i16, 2^15 = 1) is preferred over angles (roll, pitch, yaw as
+// i16, 2^15 = 180°) once the sensor sends it
+#[derive(Default)]
+pub struct WitMotionParser {
+    buf: Vec<u8>,
+    has_quaternion: bool,
+}
+
+impl WitMotionParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // hands each orientation completed by `data` to `send`, false once send
+    // returned false
+    pub fn push(&mut self, data: &[u8], mut send: impl FnMut(Quaternion) -> bool) -> bool {
+        self.buf.extend_from_slice(data);
+        while self.buf.len() >= WIT_PACKET_SIZE {
+            if self.buf[0] != WIT_HEADER {
+                self.buf.remove(0);
+                continue;
+            }
+            let packet: [u8; WIT_PACKET_SIZE] = self.buf[..WIT_PACKET_SIZE].try_into().unwrap_or_default();
+            let checksum = packet[..10].iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+            if checksum != packet[10] {
+                // a 0x55 inside the data, resync on the next one
+                self.buf.remove(0);
+                continue;
+            }
+            self.buf.drain(..WIT_PACKET_SIZE);
+
+            let value = |i: usize| i16::from_le_bytes([packet[2 + i * 2], packet[3 + i * 2]]) as f64 / 32768.0;
+            let orientation = match packet[1] {
+                WIT_QUATERNION => {
+                    self.has_quaternion = true;
+                    [value(0), value(1), value(2), value(3)]
+                }
+                WIT_ANGLE if !self.has_quaternion => imu::from_euler(value(2) * 180.0, value(1) * 180.0, value(0) * 180.0),
+                _ => continue,
+            };
+            if !send(orientation) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
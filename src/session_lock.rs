@@ -0,0 +1,116 @@
+// notice when nobody is at the desk. logind keeps a LockedHint (set by the
+// desktop's screen locker) and an IdleHint (set when the session went idle)
+// on every session. changes to both are announced with a
+// PropertiesChanged signal, but listening for it takes a d-bus client, which
+// we don't link; `loginctl show-session` is asked for them every
+// POLL_INTERVAL on a background thread instead. that is one short process
+// every couple of seconds, and a lock is noticed within POLL_INTERVAL, soon
+// enough for someone who just walked away from the desk.
+// while the session is locked or idle the caller bypasses the panner and
+// holds the speakers in front at full volume, and picks up the head again
+// once the session is back.
+
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Hints {
+    locked: bool,
+    idle: bool,
+}
+
+pub struct SessionWatcher {
+    pause_on_lock: bool,
+    pause_on_idle: bool,
+    hints: Receiver<Result<Hints, String>>,
+    pub locked: bool,
+    pub idle: bool,
+    pub paused: bool,
+    // false until loginctl answered once
+    pub known: bool,
+    pub error: Option<String>,
+}
+
+impl SessionWatcher {
+    pub fn new(pause_on_lock: bool, pause_on_idle: bool) -> Self {
+        // the session this process runs in, "auto" finds it from the process
+        // when it was started outside of one (e.g. under tmux)
+        let session = std::env::var("XDG_SESSION_ID").ok().filter(|id| !id.is_empty()).unwrap_or_else(|| "auto".to_string());
+        let (tx, hints) = mpsc::channel();
+        thread::spawn(move || loop {
+            if tx.send(query(&session)).is_err() {
+                return;
+            }
+            thread::sleep(POLL_INTERVAL);
+        });
+        Self { pause_on_lock, pause_on_idle, hints, locked: false, idle: false, paused: false, known: false, error: None }
+    }
+
+    // the new paused state when it changed, never blocks
+    pub fn poll(&mut self) -> Option<bool> {
+        loop {
+            match self.hints.try_recv() {
+                Ok(Ok(hints)) => {
+                    self.locked = hints.locked;
+                    self.idle = hints.idle;
+                    self.known = true;
+                    self.error = None;
+                }
+                // keep the last known state, a failed query does not unlock
+                Ok(Err(e)) => self.error = Some(e),
+                Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => break,
+            }
+        }
+        let paused = self.pause_on_lock && self.locked || self.pause_on_idle && self.idle;
+        if paused == self.paused {
+            return None;
+        }
+        self.paused = paused;
+        Some(paused)
+    }
+
+    // "locked", "idle" or "active" for the dashboard and events
+    pub fn label(&self) -> &'static str {
+        if self.locked {
+            "locked"
+        } else if self.idle {
+            "idle"
+        } else {
+            "active"
+        }
+    }
+}
+
+fn query(session: &str) -> Result<Hints, String> {
+    let output = Command::new("loginctl")
+        .args(["show-session", session, "-p", "LockedHint", "-p", "IdleHint"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| format!("Failed to run loginctl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("loginctl show-session {} failed", session));
+    }
+    parse_hints(&String::from_utf8_lossy(&output.stdout))
+}
+
+// "LockedHint=yes\nIdleHint=no\n"
+fn parse_hints(text: &str) -> Result<Hints, String> {
+    let mut locked = None;
+    let mut idle = None;
+    for line in text.lines() {
+        match line.trim().split_once('=') {
+            Some(("LockedHint", value)) => locked = Some(value == "yes"),
+            Some(("IdleHint", value)) => idle = Some(value == "yes"),
+            _ => {}
+        }
+    }
+    match (locked, idle) {
+        (Some(locked), Some(idle)) => Ok(Hints { locked, idle }),
+        _ => Err("loginctl did not report LockedHint/IdleHint".to_string()),
+    }
+}
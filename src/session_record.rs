@@ -0,0 +1,71 @@
+// session recording: the spatializer's output stream, i.e. exactly what
+// reached your ears, written to a wav or flac file by pw-record for as long
+// as spatial-track runs. handy for demos and for comparing pan law, width or
+// hrtf settings offline with the same tracker session.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::process::stop_child;
+
+pub const RECORD_FORMATS: [&str; 2] = ["wav", "flac"];
+
+// pw-record finishes the file header on SIGTERM, give it this long
+const STOP_TIMEOUT: Duration = Duration::from_secs(2);
+// the file size is a stat call, refreshed this often for the dashboard
+const SIZE_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct SessionRecorder {
+    pub path: PathBuf,
+    child: Option<Child>,
+    pub started: Instant,
+    // set once pw-record exited on its own
+    pub exit: Option<ExitStatus>,
+    // bytes written as of the last check
+    size: u64,
+    size_checked: Instant,
+}
+
+impl SessionRecorder {
+    // record the output of the `source` node to `path`, format by extension
+    pub fn start(source: &str, path: &Path) -> Result<Self, String> {
+        let child = Command::new("pw-record")
+            .args(["--target", source, "--media-name", "spatial-track session", "--channels", "2"])
+            .arg(path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to run pw-record: {}", e))?;
+        let now = Instant::now();
+        Ok(Self { path: path.to_path_buf(), child: Some(child), started: now, exit: None, size: 0, size_checked: now })
+    }
+
+    // notice pw-record exiting early (bad path, pipewire gone), never blocks
+    pub fn poll(&mut self, now: Instant) {
+        if now.duration_since(self.size_checked) >= SIZE_INTERVAL {
+            self.size = fs::metadata(&self.path).map_or(0, |m| m.len());
+            self.size_checked = now;
+        }
+        let Some(ref mut child) = self.child else { return };
+        if let Ok(Some(exit)) = child.try_wait() {
+            self.exit = Some(exit);
+            self.child = None;
+        }
+    }
+
+    // bytes written so far, at most SIZE_INTERVAL old
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl Drop for SessionRecorder {
+    fn drop(&mut self) {
+        if let Some(ref mut child) = self.child {
+            stop_child(child, STOP_TIMEOUT);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// output sinks: everything that consumes the computed head/audio state
+// (pipewire, relays, lights, streams, recorders). the main loop builds one
+// Frame per tracker pose and hands it to every enabled sink; each sink has
+// its own minimum interval so e.g. dmx can run slower than pipewire.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::engine::SpatialState;
+use crate::input::Pose;
+use crate::motion::Motion;
+
+pub struct Frame<'a> {
+    // pose as received from the tracker
+    pub pose: &'a Pose,
+    // smoothed, recentered yaw/pitch/roll
+    pub smoothed: [f64; 3],
+    // angular velocity and motion intensity of the tracked head
+    pub motion: Motion,
+    pub spatial: &'a SpatialState,
+    // settings changed, send even when the head did not move
+    pub force: bool,
+    // last measured audio backend latency, 0 when unknown
+    pub latency_ms: f64,
+}
+
+pub trait OutputSink {
+    // short name, used for --sink-rate and the dashboard
+    fn name(&self) -> &'static str;
+
+    fn send(&mut self, frame: &Frame);
+
+    // housekeeping once per loop iteration, may return an event
+    // (name, detail) to pass on to the other sinks
+    fn poll(&mut self, _now: Instant) -> Option<(&'static str, String)> {
+        None
+    }
+
+    // one-off events such as a recenter
+    fn event(&mut self, _name: &str, _detail: &str) {}
+
+    // one line for the dashboard, written straight into its row so a redraw
+    // doesn't allocate
+    fn status(&self, f: &mut fmt::Formatter) -> fmt::Result;
+
+    // lines for an error panel while the sink is failing
+    fn error(&self) -> Option<Vec<String>> {
+        None
+    }
+
+    // time the backend takes to apply a frame, for sinks that measure it
+    fn latency_ms(&self) -> Option<f64> {
+        None
+    }
+}
+
+struct Entry {
+    sink: Box<dyn OutputSink>,
+    interval: Duration,
+    last_sent: Option<Instant>,
+}
+
+#[derive(Default)]
+pub struct Sinks {
+    entries: Vec<Entry>,
+}
+
+impl Sinks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // interval 0 = every frame
+    pub fn add(&mut self, sink: Box<dyn OutputSink>, interval: Duration) {
+        self.entries.push(Entry { sink, interval, last_sent: None });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn send(&mut self, frame: &Frame) {
+        let now = frame.pose.time;
+        for entry in &mut self.entries {
+            let due = entry.last_sent.is_none_or(|t| now.duration_since(t) >= entry.interval);
+            if due || frame.force {
+                entry.sink.send(frame);
+                entry.last_sent = Some(now);
+            }
+        }
+    }
+
+    pub fn poll(&mut self, now: Instant) {
+        let mut events = Vec::new();
+        for entry in &mut self.entries {
+            if let Some(event) = entry.sink.poll(now) {
+                events.push(event);
+            }
+        }
+        for (name, detail) in events {
+            self.event(name, &detail);
+        }
+    }
+
+    pub fn event(&mut self, name: &str, detail: &str) {
+        for entry in &mut self.entries {
+            entry.sink.event(name, detail);
+        }
+    }
+
+    pub fn latency_ms(&self) -> Option<f64> {
+        self.entries.iter().find_map(|e| e.sink.latency_ms())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn OutputSink> {
+        self.entries.iter().map(|e| e.sink.as_ref())
+    }
+}
+
+// a sink's status line as something to format
+pub struct Status<'a>(pub &'a dyn OutputSink);
+
+impl fmt::Display for Status<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.status(f)
+    }
+}
@@ -0,0 +1,307 @@
+// smoothing analysis: measure the tracker and propose an ema factor per axis
+// instead of guessing one. first the head is held still, which shows how
+// much the tracker jitters and at which frequencies; then it is turned and
+// nodded on purpose, which shows how fast it actually moves. every candidate
+// factor is then run over both recordings: the lowest one that gets the
+// jitter under TARGET_JITTER wins, unless it lags more than MAX_LAG_MS
+// behind the movements, then the most smoothing within that lag is taken.
+
+use std::time::{Duration, Instant};
+
+use crate::engine::MAX_SMOOTHING_FACTOR;
+
+pub const STILL_TIME: Duration = Duration::from_secs(5);
+pub const MOVE_TIME: Duration = Duration::from_secs(10);
+
+// jitter left after smoothing (std dev, degrees) that is no longer heard
+const TARGET_JITTER: f64 = 0.05;
+// most lag behind a deliberate movement the proposal accepts
+const MAX_LAG_MS: f64 = 80.0;
+// more than this (std dev around the drift line, degrees) while holding
+// still means the head moved
+const MAX_STILL_NOISE: f64 = 2.0;
+// an axis slower than this (°/s) was not moved, its lag is estimated from
+// the packet rate instead of measured
+const MIN_MOVE_SPEED: f64 = 20.0;
+const FACTOR_STEP: f64 = 0.05;
+const MIN_SAMPLES: usize = 50;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Phase {
+    Still,
+    Moving,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AxisAnalysis {
+    // tracker noise while still: std dev in degrees and the frequency below
+    // which half of its power lies
+    pub noise: f64,
+    pub noise_hz: f64,
+    // jitter (degrees) and lag (ms) with the factor in use and the proposed one
+    pub current: (f64, f64),
+    pub factor: f64,
+    pub proposed: (f64, f64),
+    // the lag budget, not the jitter target, decided the factor
+    pub lag_limited: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SmoothingProposal {
+    // yaw, pitch, roll
+    pub axes: [AxisAnalysis; 3],
+}
+
+impl SmoothingProposal {
+    pub fn factors(&self) -> [f64; 3] {
+        self.axes.map(|a| a.factor)
+    }
+}
+
+pub struct SmoothingTuner {
+    phase: Option<(Phase, Instant)>,
+    still: Vec<(Instant, [f64; 3])>,
+    moving: Vec<(Instant, [f64; 3])>,
+    proposal: Option<SmoothingProposal>,
+    // instructions and results, for plain status and the dashboard
+    announcement: Option<String>,
+    pub last_event: Option<String>,
+}
+
+impl SmoothingTuner {
+    pub fn new() -> Self {
+        Self { phase: None, still: Vec::new(), moving: Vec::new(), proposal: None, announcement: None, last_event: None }
+    }
+
+    // (re)start with the hold still phase, dropping any previous proposal
+    pub fn start(&mut self, now: Instant) {
+        *self = Self { phase: Some((Phase::Still, now)), ..Self::new() };
+        self.announce(format!("smoothing analysis: hold your head still for {}s", STILL_TIME.as_secs()));
+    }
+
+    pub fn phase(&self) -> Option<Phase> {
+        self.phase.map(|(phase, _)| phase)
+    }
+
+    pub fn remaining(&self, now: Instant) -> Duration {
+        match self.phase {
+            Some((Phase::Still, t)) => STILL_TIME.saturating_sub(now.duration_since(t)),
+            Some((Phase::Moving, t)) => MOVE_TIME.saturating_sub(now.duration_since(t)),
+            None => Duration::ZERO,
+        }
+    }
+
+    pub fn proposal(&self) -> Option<SmoothingProposal> {
+        self.proposal
+    }
+
+    pub fn take_proposal(&mut self) -> Option<SmoothingProposal> {
+        self.proposal.take()
+    }
+
+    pub fn take_announcement(&mut self) -> Option<String> {
+        self.announcement.take()
+    }
+
+    // record a recentered pose; returns the proposal once both phases are
+    // done. `current` are the factors in use, to compare against
+    pub fn observe(&mut self, pose: [f64; 3], current: [f64; 3], now: Instant) -> Option<SmoothingProposal> {
+        let (phase, started) = self.phase?;
+        if pose.iter().all(|v| v.is_finite()) {
+            match phase {
+                Phase::Still => self.still.push((now, pose)),
+                Phase::Moving => self.moving.push((now, pose)),
+            }
+        }
+
+        match phase {
+            Phase::Still if now.duration_since(started) >= STILL_TIME => {
+                if self.still.len() < MIN_SAMPLES {
+                    return self.fail("not enough samples");
+                }
+                let residuals = self.still_residuals();
+                if residuals.iter().any(|r| std_dev(r) > MAX_STILL_NOISE) {
+                    return self.fail("moved while holding still");
+                }
+                self.phase = Some((Phase::Moving, now));
+                self.announce(format!("smoothing analysis: now turn and nod your head for {}s", MOVE_TIME.as_secs()));
+                None
+            }
+            Phase::Moving if now.duration_since(started) >= MOVE_TIME => {
+                self.phase = None;
+                if self.moving.len() < MIN_SAMPLES {
+                    return self.fail("not enough samples");
+                }
+                let proposal = self.propose(current);
+                let [y, p, r] = proposal.factors();
+                self.announce(format!(
+                    "smoothing analysis done, proposed yaw {:.2} pitch {:.2} roll {:.2}, press Y to apply",
+                    y, p, r
+                ));
+                self.last_event = None;
+                self.proposal = Some(proposal);
+                Some(proposal)
+            }
+            _ => None,
+        }
+    }
+
+    fn fail(&mut self, reason: &str) -> Option<SmoothingProposal> {
+        self.phase = None;
+        self.still.clear();
+        self.moving.clear();
+        self.announce(format!("smoothing analysis failed, {}, N retries", reason));
+        self.last_event = Some(reason.to_string());
+        None
+    }
+
+    fn announce(&mut self, text: String) {
+        self.announcement = Some(text);
+    }
+
+    // the still recording per axis without its drift line, which is what
+    // the filter would have to remove
+    fn still_residuals(&self) -> [Vec<f64>; 3] {
+        let t0 = self.still[0].0;
+        let xs: Vec<f64> = self.still.iter().map(|(t, _)| t.duration_since(t0).as_secs_f64()).collect();
+        std::array::from_fn(|axis| {
+            let ys: Vec<f64> = self.still.iter().map(|(_, p)| p[axis]).collect();
+            detrend(&xs, &ys)
+        })
+    }
+
+    fn propose(&self, current: [f64; 3]) -> SmoothingProposal {
+        let residuals = self.still_residuals();
+        let interval = mean_interval(&self.still);
+        let t0 = self.moving[0].0;
+        let times: Vec<f64> = self.moving.iter().map(|(t, _)| t.duration_since(t0).as_secs_f64()).collect();
+        let axes = std::array::from_fn(|axis| {
+            let still = &residuals[axis];
+            let moving: Vec<f64> = self.moving.iter().map(|(_, p)| p[axis]).collect();
+            let measure = |k: f64| (jitter(still, k), lag_ms(&times, &moving, k).unwrap_or_else(|| model_lag_ms(interval, k)));
+
+            let candidates: Vec<(f64, (f64, f64))> = (0..)
+                .map(|i| i as f64 * FACTOR_STEP)
+                .take_while(|&k| k <= MAX_SMOOTHING_FACTOR)
+                .map(|k| (k, measure(k)))
+                .collect();
+            let (mut factor, mut proposed) = *candidates
+                .iter()
+                .find(|(_, (jitter, _))| *jitter <= TARGET_JITTER)
+                .unwrap_or(&candidates[candidates.len() - 1]);
+            let lag_limited = proposed.1 > MAX_LAG_MS;
+            if lag_limited {
+                (factor, proposed) =
+                    *candidates.iter().rev().find(|(_, (_, lag))| *lag <= MAX_LAG_MS).unwrap_or(&candidates[0]);
+            }
+            AxisAnalysis {
+                noise: std_dev(still),
+                noise_hz: median_frequency(still, interval),
+                current: measure(current[axis]),
+                factor,
+                proposed,
+                lag_limited,
+            }
+        });
+        SmoothingProposal { axes }
+    }
+}
+
+impl Default for SmoothingTuner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// the engine's ema, one step per pose
+fn ema(values: &[f64], k: f64) -> Vec<f64> {
+    let mut out = Vec::with_capacity(values.len());
+    let mut state = values[0];
+    for &v in values {
+        state = k * state + (1.0 - k) * v;
+        out.push(state);
+    }
+    out
+}
+
+// std dev of the smoothed still recording, skipping the first quarter
+// where the filter is still settling
+fn jitter(still: &[f64], k: f64) -> f64 {
+    let smoothed = ema(still, k);
+    std_dev(&smoothed[smoothed.len() / 4..])
+}
+
+// how far behind the filter runs during movement, as time: the angle it
+// trails by divided by the speed. None when the axis was hardly moved
+fn lag_ms(times: &[f64], values: &[f64], k: f64) -> Option<f64> {
+    let smoothed = ema(values, k);
+    let (mut behind, mut speed) = (0.0, 0.0);
+    for i in 1..values.len() {
+        let dt = times[i] - times[i - 1];
+        if dt <= 0.0 {
+            continue;
+        }
+        let v = (values[i] - values[i - 1]) / dt;
+        if v.abs() >= MIN_MOVE_SPEED {
+            behind += (values[i] - smoothed[i]).abs();
+            speed += v.abs();
+        }
+    }
+    (speed > 0.0).then(|| behind / speed * 1000.0)
+}
+
+// the ema's time constant at the packet rate
+fn model_lag_ms(interval: f64, k: f64) -> f64 {
+    interval * k / (1.0 - k) * 1000.0
+}
+
+fn mean_interval(samples: &[(Instant, [f64; 3])]) -> f64 {
+    let span = samples[samples.len() - 1].0.duration_since(samples[0].0).as_secs_f64();
+    span / (samples.len() - 1) as f64
+}
+
+// values minus their least squares line
+fn detrend(xs: &[f64], ys: &[f64]) -> Vec<f64> {
+    let n = xs.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = ys.iter().sum::<f64>() / n;
+    let var_x = xs.iter().map(|x| (x - mean_x).powi(2)).sum::<f64>();
+    let cov = xs.iter().zip(ys).map(|(x, y)| (x - mean_x) * (y - mean_y)).sum::<f64>();
+    let slope = if var_x > f64::EPSILON { cov / var_x } else { 0.0 };
+    xs.iter().zip(ys).map(|(x, y)| y - (mean_y + slope * (x - mean_x))).collect()
+}
+
+fn std_dev(values: &[f64]) -> f64 {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt()
+}
+
+// frequency (hz) below which half of the noise power lies, from a plain dft.
+// the poses are taken as evenly spaced, which they are close enough to
+fn median_frequency(values: &[f64], interval: f64) -> f64 {
+    let n = values.len();
+    if interval <= 0.0 {
+        return 0.0;
+    }
+    let power: Vec<f64> = (1..=n / 2)
+        .map(|k| {
+            let (mut re, mut im) = (0.0, 0.0);
+            for (i, v) in values.iter().enumerate() {
+                let angle = 2.0 * std::f64::consts::PI * (k * i) as f64 / n as f64;
+                re += v * angle.cos();
+                im -= v * angle.sin();
+            }
+            re * re + im * im
+        })
+        .collect();
+    let total: f64 = power.iter().sum();
+    let mut sum = 0.0;
+    for (i, p) in power.iter().enumerate() {
+        sum += p;
+        if sum >= total / 2.0 {
+            return (i + 1) as f64 / (n as f64 * interval);
+        }
+    }
+    0.0
+}
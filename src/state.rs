@@ -0,0 +1,85 @@
+// small persistent state files under $XDG_STATE_HOME/spatial-track
+// (falls back to ~/.local/state/spatial-track).
+// one record per line: "<key> <number> <number> ...", easy to inspect and edit by hand.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+pub fn state_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".local/state"),
+    };
+    Some(base.join("spatial-track"))
+}
+
+fn state_path(name: &str) -> Option<PathBuf> {
+    Some(state_dir()?.join(name))
+}
+
+// all records in a state file, missing file = no records
+pub fn load_records(name: &str) -> Vec<(String, Vec<f64>)> {
+    let Some(text) = state_path(name).and_then(|p| fs::read_to_string(p).ok()) else {
+        return Vec::new();
+    };
+
+    let mut records = Vec::new();
+    for line in text.lines() {
+        let mut parts = line.split_whitespace();
+        let Some(key) = parts.next() else { continue };
+        if key.starts_with('#') {
+            continue;
+        }
+        // skip lines that don't parse instead of failing the whole file
+        let values: Result<Vec<f64>, _> = parts.map(|v| v.parse::<f64>()).collect();
+        if let Ok(values) = values {
+            records.push((key.to_string(), values));
+        }
+    }
+    records
+}
+
+pub fn load_record(name: &str, key: &str) -> Option<Vec<f64>> {
+    load_records(name).into_iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+// insert or replace the record for `key`
+pub fn save_record(name: &str, key: &str, values: &[f64]) -> io::Result<()> {
+    let mut records = load_records(name);
+    records.retain(|(k, _)| k != key);
+    records.push((key.to_string(), values.to_vec()));
+    write_records(name, &records)
+}
+
+// drop the record for `key`, no-op when there is none
+pub fn remove_record(name: &str, key: &str) -> io::Result<()> {
+    let mut records = load_records(name);
+    let before = records.len();
+    records.retain(|(k, _)| k != key);
+    if records.len() == before {
+        return Ok(());
+    }
+    write_records(name, &records)
+}
+
+fn write_records(name: &str, records: &[(String, Vec<f64>)]) -> io::Result<()> {
+    let path = state_path(name).ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no HOME or XDG_STATE_HOME"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut text = String::new();
+    for (k, v) in records {
+        text.push_str(k);
+        for value in v {
+            text.push_str(&format!(" {}", value));
+        }
+        text.push('\n');
+    }
+
+    // write then rename so a crash never leaves a half-written file
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, text)?;
+    fs::rename(tmp, path)
+}
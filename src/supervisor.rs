@@ -0,0 +1,119 @@
+// tracker supervision: launch opentrack (or any tracker command) together
+// with spatial-track and keep it alive, so one command brings the whole
+// head-tracking audio stack up.
+//
+// the command runs through `sh -c` in its own process group, so quitting
+// takes down everything it started. when it exits it is restarted after a
+// backoff that doubles on every quick crash and resets once it stayed up.
+
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::process::stop_group;
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+// a run at least this long counts as healthy and resets the backoff
+const STABLE_RUN: Duration = Duration::from_secs(30);
+// how long the tracker gets to exit on SIGTERM before it is killed
+const STOP_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub enum TrackerStatus {
+    Running { pid: u32, since: Instant },
+    // waiting to restart; `exit` is None when spawning itself failed
+    Waiting { restart_at: Instant, exit: Option<ExitStatus> },
+}
+
+pub struct TrackerSupervisor {
+    command: String,
+    child: Option<Child>,
+    backoff: Duration,
+    pub status: TrackerStatus,
+    pub restarts: u32,
+    pub last_error: Option<String>,
+}
+
+impl TrackerSupervisor {
+    pub fn start(command: &str) -> Self {
+        let now = Instant::now();
+        let mut supervisor = Self {
+            command: command.to_string(),
+            child: None,
+            backoff: MIN_BACKOFF,
+            status: TrackerStatus::Waiting { restart_at: now, exit: None },
+            restarts: 0,
+            last_error: None,
+        };
+        supervisor.spawn(now);
+        supervisor
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    // check on the child and restart it when due, never blocks
+    pub fn poll(&mut self, now: Instant) {
+        match self.status {
+            TrackerStatus::Running { since, .. } => {
+                let Some(ref mut child) = self.child else { return };
+                let Ok(Some(exit)) = child.try_wait() else { return };
+                // anything it left running in its group goes with it
+                kill_group(child);
+                self.child = None;
+                if now.duration_since(since) >= STABLE_RUN {
+                    self.backoff = MIN_BACKOFF;
+                }
+                self.status = TrackerStatus::Waiting { restart_at: now + self.backoff, exit: Some(exit) };
+                self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+            }
+            TrackerStatus::Waiting { restart_at, .. } => {
+                if now >= restart_at {
+                    self.restarts += 1;
+                    self.spawn(now);
+                }
+            }
+        }
+    }
+
+    fn spawn(&mut self, now: Instant) {
+        let spawned = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .process_group(0)
+            .spawn();
+        match spawned {
+            Ok(child) => {
+                self.status = TrackerStatus::Running { pid: child.id(), since: now };
+                self.child = Some(child);
+                self.last_error = None;
+            }
+            Err(e) => {
+                self.last_error = Some(format!("Failed to launch tracker: {}", e));
+                self.status = TrackerStatus::Waiting { restart_at: now + self.backoff, exit: None };
+                self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+            }
+        }
+    }
+}
+
+impl Drop for TrackerSupervisor {
+    fn drop(&mut self) {
+        if let Some(ref mut child) = self.child {
+            stop_group(child, STOP_TIMEOUT);
+        }
+    }
+}
+
+// the whole group: sh and whatever it started
+fn kill_group(child: &Child) {
+    // SAFETY: kill takes no pointers; the child was spawned as the leader of its
+    // own group, and a group id is not reused while any member is left
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGTERM);
+    }
+}
@@ -0,0 +1,254 @@
+// tracker input over tcp, for networks that block or mangle udp (corporate
+// vpns, some vm setups). same pose payload as the udp input, framed as
+//
+//   u32 big-endian length | payload
+//
+// where the payload is the 48 byte opentrack packet (six little-endian f64).
+// zero-length frames are keep-alives. senders connect to us and may
+// reconnect at any time; a connection that has been silent for longer than
+// IDLE_TIMEOUT is treated as dead and dropped, so a sender that vanished
+// without closing does not linger. scripts/udp_to_tcp.py bridges a local
+// opentrack udp output to this input.
+//
+// with a pre-shared key (opentrack-noise://addr?key=<file>) the same framing
+// carries a Noise_NNpsk0 session instead: the sender opens with the first
+// handshake message, we answer with the second, and every frame after that,
+// keep-alives included, is an encrypted transport message. a wrong key or a
+// forged/replayed frame fails to decrypt and drops the connection.
+
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::os::fd::AsRawFd;
+use std::time::{Duration, Instant};
+
+use snow::{HandshakeState, TransportState};
+
+use crate::input::{InputSource, Pose};
+use crate::packet_layout::PacketLayout;
+use crate::relay::OPENTRACK_PACKET_SIZE;
+
+// senders should send at least a keep-alive this often
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(5);
+// anything bigger is not a pose and means the stream is out of sync
+const MAX_FRAME_SIZE: usize = 1024;
+const MAX_SENDERS: usize = 8;
+// poses read but not taken yet; a sender flooding faster than we take them
+// loses its oldest poses instead of growing the queue and the lag
+const MAX_QUEUED_POSES: usize = 64;
+pub const NOISE_PATTERN: &str = "Noise_NNpsk0_25519_ChaChaPoly_BLAKE2s";
+pub const KEY_SIZE: usize = 32;
+
+enum Session {
+    Plain,
+    Handshake(Box<HandshakeState>),
+    Transport(Box<TransportState>),
+}
+
+struct Sender {
+    stream: TcpStream,
+    addr: SocketAddr,
+    buf: Vec<u8>,
+    last_seen: Instant,
+    session: Session,
+}
+
+pub struct OpenTrackTcp {
+    uri: String,
+    addr: SocketAddr,
+    listener: TcpListener,
+    senders: Vec<Sender>,
+    queue: VecDeque<Pose>,
+    psk: Option<[u8; KEY_SIZE]>,
+    layout: PacketLayout,
+}
+
+impl OpenTrackTcp {
+    // plain frames without a key, noise sessions with one
+    pub fn bind(uri: &str, address: &str, psk: Option<[u8; KEY_SIZE]>, layout: PacketLayout) -> Result<Self, String> {
+        let addr = address
+            .to_socket_addrs()
+            .map_err(|e| format!("invalid input address '{}': {}", address, e))?
+            .next()
+            .ok_or_else(|| format!("input address '{}' did not resolve", address))?;
+        let listener = TcpListener::bind(addr).map_err(|e| format!("Failed to bind tcp input {}: {}", addr, e))?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        Ok(Self { uri: uri.to_string(), addr, listener, senders: Vec::new(), queue: VecDeque::new(), psk, layout })
+    }
+
+    pub fn senders(&self) -> impl Iterator<Item = SocketAddr> + '_ {
+        self.senders.iter().map(|s| s.addr)
+    }
+
+    // wait until the listener or a sender is readable, or the timeout passes
+    fn wait(&self, timeout: Duration) {
+        // accept() never lets more than MAX_SENDERS in, so the listener and
+        // every sender fit on the stack
+        let mut fds = [libc::pollfd { fd: -1, events: libc::POLLIN, revents: 0 }; MAX_SENDERS + 1];
+        let fds_in = std::iter::once(self.listener.as_raw_fd()).chain(self.senders.iter().map(|s| s.stream.as_raw_fd()));
+        let mut count = 0;
+        for (slot, fd) in fds.iter_mut().zip(fds_in) {
+            slot.fd = fd;
+            count += 1;
+        }
+        let ms = timeout.as_millis().min(i32::MAX as u128) as libc::c_int;
+        // SAFETY: fds is a live array of at least count pollfd structs for the duration of the call
+        unsafe {
+            libc::poll(fds.as_mut_ptr(), count as libc::nfds_t, ms);
+        }
+    }
+
+    fn accept(&mut self, now: Instant) {
+        while let Ok((stream, addr)) = self.listener.accept() {
+            if self.senders.len() >= MAX_SENDERS || stream.set_nonblocking(true).is_err() {
+                continue;
+            }
+            let session = match &self.psk {
+                Some(psk) => match snow::Builder::new(NOISE_PATTERN.parse().unwrap()).psk(0, psk).build_responder() {
+                    Ok(handshake) => Session::Handshake(Box::new(handshake)),
+                    Err(_) => continue,
+                },
+                None => Session::Plain,
+            };
+            stream.set_nodelay(true).ok();
+            enable_keepalive(&stream);
+            self.senders.push(Sender { stream, addr, buf: Vec::with_capacity(256), last_seen: now, session });
+        }
+    }
+
+    fn read_senders(&mut self, now: Instant) {
+        let (queue, layout) = (&mut self.queue, &self.layout);
+        self.senders.retain_mut(|sender| {
+            let alive = sender.read(now, layout, queue);
+            alive && now.duration_since(sender.last_seen) <= IDLE_TIMEOUT
+        });
+    }
+}
+
+impl Sender {
+    // false once the connection is closed or out of sync
+    fn read(&mut self, now: Instant, layout: &PacketLayout, queue: &mut VecDeque<Pose>) -> bool {
+        let mut chunk = [0u8; 1024];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return false,
+                Ok(n) => {
+                    self.buf.extend_from_slice(&chunk[..n]);
+                    self.last_seen = now;
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => return false,
+            }
+        }
+
+        let mut offset = 0;
+        while let Some(header) = self.buf.get(offset..offset + 4) {
+            let len = u32::from_be_bytes(header.try_into().unwrap_or_default()) as usize;
+            if len > MAX_FRAME_SIZE {
+                return false;
+            }
+            let Some(frame) = self.buf.get(offset + 4..offset + 4 + len) else { break };
+            let mut plain = [0u8; MAX_FRAME_SIZE];
+            let payload = match &mut self.session {
+                Session::Plain => frame,
+                Session::Handshake(handshake) => {
+                    let mut reply = [0u8; 128];
+                    let Ok(n) = handshake.read_message(frame, &mut plain).and_then(|_| handshake.write_message(&[], &mut reply)) else {
+                        return false;
+                    };
+                    if write_frame(&mut self.stream, &reply[..n]).is_err() {
+                        return false;
+                    }
+                    let Session::Handshake(handshake) = std::mem::replace(&mut self.session, Session::Plain) else { unreachable!() };
+                    match handshake.into_transport_mode() {
+                        Ok(transport) => self.session = Session::Transport(Box::new(transport)),
+                        Err(_) => return false,
+                    }
+                    &[][..]
+                }
+                Session::Transport(transport) => match transport.read_message(frame, &mut plain) {
+                    Ok(n) => &plain[..n],
+                    Err(_) => return false,
+                },
+            };
+            // other lengths (keep-alives, future payloads) are skipped, and so
+            // are values that aren't a pose
+            let packet = <&[u8; OPENTRACK_PACKET_SIZE]>::try_from(payload).ok();
+            if let Some([x, y, z, yaw, pitch, roll]) = packet.and_then(|packet| layout.decode(packet)) {
+                if queue.len() >= MAX_QUEUED_POSES {
+                    queue.pop_front();
+                }
+                queue.push_back(Pose { translation: [x, y, z], yaw, pitch, roll, time: now });
+            }
+            offset += 4 + len;
+        }
+        self.buf.drain(..offset);
+        true
+    }
+}
+
+impl InputSource for OpenTrackTcp {
+    fn recv(&mut self, timeout: Duration) -> Result<Option<Pose>, String> {
+        if self.queue.is_empty() && !timeout.is_zero() {
+            self.wait(timeout);
+        }
+        let now = Instant::now();
+        self.accept(now);
+        self.read_senders(now);
+        Ok(self.queue.pop_front())
+    }
+
+    fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    fn describe(&self) -> String {
+        let kind = if self.psk.is_some() { "TCP+Noise" } else { "TCP" };
+        match self.layout.name() {
+            "opentrack" => format!("OpenTrack {} {} ({} connected)", kind, self.addr, self.senders.len()),
+            layout => format!("OpenTrack {} {} ({} connected, {} layout)", kind, self.addr, self.senders.len(), layout),
+        }
+    }
+}
+
+// key files hold the 32 byte key as 64 hex digits, e.g. from
+// `head -c 32 /dev/urandom | xxd -p -c 64`
+pub fn load_key(path: &str) -> Result<[u8; KEY_SIZE], String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read key file {}: {}", path, e))?;
+    // bytes, not str slices: a multi-byte character would split a char boundary
+    let hex = text.trim().as_bytes();
+    let mut key = [0u8; KEY_SIZE];
+    if hex.len() != KEY_SIZE * 2 {
+        return Err(format!("key file {} must contain {} hex digits", path, KEY_SIZE * 2));
+    }
+    let digit = |b: u8| (b as char).to_digit(16).ok_or_else(|| format!("key file {} is not hex", path));
+    for (byte, pair) in key.iter_mut().zip(hex.chunks_exact(2)) {
+        *byte = (digit(pair[0])? * 16 + digit(pair[1])?) as u8;
+    }
+    Ok(key)
+}
+
+// a handshake reply is tiny, so a fresh socket always takes it in one go
+fn write_frame(stream: &mut TcpStream, payload: &[u8]) -> io::Result<()> {
+    let mut frame = Vec::with_capacity(4 + payload.len());
+    frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    frame.extend_from_slice(payload);
+    stream.write_all(&frame)
+}
+
+// let the kernel notice peers that disappeared without closing
+fn enable_keepalive(stream: &TcpStream) {
+    let on: libc::c_int = 1;
+    // SAFETY: the fd belongs to the live stream and the option value points to a
+    // c_int of the size passed
+    unsafe {
+        libc::setsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_KEEPALIVE,
+            &on as *const libc::c_int as *const libc::c_void,
+            std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+        );
+    }
+}
@@ -0,0 +1,146 @@
+// dashboard themes. the dashboard is written with the default palette's
+// escape codes; a theme swaps those for its own or drops them, and the ascii
+// theme also replaces emoji, box drawing and other symbols, for terminals,
+// logs and screen readers that choke on them. T cycles through them.
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Theme {
+    Default,
+    // darker colors for light terminal backgrounds
+    Light,
+    // bright colors only, no dim gray
+    HighContrast,
+    // no color, symbols kept
+    Mono,
+    // no color, plain ascii
+    Ascii,
+}
+
+pub const THEMES: [Theme; 5] = [Theme::Default, Theme::Light, Theme::HighContrast, Theme::Mono, Theme::Ascii];
+
+// default palette code -> theme code
+const LIGHT: [(&str, &str); 10] = [
+    ("1;96", "1;34"),
+    ("1;37", "1;30"),
+    ("90", "2"),
+    ("1;90", "2"),
+    ("1;31", "31"),
+    ("1;32", "32"),
+    ("1;33", "33"),
+    ("1;34", "34"),
+    ("1;35", "35"),
+    ("1;36", "36"),
+];
+const HIGH_CONTRAST: [(&str, &str); 10] = [
+    ("1;96", "1;97"),
+    ("1;37", "1;97"),
+    ("90", "37"),
+    ("1;90", "1;37"),
+    ("1;31", "1;91"),
+    ("1;32", "1;92"),
+    ("1;33", "1;93"),
+    ("1;34", "1;94"),
+    ("1;35", "1;95"),
+    ("1;36", "1;96"),
+];
+
+impl Theme {
+    pub fn parse(name: &str) -> Option<Self> {
+        THEMES.into_iter().find(|t| t.label() == name)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Theme::Default => "default",
+            Theme::Light => "light",
+            Theme::HighContrast => "high-contrast",
+            Theme::Mono => "mono",
+            Theme::Ascii => "ascii",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = THEMES.iter().position(|&t| t == self).unwrap_or(0);
+        THEMES[(i + 1) % THEMES.len()]
+    }
+
+    // None = no escape codes at all
+    fn palette(&self) -> Option<&'static [(&'static str, &'static str)]> {
+        match self {
+            Theme::Default => Some(&[]),
+            Theme::Light => Some(&LIGHT),
+            Theme::HighContrast => Some(&HIGH_CONTRAST),
+            Theme::Mono | Theme::Ascii => None,
+        }
+    }
+
+    // dashboard text written for the default theme, in this theme
+    pub fn paint(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        self.paint_into(text, &mut out);
+        out
+    }
+
+    // paint appended to `out`, for redraws that reuse their buffers
+    pub fn paint_into(&self, text: &str, out: &mut String) {
+        if *self == Theme::Default {
+            out.push_str(text);
+            return;
+        }
+        let palette = self.palette();
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            rest = &rest[c.len_utf8()..];
+            if c == '\x1B' {
+                // "\x1B[<code>m"
+                let end = rest.find('m').unwrap_or(rest.len());
+                let code = rest[..end].trim_start_matches('[');
+                rest = rest.get(end + 1..).unwrap_or("");
+                if let Some(palette) = palette {
+                    let mapped = palette.iter().find(|(from, _)| *from == code).map_or(code, |(_, to)| to);
+                    out.push_str("\x1B[");
+                    out.push_str(mapped);
+                    out.push('m');
+                }
+            } else if *self == Theme::Ascii && !c.is_ascii() {
+                match ascii_symbol(c) {
+                    Some(symbol) => out.push_str(symbol),
+                    // emoji icons are dropped together with their space
+                    None => rest = rest.strip_prefix(' ').unwrap_or(rest),
+                }
+            } else {
+                out.push(c);
+            }
+        }
+    }
+}
+
+// same width as the symbol where that matters for alignment, None for icons
+fn ascii_symbol(c: char) -> Option<&'static str> {
+    Some(match c {
+        '═' => "=",
+        '━' => "-",
+        '║' | '│' => "|",
+        '╔' | '╗' | '╚' | '╝' | '╠' | '╣' => "+",
+        '✓' => "+",
+        '✗' => "x",
+        '◆' => "o",
+        '▶' | '→' => ">",
+        '←' => "<",
+        '↑' | '⬆' => "^",
+        '↓' | '⬇' => "v",
+        '°' => "",
+        '…' => "...",
+        '░' => ".",
+        '▁' => "_",
+        '▂' => ",",
+        '▃' => ":",
+        '▄' => "-",
+        '▅' => "=",
+        '▆' => "+",
+        '▇' => "*",
+        '█' => "#",
+        '🎧' | '🧭' | '🔊' | '📐' | '📡' | '📈' | '🎵' | '🎛' | '🔌' | '🔍' | '⏳' | '⌨' | '⏺' => return None,
+        _ => "?",
+    })
+}
@@ -0,0 +1,347 @@
+// virtual input devices through /dev/uinput, so head tracking can drive
+// games that only understand a mouse or a joystick. talks to the kernel
+// directly with a few ioctls instead of pulling in an evdev crate.
+//
+// needs write access to /dev/uinput (e.g. a udev rule granting the input group).
+
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+
+use crate::sink::{Frame, OutputSink};
+
+// ioctl requests from linux/uinput.h
+const UI_DEV_CREATE: u64 = 0x5501;
+const UI_DEV_DESTROY: u64 = 0x5502;
+const UI_DEV_SETUP: u64 = 0x405C_5503;
+const UI_SET_EVBIT: u64 = 0x4004_5564;
+const UI_SET_KEYBIT: u64 = 0x4004_5565;
+const UI_SET_RELBIT: u64 = 0x4004_5566;
+const UI_SET_ABSBIT: u64 = 0x4004_5567;
+const UI_ABS_SETUP: u64 = 0x401C_5504;
+
+// event types and codes from linux/input-event-codes.h
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_REL: u16 = 0x02;
+const EV_ABS: u16 = 0x03;
+const SYN_REPORT: u16 = 0;
+const REL_X: u16 = 0x00;
+const REL_Y: u16 = 0x01;
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+const ABS_Z: u16 = 0x02;
+const BTN_LEFT: u16 = 0x110;
+const BTN_TRIGGER: u16 = 0x120;
+const BUS_VIRTUAL: u16 = 0x06;
+
+const UINPUT_PATH: &str = "/dev/uinput";
+const MAX_NAME_SIZE: usize = 80;
+
+// mouse counts per degree of head rotation
+pub const DEFAULT_MOUSE_GAIN: f64 = 20.0;
+pub const MIN_MOUSE_GAIN: f64 = 0.1;
+pub const MAX_MOUSE_GAIN: f64 = 1000.0;
+
+// joystick axis range, -AXIS_MAX..AXIS_MAX with 0 at center
+const AXIS_MAX: i32 = 32767;
+
+#[repr(C)]
+struct InputId {
+    bustype: u16,
+    vendor: u16,
+    product: u16,
+    version: u16,
+}
+
+#[repr(C)]
+struct UinputSetup {
+    id: InputId,
+    name: [u8; MAX_NAME_SIZE],
+    ff_effects_max: u32,
+}
+
+#[repr(C)]
+struct InputAbsinfo {
+    value: i32,
+    minimum: i32,
+    maximum: i32,
+    fuzz: i32,
+    flat: i32,
+    resolution: i32,
+}
+
+#[repr(C)]
+struct UinputAbsSetup {
+    code: u16,
+    absinfo: InputAbsinfo,
+}
+
+#[repr(C)]
+struct InputEvent {
+    time: libc::timeval,
+    kind: u16,
+    code: u16,
+    value: i32,
+}
+
+pub struct UinputDevice {
+    file: File,
+}
+
+impl UinputDevice {
+    // create a relative pointer device (reports as a plain mouse)
+    pub fn mouse(name: &str) -> io::Result<Self> {
+        let file = open_uinput()?;
+        let fd = file.as_raw_fd();
+        ioctl(fd, UI_SET_EVBIT, EV_KEY as libc::c_ulong)?;
+        // a button is needed for udev/libinput to classify it as a mouse
+        ioctl(fd, UI_SET_KEYBIT, BTN_LEFT as libc::c_ulong)?;
+        ioctl(fd, UI_SET_EVBIT, EV_REL as libc::c_ulong)?;
+        ioctl(fd, UI_SET_RELBIT, REL_X as libc::c_ulong)?;
+        ioctl(fd, UI_SET_RELBIT, REL_Y as libc::c_ulong)?;
+        create(&file, name, 0x0001)?;
+        Ok(Self { file })
+    }
+
+    // create an absolute joystick with these axes, each -AXIS_MAX..AXIS_MAX
+    pub fn joystick(name: &str, axes: &[u16]) -> io::Result<Self> {
+        let file = open_uinput()?;
+        let fd = file.as_raw_fd();
+        // a trigger button is needed for udev to classify it as a joystick
+        ioctl(fd, UI_SET_EVBIT, EV_KEY as libc::c_ulong)?;
+        ioctl(fd, UI_SET_KEYBIT, BTN_TRIGGER as libc::c_ulong)?;
+        ioctl(fd, UI_SET_EVBIT, EV_ABS as libc::c_ulong)?;
+        for &code in axes {
+            ioctl(fd, UI_SET_ABSBIT, code as libc::c_ulong)?;
+            let setup = UinputAbsSetup {
+                code,
+                absinfo: InputAbsinfo { value: 0, minimum: -AXIS_MAX, maximum: AXIS_MAX, fuzz: 0, flat: 0, resolution: 0 },
+            };
+            // SAFETY: setup is a valid struct uinput_abs_setup for the duration of the call
+            let rc = unsafe { libc::ioctl(fd, UI_ABS_SETUP as _, &setup as *const UinputAbsSetup) };
+            if rc < 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        create(&file, name, 0x0002)?;
+        Ok(Self { file })
+    }
+
+    fn emit(&mut self, kind: u16, code: u16, value: i32) -> io::Result<()> {
+        let event = InputEvent {
+            time: libc::timeval { tv_sec: 0, tv_usec: 0 },
+            kind,
+            code,
+            value,
+        };
+        // SAFETY: InputEvent is repr(C) plain data matching struct input_event
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                &event as *const InputEvent as *const u8,
+                std::mem::size_of::<InputEvent>(),
+            )
+        };
+        self.file.write_all(bytes)
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.emit(EV_SYN, SYN_REPORT, 0)
+    }
+
+    pub fn move_relative(&mut self, dx: i32, dy: i32) -> io::Result<()> {
+        if dx == 0 && dy == 0 {
+            return Ok(());
+        }
+        if dx != 0 {
+            self.emit(EV_REL, REL_X, dx)?;
+        }
+        if dy != 0 {
+            self.emit(EV_REL, REL_Y, dy)?;
+        }
+        self.sync()
+    }
+
+    // new absolute positions, (axis code, value)
+    pub fn move_absolute(&mut self, axes: &[(u16, i32)]) -> io::Result<()> {
+        for &(code, value) in axes {
+            self.emit(EV_ABS, code, value)?;
+        }
+        self.sync()
+    }
+}
+
+impl Drop for UinputDevice {
+    fn drop(&mut self) {
+        ioctl(self.file.as_raw_fd(), UI_DEV_DESTROY, 0).ok();
+    }
+}
+
+fn open_uinput() -> io::Result<File> {
+    OpenOptions::new()
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(UINPUT_PATH)
+}
+
+fn create(file: &File, name: &str, product: u16) -> io::Result<()> {
+    let mut setup = UinputSetup {
+        id: InputId { bustype: BUS_VIRTUAL, vendor: 0x5354, product, version: 1 },
+        name: [0; MAX_NAME_SIZE],
+        ff_effects_max: 0,
+    };
+    // keep the trailing nul
+    let len = name.len().min(MAX_NAME_SIZE - 1);
+    setup.name[..len].copy_from_slice(&name.as_bytes()[..len]);
+
+    let fd = file.as_raw_fd();
+    // SAFETY: setup is a valid struct uinput_setup for the duration of the call
+    let rc = unsafe { libc::ioctl(fd, UI_DEV_SETUP as _, &setup as *const UinputSetup) };
+    if rc < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    ioctl(fd, UI_DEV_CREATE, 0)
+}
+
+fn ioctl(fd: libc::c_int, request: u64, arg: libc::c_ulong) -> io::Result<()> {
+    // SAFETY: only used for the integer-argument uinput ioctls above
+    let rc = unsafe { libc::ioctl(fd, request as _, arg) };
+    if rc < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// ==============================================================================
+// MOUSE LOOK
+// ==============================================================================
+
+// turns absolute head angles into relative mouse motion: the camera follows
+// the head, so moving the mouse by the head's change since the last frame.
+// fractional counts are carried over so slow turns don't get lost.
+pub struct MouseLook {
+    device: UinputDevice,
+    pub gain: f64,
+    last: Option<(f64, f64)>,
+    remainder: (f64, f64),
+}
+
+impl MouseLook {
+    pub fn new(gain: f64) -> io::Result<Self> {
+        Ok(Self {
+            device: UinputDevice::mouse("spatial-track mouse look")?,
+            gain,
+            last: None,
+            remainder: (0.0, 0.0),
+        })
+    }
+
+    // feed the smoothed yaw/pitch in degrees (positive yaw = left, positive pitch = up)
+    pub fn update(&mut self, yaw: f64, pitch: f64) -> io::Result<()> {
+        let Some((last_yaw, last_pitch)) = self.last.replace((yaw, pitch)) else {
+            return Ok(());
+        };
+
+        // screen x grows to the right and y grows downward
+        let dx = -(yaw - last_yaw) * self.gain + self.remainder.0;
+        let dy = -(pitch - last_pitch) * self.gain + self.remainder.1;
+        let (ix, iy) = (dx.trunc(), dy.trunc());
+        self.remainder = (dx - ix, dy - iy);
+
+        self.device.move_relative(ix as i32, iy as i32)
+    }
+
+    // forget the previous pose so a jump (recenter, tracking loss) doesn't fling the cursor
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.remainder = (0.0, 0.0);
+    }
+}
+
+// ==============================================================================
+// JOYSTICK
+// ==============================================================================
+
+// default head angle for full deflection, yaw / pitch / roll
+pub const DEFAULT_JOYSTICK_RANGES: [f64; 3] = [90.0, 45.0, 45.0];
+pub const MAX_JOYSTICK_RANGE: f64 = 180.0;
+
+// how one head angle moves its joystick axis
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct JoystickAxis {
+    // degrees from center for full deflection
+    pub range: f64,
+    // response exponent: 1.0 = linear, >1 = finer near center
+    pub curve: f64,
+}
+
+impl JoystickAxis {
+    // -1..1, sign kept through the curve
+    pub fn deflection(&self, angle: f64) -> f64 {
+        let normalized = (angle / self.range).clamp(-1.0, 1.0);
+        if !normalized.is_finite() {
+            return 0.0;
+        }
+        normalized.signum() * normalized.abs().powf(self.curve)
+    }
+}
+
+// yaw, pitch and roll of the smoothed, recentered head as the X, Y and Z
+// axes of a virtual joystick, for sims that take head look from a joystick.
+// X grows when looking right and Y when looking down, like a stick pushed
+// that way; games can invert an axis in their own bindings.
+pub struct VirtualJoystick {
+    device: UinputDevice,
+    axes: [JoystickAxis; 3],
+    values: Option<[i32; 3]>,
+    error: Option<String>,
+    pub sent: u64,
+}
+
+impl VirtualJoystick {
+    pub fn new(axes: [JoystickAxis; 3]) -> Result<Self, String> {
+        let device = UinputDevice::joystick("spatial-track head joystick", &[ABS_X, ABS_Y, ABS_Z])
+            .map_err(|e| format!("Failed to create virtual joystick (needs write access to {}): {}", UINPUT_PATH, e))?;
+        Ok(Self { device, axes, values: None, error: None, sent: 0 })
+    }
+}
+
+impl OutputSink for VirtualJoystick {
+    fn name(&self) -> &'static str {
+        "joystick"
+    }
+
+    fn send(&mut self, frame: &Frame) {
+        let [yaw, pitch, roll] = frame.smoothed;
+        // positive yaw = looking left, positive pitch = looking up
+        let values = [(-yaw, 0), (-pitch, 1), (roll, 2)]
+            .map(|(angle, i)| (self.axes[i].deflection(angle) * AXIS_MAX as f64).round() as i32);
+        if self.values == Some(values) {
+            return;
+        }
+        let axes = [(ABS_X, values[0]), (ABS_Y, values[1]), (ABS_Z, values[2])];
+        match self.device.move_absolute(&axes) {
+            Ok(()) => {
+                self.values = Some(values);
+                self.sent += 1;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(format!("Failed to write joystick event: {}", e)),
+        }
+    }
+
+    fn status(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref e) = self.error {
+            return write!(f, "\x1B[1;37mJoystick:\x1B[0m \x1B[1;31m✗\x1B[0m {}", e);
+        }
+        let [x, y, z] = self.values.unwrap_or_default();
+        let percent = |v: i32| v as f64 / AXIS_MAX as f64 * 100.0;
+        write!(
+            f,
+            "\x1B[1;37mJoystick:\x1B[0m X {:>+4.0}%  Y {:>+4.0}%  Z {:>+4.0}%  sent \x1B[1;37m{}\x1B[0m",
+            percent(x), percent(y), percent(z), self.sent
+        )
+    }
+}
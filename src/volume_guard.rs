@@ -0,0 +1,74 @@
+// last line of defense before the speaker gains leave the process. whatever
+// the distance, leveling, zone or scripting math came up with, the applied
+// gain never goes above the ceiling, and it never rises by more than the
+// step from one update to the next: a bigger jump is spread over several
+// updates instead of reaching the ears at once. gains that are not numbers
+// are refused and the previous ones kept.
+
+use std::time::{Duration, Instant};
+
+use crate::engine::SpatialState;
+
+pub const DEFAULT_CEILING: f64 = 1.0;
+pub const DEFAULT_MAX_STEP_DB: f64 = 6.0;
+pub const MIN_MAX_STEP_DB: f64 = 0.5;
+pub const MAX_MAX_STEP_DB: f64 = 24.0;
+
+// rises are measured from at least this gain (-40 dB), so coming out of
+// silence ramps up as well
+const STEP_FLOOR: f64 = 0.01;
+// limiting again within this long is the same episode
+const EPISODE_GAP: Duration = Duration::from_secs(1);
+
+pub struct VolumeGuard {
+    ceiling: f64,
+    max_step: f64,
+    last: Option<[f64; 2]>,
+    // updates that were changed, and the last reason
+    pub limited: u64,
+    pub last_limit: Option<(&'static str, Instant)>,
+}
+
+impl VolumeGuard {
+    // ceiling as linear gain, at most 1; step in dB per update
+    pub fn new(ceiling: f64, max_step_db: f64) -> Self {
+        Self {
+            ceiling: ceiling.clamp(0.0, 1.0),
+            max_step: 10f64.powf(max_step_db / 20.0),
+            last: None,
+            limited: 0,
+            last_limit: None,
+        }
+    }
+
+    // clamp the speaker gains in place. returns the reason when this starts
+    // a new limiting episode, to report it once instead of every update
+    pub fn apply(&mut self, spatial: &mut SpatialState, now: Instant) -> Option<&'static str> {
+        let mut reason = None;
+        for (i, gain) in [&mut spatial.left_gain, &mut spatial.right_gain].into_iter().enumerate() {
+            let previous = self.last.map(|last| last[i]);
+            if !gain.is_finite() || *gain < 0.0 {
+                *gain = previous.unwrap_or(0.0);
+                reason = Some("invalid gain");
+            }
+            if *gain > self.ceiling {
+                *gain = self.ceiling;
+                reason = reason.or(Some("above ceiling"));
+            }
+            if let Some(previous) = previous {
+                let most = previous.max(STEP_FLOOR) * self.max_step;
+                if *gain > most {
+                    *gain = most;
+                    reason = reason.or(Some("sudden jump"));
+                }
+            }
+        }
+        self.last = Some([spatial.left_gain, spatial.right_gain]);
+
+        let reason = reason?;
+        self.limited += 1;
+        let new_episode = self.last_limit.is_none_or(|(_, at)| now.duration_since(at) > EPISODE_GAP);
+        self.last_limit = Some((reason, now));
+        new_episode.then_some(reason)
+    }
+}
@@ -0,0 +1,128 @@
+// watchdog for the pw-cli backend. pw-cli calls are fire-and-forget so a
+// frame never waits on pipewire, but their results still matter: after a
+// pipewire restart the cached node id is gone, and with missing permissions
+// every call fails. finished calls are reaped here (which also keeps them
+// from piling up as zombies), consecutive failures trigger a backoff and a
+// fresh node search, and the last error text is kept for the dashboard.
+
+use std::io::Read;
+use std::process::Child;
+use std::time::{Duration, Instant};
+
+// consecutive failures before the backend counts as down
+pub const FAILURE_THRESHOLD: u32 = 3;
+// a pw-cli call that takes longer than this is considered hung
+const CALL_TIMEOUT: Duration = Duration::from_secs(2);
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+pub struct Watchdog {
+    pending: Vec<(Child, Instant)>,
+    pub failures: u32,
+    pub last_error: Option<String>,
+    backoff: Duration,
+    retry_at: Option<Instant>,
+    reconnect: bool,
+}
+
+impl Watchdog {
+    pub fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            failures: 0,
+            last_error: None,
+            backoff: MIN_BACKOFF,
+            retry_at: None,
+            reconnect: false,
+        }
+    }
+
+    pub fn is_down(&self) -> bool {
+        self.failures >= FAILURE_THRESHOLD
+    }
+
+    // time left until the next attempt while backing off
+    pub fn retry_in(&self, now: Instant) -> Option<Duration> {
+        Some(self.retry_at?.saturating_duration_since(now))
+    }
+
+    // whether a new call may be made; false while backing off
+    pub fn ready(&mut self, now: Instant) -> bool {
+        match self.retry_at {
+            Some(t) if now < t => false,
+            Some(_) => {
+                self.retry_at = None;
+                true
+            }
+            None => true,
+        }
+    }
+
+    // true once after the backend went down: cached node ids are stale
+    pub fn take_reconnect(&mut self) -> bool {
+        std::mem::take(&mut self.reconnect)
+    }
+
+    pub fn track(&mut self, call: Result<Child, String>, now: Instant) {
+        match call {
+            Ok(child) => self.pending.push((child, now)),
+            Err(e) => self.failure(e, now),
+        }
+    }
+
+    // reap finished calls, never blocks
+    pub fn poll(&mut self, now: Instant) {
+        let mut i = 0;
+        while i < self.pending.len() {
+            let (ref mut child, started) = self.pending[i];
+            let result = match child.try_wait() {
+                // pw-cli reports some errors on stderr but still exits 0
+                Ok(Some(status)) => match error_text(child) {
+                    Some(e) => Some(Err(e)),
+                    None if status.success() => Some(Ok(())),
+                    None => Some(Err(format!("pw-cli {}", status))),
+                },
+                Ok(None) if now.duration_since(started) > CALL_TIMEOUT => {
+                    child.kill().ok();
+                    child.wait().ok();
+                    Some(Err("pw-cli timed out".to_string()))
+                }
+                Ok(None) => None,
+                Err(e) => Some(Err(format!("Failed to wait for pw-cli: {}", e))),
+            };
+            match result {
+                None => i += 1,
+                Some(result) => {
+                    self.pending.swap_remove(i);
+                    match result {
+                        Ok(()) => self.success(),
+                        Err(e) => self.failure(e, now),
+                    }
+                }
+            }
+        }
+    }
+
+    fn success(&mut self) {
+        self.failures = 0;
+        self.backoff = MIN_BACKOFF;
+        self.retry_at = None;
+    }
+
+    fn failure(&mut self, error: String, now: Instant) {
+        self.failures += 1;
+        self.last_error = Some(error);
+        if self.is_down() && self.retry_at.is_none() {
+            self.retry_at = Some(now + self.backoff);
+            self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+            self.reconnect = true;
+        }
+    }
+}
+
+// first non-empty line pw-cli printed to stderr
+fn error_text(child: &mut Child) -> Option<String> {
+    let mut text = String::new();
+    child.stderr.take()?.read_to_string(&mut text).ok()?;
+    text.lines().map(str::trim).find(|l| !l.is_empty()).map(str::to_string)
+}
@@ -0,0 +1,362 @@
+// zeromq PUB socket for streaming pose/audio/events to zmq pipelines.
+//
+// a minimal ZMTP 3.0 server (NULL security) written against the spec
+// (rfc.zeromq.org/spec/23) instead of linking libzmq: any SUB socket can
+// connect with tcp://host:port and subscribe by topic prefix.
+//
+// every message is two frames: [topic, json]. topics:
+//   pose   - raw and smoothed head angles, angular velocity, motion intensity
+//   audio  - the spatial state sent to pipewire
+//   events - one-off things like recenter or calibration
+
+use std::fmt::{self, Write as _};
+use std::io::{self, Read, Write};
+use std::mem;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::time::Instant;
+
+use crate::sink::{Frame, OutputSink};
+
+const GREETING_SIZE: usize = 64;
+// incoming frames are only subscriptions and handshake commands
+const MAX_FRAME_SIZE: u64 = 64 * 1024;
+// per subscriber backlog; messages are dropped (not queued) past this, like zmq's HWM
+const HIGH_WATER_MARK: usize = 256 * 1024;
+// a subscriber needs a handful of topic prefixes; one pushing more is dropped
+const MAX_SUBSCRIPTIONS: usize = 64;
+
+const FLAG_MORE: u8 = 0x01;
+const FLAG_LONG: u8 = 0x02;
+const FLAG_COMMAND: u8 = 0x04;
+
+#[derive(PartialEq)]
+enum PeerState {
+    Greeting,
+    Handshake,
+    Ready,
+}
+
+struct Peer {
+    stream: TcpStream,
+    state: PeerState,
+    inbuf: Vec<u8>,
+    outbuf: Vec<u8>,
+    subscriptions: Vec<Vec<u8>>,
+    dead: bool,
+}
+
+pub struct ZmqPublisher {
+    listener: TcpListener,
+    peers: Vec<Peer>,
+    pub dropped: u64,
+    // json of the message being published, kept so a frame doesn't allocate
+    payload: String,
+}
+
+impl ZmqPublisher {
+    // accepts "tcp://host:port" or plain "host:port"
+    pub fn bind(endpoint: &str) -> Result<Self, String> {
+        let addr = endpoint.strip_prefix("tcp://").unwrap_or(endpoint);
+        let listener = TcpListener::bind(addr).map_err(|e| format!("Failed to bind zmq endpoint {}: {}", endpoint, e))?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        Ok(Self { listener, peers: Vec::new(), dropped: 0, payload: String::with_capacity(512) })
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.listener.local_addr().ok()
+    }
+
+    pub fn subscribers(&self) -> usize {
+        self.peers.iter().filter(|p| p.state == PeerState::Ready).count()
+    }
+
+    // accept new subscribers and handle their handshakes/subscriptions, never blocks
+    pub fn poll(&mut self) {
+        while let Ok((stream, _)) = self.listener.accept() {
+            if stream.set_nonblocking(true).is_err() {
+                continue;
+            }
+            stream.set_nodelay(true).ok();
+            let mut peer = Peer {
+                stream,
+                state: PeerState::Greeting,
+                inbuf: Vec::new(),
+                outbuf: Vec::with_capacity(GREETING_SIZE + 32),
+                subscriptions: Vec::new(),
+                dead: false,
+            };
+            peer.outbuf.extend_from_slice(&greeting());
+            peer.outbuf.extend_from_slice(&ready_command());
+            self.peers.push(peer);
+        }
+
+        for peer in &mut self.peers {
+            peer.read();
+            peer.process();
+            peer.flush();
+        }
+        self.peers.retain(|p| !p.dead);
+    }
+
+    // whether anyone would get `topic`, so nobody subscribed costs no formatting
+    fn wanted(&self, topic: &str) -> bool {
+        self.peers.iter().any(|p| p.state == PeerState::Ready && p.subscribed(topic.as_bytes()))
+    }
+
+    pub fn publish(&mut self, topic: &str, payload: &str) {
+        for peer in &mut self.peers {
+            if peer.state != PeerState::Ready || !peer.subscribed(topic.as_bytes()) {
+                continue;
+            }
+            if peer.outbuf.len() > HIGH_WATER_MARK {
+                self.dropped += 1;
+                continue;
+            }
+            write_frame(&mut peer.outbuf, FLAG_MORE, topic.as_bytes());
+            write_frame(&mut peer.outbuf, 0, payload.as_bytes());
+            peer.flush();
+        }
+        self.peers.retain(|p| !p.dead);
+    }
+}
+
+impl OutputSink for ZmqPublisher {
+    fn name(&self) -> &'static str {
+        "zmq"
+    }
+
+    fn send(&mut self, frame: &Frame) {
+        let (raw, s, a) = (frame.pose, frame.smoothed, frame.spatial);
+        let (v, intensity) = (frame.motion.velocity, frame.motion.intensity);
+        let mut payload = mem::take(&mut self.payload);
+        if self.wanted("pose") {
+            payload.clear();
+            write!(
+                payload,
+                "{{\"raw\":{{\"yaw\":{:.3},\"pitch\":{:.3},\"roll\":{:.3}}},\"smoothed\":{{\"yaw\":{:.3},\"pitch\":{:.3},\"roll\":{:.3}}},\"velocity\":{{\"yaw\":{:.2},\"pitch\":{:.2},\"roll\":{:.2}}},\"intensity\":{:.3}}}",
+                raw.yaw, raw.pitch, raw.roll, s[0], s[1], s[2], v[0], v[1], v[2], intensity
+            )
+            .ok();
+            self.publish("pose", &payload);
+        }
+        if self.wanted("audio") {
+            payload.clear();
+            write!(
+                payload,
+                "{{\"left_az\":{:.2},\"right_az\":{:.2},\"elevation\":{:.2},\"radius\":{:.2},\"left_gain\":{:.3},\"right_gain\":{:.3},\"reverb\":{:.3},\"stereo_mix\":{:.3},\"crosstalk\":{:.3}}}",
+                a.left_az, a.right_az, a.elevation, a.radius, a.left_gain, a.right_gain, a.reverb_gain, a.stereo_mix,
+                a.crosstalk
+            )
+            .ok();
+            self.publish("audio", &payload);
+        }
+        self.payload = payload;
+    }
+
+    fn poll(&mut self, _now: Instant) -> Option<(&'static str, String)> {
+        ZmqPublisher::poll(self);
+        None
+    }
+
+    fn event(&mut self, name: &str, detail: &str) {
+        self.publish("events", &format!("{{\"event\":{},\"detail\":{}}}", json_string(name), json_string(detail)));
+    }
+
+    fn status(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("\x1B[1;37mZMQ PUB:\x1B[0m tcp://")?;
+        if let Some(addr) = self.local_addr() {
+            write!(f, "{}", addr)?;
+        }
+        write!(f, "  subs \x1B[1;37m{}\x1B[0m  dropped {}", self.subscribers(), self.dropped)
+    }
+}
+
+impl Peer {
+    fn subscribed(&self, topic: &[u8]) -> bool {
+        self.subscriptions.iter().any(|s| topic.starts_with(s))
+    }
+
+    fn read(&mut self) {
+        let mut chunk = [0u8; 1024];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => {
+                    self.dead = true;
+                    return;
+                }
+                Ok(n) => self.inbuf.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => {
+                    self.dead = true;
+                    return;
+                }
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        while !self.outbuf.is_empty() {
+            match self.stream.write(&self.outbuf) {
+                Ok(0) => {
+                    self.dead = true;
+                    return;
+                }
+                Ok(n) => {
+                    self.outbuf.drain(..n);
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => {
+                    self.dead = true;
+                    return;
+                }
+            }
+        }
+    }
+
+    fn process(&mut self) {
+        if self.state == PeerState::Greeting {
+            if self.inbuf.len() < GREETING_SIZE {
+                return;
+            }
+            let g = &self.inbuf[..GREETING_SIZE];
+            // signature, major version 3+, NULL mechanism
+            if g[0] != 0xFF || g[9] & 0x01 == 0 || g[10] < 3 || !g[12..32].starts_with(b"NULL") {
+                self.dead = true;
+                return;
+            }
+            self.inbuf.drain(..GREETING_SIZE);
+            self.state = PeerState::Handshake;
+        }
+
+        while let Some((flags, body)) = self.next_frame() {
+            if flags & FLAG_COMMAND != 0 {
+                self.handle_command(&body);
+            } else if self.state == PeerState::Ready {
+                // zmtp 3.0 subscriptions: 0x01 + topic / 0x00 + topic
+                match body.split_first() {
+                    Some((1, topic)) => self.subscribe(topic),
+                    Some((0, topic)) => self.unsubscribe(topic),
+                    _ => {}
+                }
+            }
+            if self.dead {
+                return;
+            }
+        }
+    }
+
+    fn handle_command(&mut self, body: &[u8]) {
+        let Some((&name_len, rest)) = body.split_first() else {
+            self.dead = true;
+            return;
+        };
+        let name_len = name_len as usize;
+        if rest.len() < name_len {
+            self.dead = true;
+            return;
+        }
+        let (name, data) = rest.split_at(name_len);
+        match name {
+            b"READY" => self.state = PeerState::Ready,
+            // zmtp 3.1 style subscriptions
+            b"SUBSCRIBE" => self.subscribe(data),
+            b"CANCEL" => self.unsubscribe(data),
+            b"ERROR" => self.dead = true,
+            _ => {}
+        }
+    }
+
+    fn subscribe(&mut self, topic: &[u8]) {
+        if self.subscriptions.len() >= MAX_SUBSCRIPTIONS {
+            self.dead = true;
+            return;
+        }
+        self.subscriptions.push(topic.to_vec());
+    }
+
+    fn unsubscribe(&mut self, topic: &[u8]) {
+        if let Some(i) = self.subscriptions.iter().position(|s| s == topic) {
+            self.subscriptions.remove(i);
+        }
+    }
+
+    // pop one complete frame off the input buffer
+    fn next_frame(&mut self) -> Option<(u8, Vec<u8>)> {
+        let flags = *self.inbuf.first()?;
+        let (header, size) = if flags & FLAG_LONG != 0 {
+            let bytes: [u8; 8] = self.inbuf.get(1..9)?.try_into().ok()?;
+            (9, u64::from_be_bytes(bytes))
+        } else {
+            (2, *self.inbuf.get(1)? as u64)
+        };
+        if size > MAX_FRAME_SIZE {
+            self.dead = true;
+            return None;
+        }
+        let end = header + size as usize;
+        if self.inbuf.len() < end {
+            return None;
+        }
+        let body = self.inbuf[header..end].to_vec();
+        self.inbuf.drain(..end);
+        Some((flags, body))
+    }
+}
+
+fn greeting() -> [u8; GREETING_SIZE] {
+    let mut g = [0u8; GREETING_SIZE];
+    g[0] = 0xFF;
+    g[9] = 0x7F;
+    g[10] = 3; // version 3.0
+    g[11] = 0;
+    g[12..16].copy_from_slice(b"NULL");
+    // as-server = 0 and filler stay zero
+    g
+}
+
+fn ready_command() -> Vec<u8> {
+    let mut body = Vec::with_capacity(32);
+    body.push(5);
+    body.extend_from_slice(b"READY");
+    let (name, value) = (b"Socket-Type", b"PUB");
+    body.push(name.len() as u8);
+    body.extend_from_slice(name);
+    body.extend_from_slice(&(value.len() as u32).to_be_bytes());
+    body.extend_from_slice(value);
+
+    let mut frame = Vec::with_capacity(body.len() + 2);
+    write_frame(&mut frame, FLAG_COMMAND, &body);
+    frame
+}
+
+fn write_frame(out: &mut Vec<u8>, flags: u8, body: &[u8]) {
+    if body.len() > 255 {
+        out.push(flags | FLAG_LONG);
+        out.extend_from_slice(&(body.len() as u64).to_be_bytes());
+    } else {
+        out.push(flags);
+        out.push(body.len() as u8);
+    }
+    out.extend_from_slice(body);
+}
+
+// a quoted json string; event details carry device names and error messages
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}